@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use apicize_lib::{
+    ApicizeError, Validated, ValidationState, Workspace, add_validation_error,
+    authorization::AuthorizationPlain, decrypt, encrypt, parameters::ParameterEncryption,
+    remove_validation_error,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aws_sigv4::AwsSigV4Parameters,
+    digest_auth::DigestParameters,
+    oauth2_grants::{
+        OAuth2DeviceParameters, OAuth2JwtBearerParameters, OAuth2PasswordParameters, TokenRequest,
+        build_client,
+    },
+    token_cache::TokenSource,
+    updates::AuthorizationUpdateType,
+};
+
+/// Validation error fields owned by extended authorizations
+const VALIDATED_FIELDS: [&str; 12] = [
+    "accessKeyId",
+    "secretAccessKey",
+    "region",
+    "service",
+    "username",
+    "deviceAuthorizationUrl",
+    "accessTokenUrl",
+    "clientId",
+    "signingCertificate",
+    "issuer",
+    "subject",
+    "assertionLifetime",
+];
+
+/// Authorization types implemented by Apicize rather than apicize_lib
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type")]
+pub enum ExtendedAuthorization {
+    /// AWS Signature Version 4
+    AwsSigV4(AwsSigV4Parameters),
+    /// HTTP Digest authentication
+    Digest(DigestParameters),
+    /// OAuth2 Device Authorization Grant
+    OAuth2Device(OAuth2DeviceParameters),
+    /// OAuth2 Resource Owner Password Credentials Grant
+    OAuth2Password(OAuth2PasswordParameters),
+    /// OAuth2 JWT Bearer Grant
+    OAuth2JwtBearer(OAuth2JwtBearerParameters),
+}
+
+impl ExtendedAuthorization {
+    /// Return default settings if the update type is implemented by Apicize
+    pub fn from_update_type(update_type: &AuthorizationUpdateType) -> Option<Self> {
+        match update_type {
+            AuthorizationUpdateType::AwsSigV4 => Some(ExtendedAuthorization::AwsSigV4(
+                AwsSigV4Parameters::default(),
+            )),
+            AuthorizationUpdateType::Digest => {
+                Some(ExtendedAuthorization::Digest(DigestParameters::default()))
+            }
+            AuthorizationUpdateType::OAuth2Device => Some(ExtendedAuthorization::OAuth2Device(
+                OAuth2DeviceParameters::default(),
+            )),
+            AuthorizationUpdateType::OAuth2Password => Some(ExtendedAuthorization::OAuth2Password(
+                OAuth2PasswordParameters::default(),
+            )),
+            AuthorizationUpdateType::OAuth2JwtBearer => Some(
+                ExtendedAuthorization::OAuth2JwtBearer(OAuth2JwtBearerParameters::default()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Return the update type corresponding to this authorization
+    pub fn get_update_type(&self) -> AuthorizationUpdateType {
+        match self {
+            ExtendedAuthorization::AwsSigV4(_) => AuthorizationUpdateType::AwsSigV4,
+            ExtendedAuthorization::Digest(_) => AuthorizationUpdateType::Digest,
+            ExtendedAuthorization::OAuth2Device(_) => AuthorizationUpdateType::OAuth2Device,
+            ExtendedAuthorization::OAuth2Password(_) => AuthorizationUpdateType::OAuth2Password,
+            ExtendedAuthorization::OAuth2JwtBearer(_) => AuthorizationUpdateType::OAuth2JwtBearer,
+        }
+    }
+
+    /// Return the authorization stored in the workbook to represent an extended
+    /// authorization, so that it can be selected and encrypted like any other.
+    /// Signature and digest authorizations use an empty header that is replaced
+    /// when dispatching requests. Token-based grants use a PKCE placeholder, so
+    /// that tokens stored in the OAuth2 token cache are sent when dispatching
+    /// requests
+    pub fn create_placeholder(&self, id: &str, name: &str) -> AuthorizationPlain {
+        match self {
+            ExtendedAuthorization::AwsSigV4(_) | ExtendedAuthorization::Digest(_) => {
+                AuthorizationPlain::ApiKey {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    header: "Authorization".to_string(),
+                    value: String::default(),
+                    validation_state: ValidationState::empty(),
+                    validation_warnings: None,
+                    validation_errors: None,
+                }
+            }
+            ExtendedAuthorization::OAuth2Device(_)
+            | ExtendedAuthorization::OAuth2Password(_)
+            | ExtendedAuthorization::OAuth2JwtBearer(_) => AuthorizationPlain::OAuth2Pkce {
+                id: id.to_string(),
+                name: name.to_string(),
+                authorize_url: String::default(),
+                access_token_url: String::default(),
+                client_id: String::default(),
+                scope: String::default(),
+                send_credentials_in_body: None,
+                token: None,
+                refresh_token: None,
+                expiration: None,
+                validation_state: ValidationState::empty(),
+                validation_warnings: None,
+                validation_errors: None,
+            },
+        }
+    }
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ExtendedAuthorization::AwsSigV4(parameters) => parameters.validate(),
+            ExtendedAuthorization::Digest(parameters) => parameters.validate(),
+            ExtendedAuthorization::OAuth2Device(parameters) => parameters.validate(),
+            ExtendedAuthorization::OAuth2Password(parameters) => parameters.validate(),
+            ExtendedAuthorization::OAuth2JwtBearer(parameters) => parameters.validate(),
+        }
+    }
+
+    /// Return a token request for grants that do not require user interaction,
+    /// using certificates and proxies from the workspace, and applying substitution values
+    pub fn create_token_request(
+        &self,
+        workspace: &Workspace,
+        subs: &HashMap<String, String>,
+    ) -> Option<Result<TokenRequest, String>> {
+        let (selected_certificate, selected_proxy) = match self {
+            ExtendedAuthorization::OAuth2Password(parameters) => {
+                (&parameters.selected_certificate, &parameters.selected_proxy)
+            }
+            ExtendedAuthorization::OAuth2JwtBearer(parameters) => {
+                (&parameters.selected_certificate, &parameters.selected_proxy)
+            }
+            _ => return None,
+        };
+        let client = match build_client(
+            workspace
+                .certificates
+                .get_optional(&selected_certificate.id),
+            workspace.proxies.get_optional(&selected_proxy.id),
+        ) {
+            Ok(client) => client,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(match self {
+            ExtendedAuthorization::OAuth2Password(parameters) => {
+                Ok(parameters.substitute(subs).token_request(client))
+            }
+            ExtendedAuthorization::OAuth2JwtBearer(parameters) => {
+                parameters.substitute(subs).token_request(
+                    client,
+                    workspace
+                        .certificates
+                        .get_optional(&parameters.signing_certificate.id),
+                )
+            }
+            _ => unreachable!(),
+        })
+    }
+
+    /// Return how a token can be renewed without user interaction, if possible
+    pub fn get_token_source(
+        &self,
+        workspace: &Workspace,
+        subs: &HashMap<String, String>,
+    ) -> Option<Result<TokenSource, String>> {
+        match self {
+            ExtendedAuthorization::OAuth2Device(parameters) => {
+                let parameters = parameters.substitute(subs);
+                Some(
+                    build_client(
+                        workspace
+                            .certificates
+                            .get_optional(&parameters.selected_certificate.id),
+                        workspace
+                            .proxies
+                            .get_optional(&parameters.selected_proxy.id),
+                    )
+                    .map(|client| TokenSource::Refresh {
+                        client,
+                        url: parameters.access_token_url.clone(),
+                        client_id: parameters.client_id.clone(),
+                        client_secret: parameters.client_secret.clone(),
+                    }),
+                )
+            }
+            _ => self
+                .create_token_request(workspace, subs)
+                .map(|request| request.map(TokenSource::Request)),
+        }
+    }
+}
+
+/// Extended authorization settings, indexed by authorization ID in workbook extensions
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum AuthorizationSettings {
+    /// Settings encrypted with the password of the authorization's parameter store
+    Cipher {
+        encrypted: String,
+    },
+    Plain(ExtendedAuthorization),
+}
+
+impl AuthorizationSettings {
+    /// Encrypt settings using the specified password
+    pub fn encrypt(
+        &mut self,
+        password: &str,
+        method: ParameterEncryption,
+    ) -> Result<(), ApicizeError> {
+        if let AuthorizationSettings::Plain(plain) = self {
+            let data = serde_json::to_string(plain).map_err(|err| ApicizeError::Encryption {
+                description: err.to_string(),
+            })?;
+            *self = AuthorizationSettings::Cipher {
+                encrypted: encrypt(&data, password, method)?,
+            };
+        }
+        Ok(())
+    }
+
+    /// Decrypt settings using the specified password
+    pub fn decrypt(
+        &mut self,
+        password: &str,
+        method: ParameterEncryption,
+    ) -> Result<(), ApicizeError> {
+        if let AuthorizationSettings::Cipher { encrypted } = self {
+            let plain = serde_json::from_str::<ExtendedAuthorization>(&decrypt(
+                encrypted, password, method,
+            )?)
+            .map_err(|err| ApicizeError::Encryption {
+                description: err.to_string(),
+            })?;
+            *self = AuthorizationSettings::Plain(plain);
+        }
+        Ok(())
+    }
+
+    /// Update the authorization's validation errors based upon its extended settings, if any
+    pub fn validate_authorization(settings: Option<&Self>, auth: &mut AuthorizationPlain) {
+        let mut errors = auth.get_validation_errors().clone();
+        for field in VALIDATED_FIELDS {
+            remove_validation_error(&mut errors, field);
+        }
+        if let Some(AuthorizationSettings::Plain(plain)) = settings {
+            for (field, error) in plain.validate() {
+                add_validation_error(&mut errors, field, &error);
+            }
+        }
+        auth.set_validation_errors(errors);
+    }
+}
@@ -0,0 +1,385 @@
+//! AWS Signature Version 4 request signing, see
+//! https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv.html
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::substitution::clone_and_sub;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Credentials and scope used to sign requests with AWS Signature Version 4
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AwsSigV4Parameters {
+    /// Access key ID
+    pub access_key_id: String,
+    /// Secret access key
+    pub secret_access_key: String,
+    /// Session token, when using temporary credentials
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub session_token: String,
+    /// Region (e.g. "us-east-1")
+    pub region: String,
+    /// Service name (e.g. "execute-api" or "s3")
+    pub service: String,
+}
+
+impl AwsSigV4Parameters {
+    /// Return validation errors, indexed by field name
+    pub fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        if self.access_key_id.trim().is_empty() {
+            errors.push(("accessKeyId", "Access key ID is required".to_string()));
+        }
+        if self.secret_access_key.trim().is_empty() {
+            errors.push((
+                "secretAccessKey",
+                "Secret access key is required".to_string(),
+            ));
+        }
+        for (field, label, value) in [
+            ("region", "Region", &self.region),
+            ("service", "Service", &self.service),
+        ] {
+            // Templated values are resolved at runtime
+            if value.trim().is_empty() {
+                errors.push((field, format!("{label} is required")));
+            } else if !value.starts_with("{{")
+                && !value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                errors.push((field, format!("{label} contains invalid characters")));
+            }
+        }
+        errors
+    }
+
+    /// Return a copy of the parameters with substitution values applied
+    pub fn substitute(&self, subs: &HashMap<String, String>) -> Self {
+        AwsSigV4Parameters {
+            access_key_id: clone_and_sub(&self.access_key_id, subs),
+            secret_access_key: clone_and_sub(&self.secret_access_key, subs),
+            session_token: clone_and_sub(&self.session_token, subs),
+            region: clone_and_sub(&self.region, subs),
+            service: clone_and_sub(&self.service, subs),
+        }
+    }
+
+    /// Return the headers to add to a request to sign it; the list of existing headers
+    /// and body must be exactly what will be dispatched
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        timestamp: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, String> {
+        let url = Url::parse(url).map_err(|err| format!("URL \"{url}\" is invalid: {err}"))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("URL \"{url}\" does not include a host")),
+        };
+
+        let amz_date = timestamp.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = timestamp.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body));
+        let is_s3 = self.service == "s3";
+
+        let mut added = vec![("X-Amz-Date".to_string(), amz_date.clone())];
+        if is_s3 {
+            added.push(("X-Amz-Content-Sha256".to_string(), payload_hash.clone()));
+        }
+        if !self.session_token.is_empty() {
+            added.push((
+                "X-Amz-Security-Token".to_string(),
+                self.session_token.clone(),
+            ));
+        }
+
+        // Sign host, content and AWS headers, other headers may be altered in transit
+        let mut signed = vec![("host".to_string(), host)];
+        for (name, value) in headers.iter().chain(added.iter()) {
+            let name = name.to_lowercase();
+            if name == "host"
+                || !(name == "content-type" || name == "content-md5" || name.starts_with("x-amz-"))
+            {
+                continue;
+            }
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            match signed.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, existing)) => {
+                    existing.push(',');
+                    existing.push_str(&value);
+                }
+                None => signed.push((name, value)),
+            }
+        }
+        signed.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let canonical_headers = signed
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect::<String>();
+        let signed_headers = signed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.to_uppercase(),
+            canonical_uri(&url, is_s3),
+            canonical_query(&url),
+            canonical_headers,
+            signed_headers,
+            payload_hash,
+        );
+
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), self.service.as_str(), "aws4_request"] {
+            key = hmac(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        added.push((
+            "Authorization".to_string(),
+            format!(
+                "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.access_key_id
+            ),
+        ));
+        Ok(added)
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// URI-encode per AWS rules, leaving only unreserved characters (and optionally slashes) as-is
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char)
+            }
+            b'/' if !encode_slash => result.push('/'),
+            _ => result.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    result
+}
+
+/// Path segments are encoded once for S3, and twice for all other services
+fn canonical_uri(url: &Url, is_s3: bool) -> String {
+    let path = match url.path() {
+        "" => "/",
+        path => path,
+    };
+
+    // The parsed path is already percent-encoded, so retain existing escapes
+    // and encode anything else that is not unreserved
+    let mut encoded = String::with_capacity(path.len());
+    let mut chars = path.char_indices();
+    while let Some((i, c)) = chars.next() {
+        let escape = path
+            .get(i + 1..i + 3)
+            .filter(|hex| c == '%' && hex.chars().all(|h| h.is_ascii_hexdigit()));
+        match escape {
+            Some(hex) => {
+                encoded.push('%');
+                encoded.push_str(&hex.to_uppercase());
+                chars.next();
+                chars.next();
+            }
+            None => encoded.push_str(&uri_encode(c.encode_utf8(&mut [0; 4]), false)),
+        }
+    }
+
+    if is_s3 {
+        encoded
+    } else {
+        uri_encode(&encoded, false)
+    }
+}
+
+/// Decode percent-escapes, leaving "+" as-is (it is not a space in RFC 3986)
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = value
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Query parameters are built from the raw query string, so that values are
+/// encoded exactly as they will be sent, and sorted by encoded name and value
+fn canonical_query(url: &Url) -> String {
+    let mut pairs = url
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                uri_encode(&percent_decode(name), true),
+                uri_encode(&percent_decode(value), true),
+            )
+        })
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn parameters(service: &str) -> AwsSigV4Parameters {
+        AwsSigV4Parameters {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: String::default(),
+            region: "us-east-1".to_string(),
+            service: service.to_string(),
+        }
+    }
+
+    fn timestamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+    }
+
+    fn authorization(headers: &[(String, String)]) -> &str {
+        &headers
+            .iter()
+            .find(|(name, _)| name == "Authorization")
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn signs_vanilla_request() {
+        // AWS SigV4 test suite "get-vanilla"
+        let headers = parameters("service")
+            .sign(
+                "GET",
+                "https://example.amazonaws.com/",
+                &[],
+                &[],
+                timestamp(),
+            )
+            .unwrap();
+        assert_eq!(
+            authorization(&headers),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn signs_query_and_content_type() {
+        // AWS documentation IAM ListUsers example
+        let headers = parameters("iam")
+            .sign(
+                "GET",
+                "https://iam.amazonaws.com/?Version=2010-05-08&Action=ListUsers",
+                &[(
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+                )],
+                &[],
+                timestamp(),
+            )
+            .unwrap();
+        assert_eq!(
+            authorization(&headers),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    #[test]
+    fn includes_payload_hash_and_session_token() {
+        let mut params = parameters("s3");
+        params.session_token = "TOKEN".to_string();
+        let headers = params
+            .sign(
+                "PUT",
+                "https://bucket.s3.amazonaws.com/my file.txt",
+                &[],
+                b"hello",
+                timestamp(),
+            )
+            .unwrap();
+        let get = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(
+            get("X-Amz-Content-Sha256"),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        assert_eq!(get("X-Amz-Security-Token"), Some("TOKEN"));
+        assert!(
+            authorization(&headers).contains(
+                "SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token,"
+            )
+        );
+    }
+
+    #[test]
+    fn encodes_paths_per_service() {
+        let url = Url::parse("https://host/a b/c%2Fd").unwrap();
+        assert_eq!(canonical_uri(&url, true), "/a%20b/c%2Fd");
+        assert_eq!(canonical_uri(&url, false), "/a%2520b/c%252Fd");
+    }
+
+    #[test]
+    fn encodes_query_per_rfc_3986() {
+        let url = Url::parse("https://host/?b=x+y&a=%7Efoo%20bar&c&a=1").unwrap();
+        assert_eq!(canonical_query(&url), "a=1&a=~foo%20bar&b=x%2By&c=");
+    }
+}
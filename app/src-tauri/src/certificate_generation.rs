@@ -0,0 +1,489 @@
+//! Client certificate generation for mTLS testing.
+//!
+//! Generates a key pair and a client certificate, either self-signed or
+//! signed by a local certificate authority, and packages the result as an
+//! Apicize [`Certificate`] so it can be added to a workbook directly. The
+//! issuing authority (or the self-signed certificate itself) is returned in
+//! PEM format so it can be configured as a trusted CA on the server side.
+
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use apicize_lib::{Certificate, certificate::CertificatePlain, generate_uuid};
+use rcgen::{
+    BasicConstraints, CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, IsCa,
+    Issuer, KeyPair, KeyUsagePurpose, PKCS_ECDSA_P256_SHA256, PKCS_ECDSA_P384_SHA384, SanType,
+};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use crate::{error::ApicizeAppError, trusted_roots::parse_pem_certificates};
+
+/// Default number of days a generated certificate is valid for
+const DEFAULT_VALIDITY_DAYS: u32 = 365;
+
+/// Key algorithm used for generated key pairs
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Default)]
+pub enum GeneratedKeyAlgorithm {
+    /// ECDSA using the P-256 curve and SHA-256
+    #[default]
+    #[serde(rename = "ECDSA_P256")]
+    EcdsaP256,
+    /// ECDSA using the P-384 curve and SHA-384
+    #[serde(rename = "ECDSA_P384")]
+    EcdsaP384,
+}
+
+/// Format in which the generated certificate is stored
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Default)]
+pub enum GeneratedCertificateFormat {
+    /// Certificate and private key in a single PEM file
+    #[default]
+    #[serde(rename = "PEM")]
+    Pem,
+    /// PEM encoded certificate with a separate PKCS8 private key
+    #[serde(rename = "PKCS8_PEM")]
+    Pkcs8Pem,
+    /// Password protected PKCS12 archive
+    #[serde(rename = "PKCS12")]
+    Pkcs12,
+}
+
+/// Authority used to sign the generated certificate
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type")]
+pub enum CertificateIssuer {
+    /// Certificate is signed with its own key
+    #[default]
+    SelfSigned,
+    /// A new certificate authority is generated to sign the certificate
+    #[serde(rename_all = "camelCase")]
+    NewAuthority {
+        /// Common name of the generated authority
+        common_name: String,
+    },
+    /// An existing certificate authority signs the certificate
+    #[serde(rename_all = "camelCase")]
+    ExistingAuthority {
+        /// PEM encoded CA certificate
+        certificate_pem: String,
+        /// PEM encoded PKCS8 CA private key
+        key_pem: String,
+    },
+}
+
+/// Parameters for generating a client certificate
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateGenerationRequest {
+    /// Name of the certificate entity to create
+    pub name: String,
+    /// Subject common name
+    pub common_name: String,
+    /// Subject organization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// Subject organizational unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organizational_unit: Option<String>,
+    /// Subject alternative names (DNS names, IP addresses, e-mail addresses or URIs)
+    #[serde(default)]
+    pub subject_alt_names: Vec<String>,
+    /// Number of days the certificate is valid for
+    #[serde(default = "default_validity_days")]
+    pub validity_days: u32,
+    /// Key algorithm
+    #[serde(default)]
+    pub key_algorithm: GeneratedKeyAlgorithm,
+    /// Authority used to sign the certificate
+    #[serde(default)]
+    pub issuer: CertificateIssuer,
+    /// Format to store the certificate in
+    #[serde(default)]
+    pub format: GeneratedCertificateFormat,
+    /// Password for PKCS12 archives (required, archives are not left unprotected)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// If set, the issuing certificate is written to this file in PEM format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_ca_file_name: Option<String>,
+}
+
+fn default_validity_days() -> u32 {
+    DEFAULT_VALIDITY_DAYS
+}
+
+/// Generated certificate and the PEM encoded certificate of its issuer
+pub struct GeneratedCertificate {
+    pub certificate: Certificate,
+    /// Certificate servers should trust to accept the generated certificate
+    pub ca_certificate_pem: String,
+    /// Private key of a newly generated authority, if any
+    pub ca_key_pem: Option<String>,
+}
+
+/// Generate a client certificate as described by `request`
+pub fn generate_certificate(
+    request: &CertificateGenerationRequest,
+) -> Result<GeneratedCertificate, ApicizeAppError> {
+    if request.common_name.trim().is_empty() {
+        return Err(generation_error("common name is required"));
+    }
+    if request.validity_days == 0 {
+        return Err(generation_error("validity must be at least one day"));
+    }
+    if matches!(request.format, GeneratedCertificateFormat::Pkcs12)
+        && request.password.as_deref().is_none_or(str::is_empty)
+    {
+        return Err(generation_error(
+            "a password is required for PKCS12 archives",
+        ));
+    }
+
+    let not_before = OffsetDateTime::now_utc() - Duration::minutes(5);
+    let not_after = not_before + Duration::days(request.validity_days as i64);
+
+    let mut params = CertificateParams::default();
+    params.distinguished_name = DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(DnType::CommonName, request.common_name.trim());
+    if let Some(organization) = non_empty(&request.organization) {
+        params
+            .distinguished_name
+            .push(DnType::OrganizationName, organization);
+    }
+    if let Some(unit) = non_empty(&request.organizational_unit) {
+        params
+            .distinguished_name
+            .push(DnType::OrganizationalUnitName, unit);
+    }
+    params.subject_alt_names = request
+        .subject_alt_names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(parse_subject_alt_name)
+        .collect::<Result<Vec<SanType>, ApicizeAppError>>()?;
+    params.not_before = not_before;
+    params.not_after = not_after;
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    params.use_authority_key_identifier_extension =
+        !matches!(request.issuer, CertificateIssuer::SelfSigned);
+
+    let key_pair = generate_key_pair(request.key_algorithm)?;
+
+    let (certificate_pem, certificate_der, ca, ca_key_pem) = match &request.issuer {
+        CertificateIssuer::SelfSigned => {
+            let certificate = params.self_signed(&key_pair).map_err(rcgen_error)?;
+            (certificate.pem(), certificate.der().to_vec(), None, None)
+        }
+        CertificateIssuer::NewAuthority { common_name } => {
+            if common_name.trim().is_empty() {
+                return Err(generation_error("authority common name is required"));
+            }
+            let mut ca_params = CertificateParams::default();
+            ca_params.distinguished_name = DistinguishedName::new();
+            ca_params
+                .distinguished_name
+                .push(DnType::CommonName, common_name.trim());
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+            ca_params.key_usages = vec![
+                KeyUsagePurpose::KeyCertSign,
+                KeyUsagePurpose::CrlSign,
+                KeyUsagePurpose::DigitalSignature,
+            ];
+            ca_params.not_before = not_before;
+            ca_params.not_after = not_after;
+
+            let ca_key_pair = generate_key_pair(request.key_algorithm)?;
+            let ca_certificate = ca_params.self_signed(&ca_key_pair).map_err(rcgen_error)?;
+            let ca_key_pem = ca_key_pair.serialize_pem();
+            let issuer = Issuer::new(ca_params, ca_key_pair);
+            let certificate = params.signed_by(&key_pair, &issuer).map_err(rcgen_error)?;
+            (
+                certificate.pem(),
+                certificate.der().to_vec(),
+                Some((ca_certificate.pem(), ca_certificate.der().to_vec())),
+                Some(ca_key_pem),
+            )
+        }
+        CertificateIssuer::ExistingAuthority {
+            certificate_pem,
+            key_pem,
+        } => {
+            let ca_key_pair = KeyPair::from_pem(key_pem)
+                .map_err(|err| generation_error(&format!("invalid authority key: {err}")))?;
+            let issuer = Issuer::from_ca_cert_pem(certificate_pem, ca_key_pair).map_err(|err| {
+                generation_error(&format!("invalid authority certificate: {err}"))
+            })?;
+            let ca_der = parse_pem_certificates(certificate_pem)
+                .map_err(|err| generation_error(&format!("invalid authority certificate: {err}")))?
+                .swap_remove(0);
+            let certificate = params.signed_by(&key_pair, &issuer).map_err(rcgen_error)?;
+            (
+                certificate.pem(),
+                certificate.der().to_vec(),
+                Some((certificate_pem.to_string(), ca_der)),
+                None,
+            )
+        }
+    };
+
+    let key_pem = key_pair.serialize_pem();
+    let id = generate_uuid();
+    let name = if request.name.trim().is_empty() {
+        request.common_name.trim().to_string()
+    } else {
+        request.name.trim().to_string()
+    };
+
+    let plain = match request.format {
+        GeneratedCertificateFormat::Pem => CertificatePlain::PEM {
+            id,
+            name,
+            pem: format!("{certificate_pem}{key_pem}").into_bytes(),
+            validation_state: Default::default(),
+            validation_warnings: None,
+            validation_errors: None,
+        },
+        GeneratedCertificateFormat::Pkcs8Pem => CertificatePlain::PKCS8PEM {
+            id,
+            name,
+            pem: certificate_pem.clone().into_bytes(),
+            key: key_pem.into_bytes(),
+            validation_state: Default::default(),
+            validation_warnings: None,
+            validation_errors: None,
+        },
+        GeneratedCertificateFormat::Pkcs12 => {
+            // Checked above, PKCS12 archives always have a password
+            let password = request.password.clone().unwrap_or_default();
+            let mut chain = vec![certificate_der.as_slice()];
+            if let Some((_, ca_der)) = &ca {
+                chain.push(ca_der.as_slice());
+            }
+            let pfx = build_pkcs12(
+                &request.common_name,
+                &key_pair.serialize_der(),
+                &chain,
+                &password,
+            )?;
+            CertificatePlain::PKCS12 {
+                id,
+                name,
+                pfx,
+                password: Some(password),
+                validation_state: Default::default(),
+                validation_warnings: None,
+                validation_errors: None,
+            }
+        }
+    };
+
+    let mut plain = Box::new(plain);
+    plain.perform_validation();
+    let certificate = Certificate::Plain(plain);
+
+    Ok(GeneratedCertificate {
+        certificate,
+        ca_certificate_pem: match ca {
+            Some((ca_pem, _)) => ca_pem,
+            None => certificate_pem,
+        },
+        ca_key_pem,
+    })
+}
+
+fn generate_key_pair(algorithm: GeneratedKeyAlgorithm) -> Result<KeyPair, ApicizeAppError> {
+    KeyPair::generate_for(match algorithm {
+        GeneratedKeyAlgorithm::EcdsaP256 => &PKCS_ECDSA_P256_SHA256,
+        GeneratedKeyAlgorithm::EcdsaP384 => &PKCS_ECDSA_P384_SHA384,
+    })
+    .map_err(rcgen_error)
+}
+
+/// Interpret a subject alternative name as an IP address, e-mail address, URI or DNS name
+fn parse_subject_alt_name(name: &str) -> Result<SanType, ApicizeAppError> {
+    let invalid = |_| generation_error(&format!("invalid subject alternative name \"{name}\""));
+    if let Ok(ip) = IpAddr::from_str(name) {
+        Ok(SanType::IpAddress(ip))
+    } else if name.contains("://") {
+        Ok(SanType::URI(name.try_into().map_err(invalid)?))
+    } else if name.contains('@') {
+        Ok(SanType::Rfc822Name(name.try_into().map_err(invalid)?))
+    } else {
+        Ok(SanType::DnsName(name.try_into().map_err(invalid)?))
+    }
+}
+
+fn build_pkcs12(
+    alias: &str,
+    key_der: &[u8],
+    chain: &[&[u8]],
+    password: &str,
+) -> Result<Vec<u8>, ApicizeAppError> {
+    let key = p12_keystore::PrivateKey::from_der(key_der).map_err(pkcs12_error)?;
+    let certificates = chain
+        .iter()
+        .map(|der| p12_keystore::Certificate::from_der(der))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pkcs12_error)?;
+
+    let mut key_store = p12_keystore::KeyStore::new();
+    key_store.add_entry(
+        alias,
+        p12_keystore::KeyStoreEntry::PrivateKeyChain(p12_keystore::PrivateKeyChain::new(
+            generate_uuid(),
+            key,
+            certificates,
+        )),
+    );
+    key_store.writer(password).write().map_err(pkcs12_error)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn generation_error(message: &str) -> ApicizeAppError {
+    ApicizeAppError::CertificateGenerationError(message.to_string())
+}
+
+fn rcgen_error(err: rcgen::Error) -> ApicizeAppError {
+    generation_error(&err.to_string())
+}
+
+fn pkcs12_error(err: p12_keystore::error::Error) -> ApicizeAppError {
+    generation_error(&format!("unable to create PKCS12 archive: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(
+        issuer: CertificateIssuer,
+        format: GeneratedCertificateFormat,
+    ) -> CertificateGenerationRequest {
+        CertificateGenerationRequest {
+            name: "Test Client".to_string(),
+            common_name: "client.test".to_string(),
+            organization: Some("Apicize".to_string()),
+            organizational_unit: None,
+            subject_alt_names: vec![
+                "client.test".to_string(),
+                "127.0.0.1".to_string(),
+                "tester@client.test".to_string(),
+            ],
+            validity_days: 30,
+            key_algorithm: GeneratedKeyAlgorithm::EcdsaP256,
+            issuer,
+            format,
+            password: Some("secret".to_string()),
+            export_ca_file_name: None,
+        }
+    }
+
+    #[test]
+    fn generates_self_signed_pem() {
+        let generated = generate_certificate(&request(
+            CertificateIssuer::SelfSigned,
+            GeneratedCertificateFormat::Pem,
+        ))
+        .unwrap();
+        assert!(generated.ca_key_pem.is_none());
+        match generated.certificate {
+            Certificate::Plain(plain) => match *plain {
+                CertificatePlain::PEM { pem, name, .. } => {
+                    let pem = String::from_utf8(pem).unwrap();
+                    assert_eq!(name, "Test Client");
+                    assert!(pem.contains("BEGIN CERTIFICATE"));
+                    assert!(pem.contains("BEGIN PRIVATE KEY"));
+                    assert!(pem.starts_with(generated.ca_certificate_pem.trim()));
+                }
+                _ => panic!("expected PEM certificate"),
+            },
+            Certificate::Cipher(_) => panic!("expected plain certificate"),
+        }
+    }
+
+    #[test]
+    fn generates_authority_signed_pkcs12() {
+        let generated = generate_certificate(&request(
+            CertificateIssuer::NewAuthority {
+                common_name: "Test CA".to_string(),
+            },
+            GeneratedCertificateFormat::Pkcs12,
+        ))
+        .unwrap();
+        let ca_key_pem = generated.ca_key_pem.clone().unwrap();
+
+        let pfx = match generated.certificate {
+            Certificate::Plain(plain) => match *plain {
+                CertificatePlain::PKCS12 { pfx, password, .. } => {
+                    assert_eq!(password.as_deref(), Some("secret"));
+                    pfx
+                }
+                _ => panic!("expected PKCS12 certificate"),
+            },
+            Certificate::Cipher(_) => panic!("expected plain certificate"),
+        };
+        let key_store = p12_keystore::KeyStore::from_pkcs12(
+            &pfx,
+            "secret",
+            p12_keystore::Pkcs12ImportPolicy::Strict,
+        )
+        .unwrap();
+        let (_, chain) = key_store.private_key_chain().unwrap();
+        assert_eq!(chain.certs().len(), 2);
+
+        // The generated authority can be reused to sign additional certificates
+        let mut reissue = request(
+            CertificateIssuer::ExistingAuthority {
+                certificate_pem: generated.ca_certificate_pem.clone(),
+                key_pem: ca_key_pem,
+            },
+            GeneratedCertificateFormat::Pkcs8Pem,
+        );
+        reissue.name = String::default();
+        let reissued = generate_certificate(&reissue).unwrap();
+        assert_eq!(reissued.ca_certificate_pem, generated.ca_certificate_pem);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let mut invalid = request(
+            CertificateIssuer::SelfSigned,
+            GeneratedCertificateFormat::Pem,
+        );
+        invalid.common_name = " ".to_string();
+        assert!(generate_certificate(&invalid).is_err());
+
+        let mut invalid = request(
+            CertificateIssuer::SelfSigned,
+            GeneratedCertificateFormat::Pem,
+        );
+        invalid.subject_alt_names = vec!["bad\u{e9}name".to_string()];
+        assert!(generate_certificate(&invalid).is_err());
+
+        let mut invalid = request(
+            CertificateIssuer::SelfSigned,
+            GeneratedCertificateFormat::Pkcs12,
+        );
+        invalid.password = Some(String::default());
+        assert!(generate_certificate(&invalid).is_err());
+        invalid.password = None;
+        assert!(generate_certificate(&invalid).is_err());
+
+        let invalid = request(
+            CertificateIssuer::ExistingAuthority {
+                certificate_pem: "not a certificate".to_string(),
+                key_pem: "not a key".to_string(),
+            },
+            GeneratedCertificateFormat::Pem,
+        );
+        assert!(generate_certificate(&invalid).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! HTTP Digest authentication (RFC 7616) challenge parsing and response generation
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+use uuid::Uuid;
+
+use crate::substitution::clone_and_sub;
+
+/// Amount of time after its last use that a nonce's count is discarded
+const NONCE_EXPIRATION: Duration = Duration::from_secs(15 * 60);
+
+/// Nonce counts for a workspace, indexed by authorization ID and nonce
+#[derive(Default)]
+pub struct NonceCounts {
+    counts: Mutex<HashMap<(String, String), (u32, Instant)>>,
+}
+
+impl NonceCounts {
+    /// Increment and return the count for the nonce, discarding expired counts
+    pub fn next(&self, authorization_id: &str, nonce: &str) -> u32 {
+        let now = Instant::now();
+        let mut counts = self.counts.lock().unwrap();
+        counts.retain(|_, (_, last_used)| now.duration_since(*last_used) < NONCE_EXPIRATION);
+        let (count, last_used) = counts
+            .entry((authorization_id.to_string(), nonce.to_string()))
+            .or_insert((0, now));
+        *count += 1;
+        *last_used = now;
+        *count
+    }
+}
+
+/// Credentials used to respond to Digest authentication challenges
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestParameters {
+    /// User name
+    pub username: String,
+    /// Password
+    pub password: String,
+}
+
+/// Hash algorithm requested by a Digest challenge
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_uppercase().as_str() {
+            "MD5" => Some(DigestAlgorithm::Md5),
+            "MD5-SESS" => Some(DigestAlgorithm::Md5Sess),
+            "SHA-256" => Some(DigestAlgorithm::Sha256),
+            "SHA-256-SESS" => Some(DigestAlgorithm::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Md5 => "MD5",
+            DigestAlgorithm::Md5Sess => "MD5-sess",
+            DigestAlgorithm::Sha256 => "SHA-256",
+            DigestAlgorithm::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_session(&self) -> bool {
+        matches!(self, DigestAlgorithm::Md5Sess | DigestAlgorithm::Sha256Sess)
+    }
+
+    fn hash(&self, data: &str) -> String {
+        match self {
+            DigestAlgorithm::Md5 | DigestAlgorithm::Md5Sess => hex::encode(Md5::digest(data)),
+            DigestAlgorithm::Sha256 | DigestAlgorithm::Sha256Sess => {
+                hex::encode(Sha256::digest(data))
+            }
+        }
+    }
+}
+
+/// A Digest challenge received in a WWW-Authenticate header
+#[derive(Clone, PartialEq, Debug)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: DigestAlgorithm,
+    /// True if the server supports qop=auth
+    pub qop_auth: bool,
+}
+
+impl DigestChallenge {
+    /// Parse the strongest supported Digest challenge from a WWW-Authenticate header
+    pub fn parse(header: &str) -> Result<DigestChallenge, String> {
+        let challenges = parse_challenges(header)
+            .into_iter()
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("digest"))
+            .filter_map(|(_, params)| {
+                let algorithm = match params.get("algorithm") {
+                    Some(algorithm) => DigestAlgorithm::parse(algorithm)?,
+                    None => DigestAlgorithm::Md5,
+                };
+                let qop = params.get("qop");
+                // Only qop=auth (or legacy challenges without qop) are supported
+                let qop_auth = qop.is_some_and(|qop| {
+                    qop.split(',')
+                        .any(|q| q.trim().eq_ignore_ascii_case("auth"))
+                });
+                if qop.is_some() && !qop_auth {
+                    return None;
+                }
+                Some(DigestChallenge {
+                    realm: params.get("realm").cloned().unwrap_or_default(),
+                    nonce: params.get("nonce")?.clone(),
+                    opaque: params.get("opaque").cloned(),
+                    algorithm,
+                    qop_auth,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        challenges
+            .iter()
+            .find(|c| {
+                matches!(
+                    c.algorithm,
+                    DigestAlgorithm::Sha256 | DigestAlgorithm::Sha256Sess
+                )
+            })
+            .or(challenges.first())
+            .cloned()
+            .ok_or_else(|| "No supported Digest challenge found".to_string())
+    }
+}
+
+impl DigestParameters {
+    /// Return validation errors, indexed by field name
+    pub fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.username.trim().is_empty() {
+            vec![("username", "User name is required".to_string())]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Return a copy of the parameters with substitution values applied
+    pub fn substitute(&self, subs: &HashMap<String, String>) -> Self {
+        DigestParameters {
+            username: clone_and_sub(&self.username, subs),
+            password: clone_and_sub(&self.password, subs),
+        }
+    }
+
+    /// Return the Authorization header value responding to the challenge for the
+    /// specified request, incrementing the nonce count for the authorization
+    pub fn respond(
+        &self,
+        nonce_counts: &NonceCounts,
+        authorization_id: &str,
+        method: &str,
+        url: &str,
+        www_authenticate: &str,
+    ) -> Result<String, String> {
+        let challenge = DigestChallenge::parse(www_authenticate)?;
+        let url = Url::parse(url).map_err(|err| format!("URL \"{url}\" is invalid: {err}"))?;
+        let uri = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+
+        let nonce_count = nonce_counts.next(authorization_id, &challenge.nonce);
+        let cnonce = Uuid::new_v4().simple().to_string();
+        Ok(self.build_response(&challenge, method, &uri, nonce_count, &cnonce))
+    }
+
+    fn build_response(
+        &self,
+        challenge: &DigestChallenge,
+        method: &str,
+        uri: &str,
+        nonce_count: u32,
+        cnonce: &str,
+    ) -> String {
+        let algorithm = challenge.algorithm;
+        let nc = format!("{nonce_count:08x}");
+
+        let mut ha1 = algorithm.hash(&format!(
+            "{}:{}:{}",
+            self.username, challenge.realm, self.password
+        ));
+        if algorithm.is_session() {
+            ha1 = algorithm.hash(&format!("{ha1}:{}:{cnonce}", challenge.nonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{uri}", method.to_uppercase()));
+        let response = if challenge.qop_auth {
+            algorithm.hash(&format!(
+                "{ha1}:{}:{nc}:{cnonce}:auth:{ha2}",
+                challenge.nonce
+            ))
+        } else {
+            algorithm.hash(&format!("{ha1}:{}:{ha2}", challenge.nonce))
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{uri}\", algorithm={}, response=\"{response}\"",
+            quote(&self.username),
+            quote(&challenge.realm),
+            quote(&challenge.nonce),
+            algorithm.name(),
+        );
+        if let Some(opaque) = &challenge.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", quote(opaque)));
+        }
+        if challenge.qop_auth {
+            header.push_str(&format!(", qop=auth, nc={nc}, cnonce=\"{cnonce}\""));
+        }
+        header
+    }
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Parse a WWW-Authenticate header into a list of schemes and their parameters
+fn parse_challenges(header: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut challenges = Vec::<(String, HashMap<String, String>)>::new();
+    let mut chars = header.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == ',') {
+            chars.next();
+        }
+        let mut token = String::new();
+        while let Some(c) = chars.peek() {
+            if c.is_whitespace() || *c == ',' || *c == '=' {
+                break;
+            }
+            token.push(*c);
+            chars.next();
+        }
+        if token.is_empty() {
+            break;
+        }
+        while chars.peek().is_some_and(|c| *c == ' ') {
+            chars.next();
+        }
+
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            while chars.peek().is_some_and(|c| *c == ' ') {
+                chars.next();
+            }
+            let mut value = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.peek() {
+                    if *c == ',' || c.is_whitespace() {
+                        break;
+                    }
+                    value.push(*c);
+                    chars.next();
+                }
+            }
+            if let Some((_, params)) = challenges.last_mut() {
+                params.insert(token.to_lowercase(), value);
+            }
+        } else {
+            challenges.push((token, HashMap::new()));
+        }
+    }
+    challenges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHALLENGE: &str = "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", algorithm=MD5, nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"";
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    fn parameters() -> DigestParameters {
+        DigestParameters {
+            username: "Mufasa".to_string(),
+            password: "Circle of Life".to_string(),
+        }
+    }
+
+    #[test]
+    fn responds_to_md5_challenge() {
+        // RFC 7616 section 3.9.1
+        let challenge = DigestChallenge::parse(CHALLENGE).unwrap();
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Md5);
+        let header = parameters().build_response(&challenge, "GET", "/dir/index.html", 1, CNONCE);
+        assert!(header.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""));
+        assert!(header.contains("qop=auth, nc=00000001"));
+        assert!(header.contains("opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\""));
+    }
+
+    #[test]
+    fn prefers_sha256_challenge() {
+        // RFC 7616 section 3.9.1, with both challenges offered
+        let header = format!(
+            "{}, {}, Basic realm=\"x\"",
+            CHALLENGE.replace("MD5", "SHA-256"),
+            CHALLENGE
+        );
+        let challenge = DigestChallenge::parse(&header).unwrap();
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Sha256);
+        let header = parameters().build_response(&challenge, "GET", "/dir/index.html", 1, CNONCE);
+        assert!(header.contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+    }
+
+    #[test]
+    fn counts_nonces() {
+        let nonce_counts = NonceCounts::default();
+        let first = parameters()
+            .respond(&nonce_counts, "auth", "GET", "http://host/a?b=c", CHALLENGE)
+            .unwrap();
+        let second = parameters()
+            .respond(&nonce_counts, "auth", "GET", "http://host/a?b=c", CHALLENGE)
+            .unwrap();
+        assert!(first.contains("nc=00000001") && first.contains("uri=\"/a?b=c\""));
+        assert!(second.contains("nc=00000002"));
+
+        // Counts are scoped to the authorization, and expire when unused
+        assert_eq!(nonce_counts.next("other", "nonce"), 1);
+        nonce_counts
+            .counts
+            .lock()
+            .unwrap()
+            .values_mut()
+            .for_each(|(_, last_used)| *last_used -= NONCE_EXPIRATION);
+        assert_eq!(nonce_counts.next("other", "nonce"), 1);
+        assert_eq!(nonce_counts.counts.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_unsupported_challenges() {
+        assert!(DigestChallenge::parse("Basic realm=\"x\"").is_err());
+        assert!(
+            DigestChallenge::parse("Digest realm=\"x\", nonce=\"n\", qop=\"auth-int\"").is_err()
+        );
+        assert!(
+            DigestChallenge::parse("Digest realm=\"x\", nonce=\"n\", algorithm=SHA-512").is_err()
+        );
+    }
+}
@@ -53,6 +53,9 @@ pub enum ApicizeAppError {
     #[error("invalid data set '{0}'")]
     InvalidDataSet(String),
 
+    #[error("invalid trusted roots '{0}'")]
+    InvalidTrustedRoots(String),
+
     #[error("invalid operation type '{0}'")]
     InvalidTypeForOperation(EntityType),
 
@@ -62,6 +65,12 @@ pub enum ApicizeAppError {
     #[error("file name required")]
     FileNameRequired(),
 
+    #[error("secret variable commands have not been trusted for this workbook: {0}")]
+    UntrustedSecretCommands(String),
+
+    #[error("{0} parameters are locked; enter their password to move parameters to them")]
+    ParameterStoreLocked(String),
+
     #[error("invalid operation {0}")]
     InvalidOperation(String),
 
@@ -80,6 +89,12 @@ pub enum ApicizeAppError {
     #[error("code generation error '{0}'")]
     CodeGenerationError(String),
 
+    #[error("certificate generation error '{0}'")]
+    CertificateGenerationError(String),
+
+    #[error("proxy resolution error '{0}'")]
+    ProxyResolutionError(String),
+
     #[error(transparent)]
     FromUtf8Error(#[from] FromUtf8Error),
 }
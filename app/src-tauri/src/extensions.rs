@@ -0,0 +1,553 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use apicize_lib::{
+    ApicizeError, Authorization, IndexedEntities, PERSIST_PRIVATE, PERSIST_VAULT, Parameters,
+    Proxy, Selection, Validated, Workspace, delete_data_file, open_data_file,
+    parameters::ParameterEncryption, save_data_file,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    authorization_settings::AuthorizationSettings, proxy_settings::ProxySettings,
+    secret_variables::ScenarioSecretVariables, trusted_roots::TrustedRoots,
+};
+
+/// Workbook settings managed by Apicize that are not part of the workbook format,
+/// persisted to a file alongside the workbook
+#[derive(Clone, Default)]
+pub struct WorkbookExtensions {
+    /// Trusted root certificate authority bundles
+    pub trusted_roots: IndexedEntities<TrustedRoots>,
+    /// Trusted root bundle selections, indexed by request, group or defaults ID
+    pub selected_trusted_roots: BTreeMap<String, Selection>,
+    /// Additional proxy settings, indexed by proxy ID
+    pub proxy_settings: BTreeMap<String, ProxySettings>,
+    /// Settings of authorization types implemented by Apicize, indexed by authorization ID
+    pub authorization_settings: BTreeMap<String, AuthorizationSettings>,
+    /// Secret variable references, indexed by scenario ID
+    pub secret_variables: BTreeMap<String, ScenarioSecretVariables>,
+}
+
+/// Persisted workbook extensions
+#[derive(Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct StoredWorkbookExtensions {
+    /// Version of extension format (should not be changed manually)
+    pub version: f32,
+    /// Trusted root certificate authority bundles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_roots: Option<Vec<TrustedRoots>>,
+    /// Trusted root bundle selections, indexed by request, group or defaults ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_trusted_roots: Option<BTreeMap<String, Selection>>,
+    /// Additional proxy settings, indexed by proxy ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_settings: Option<BTreeMap<String, ProxySettings>>,
+    /// Settings of authorization types implemented by Apicize, indexed by authorization ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_settings: Option<BTreeMap<String, AuthorizationSettings>>,
+    /// Secret variable references, indexed by scenario ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_variables: Option<BTreeMap<String, ScenarioSecretVariables>>,
+}
+
+impl StoredWorkbookExtensions {
+    /// Returns true if there is nothing to store
+    fn is_empty(&self) -> bool {
+        self.trusted_roots.is_none()
+            && self.selected_trusted_roots.is_none()
+            && self.proxy_settings.is_none()
+            && self.authorization_settings.is_none()
+            && self.secret_variables.is_none()
+    }
+}
+
+/// Extended settings of vault parameters, persisted alongside the vault so that they are
+/// shared by workbooks and encrypted with the vault's current password
+#[derive(Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct StoredVaultExtensions {
+    /// Version of extension format (should not be changed manually)
+    pub version: f32,
+    /// Additional proxy settings, indexed by proxy ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_settings: Option<BTreeMap<String, ProxySettings>>,
+    /// Settings of authorization types implemented by Apicize, indexed by authorization ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_settings: Option<BTreeMap<String, AuthorizationSettings>>,
+}
+
+impl WorkbookExtensions {
+    /// Return the name of the extensions file for the specified workbook
+    pub fn get_file_name(workbook_path: &Path) -> PathBuf {
+        let mut extensions_path = PathBuf::from(workbook_path);
+        extensions_path.set_extension("apicize-ext");
+        extensions_path
+    }
+
+    /// Return the name of the file storing extended settings of vault parameters
+    pub fn get_vault_file_name() -> PathBuf {
+        Parameters::get_globals_filename().with_extension("apicize-ext")
+    }
+
+    /// Open extensions for the specified workbook, if saved, along with extended settings
+    /// of vault parameters
+    pub fn open(
+        workbook_path: Option<&Path>,
+        workspace: &mut Workspace,
+    ) -> Result<WorkbookExtensions, ApicizeError> {
+        Self::open_with_vault(workbook_path, &Self::get_vault_file_name(), workspace)
+    }
+
+    /// Open extensions for the specified workbook, if saved, along with extended settings
+    /// of vault parameters from the specified file
+    pub fn open_with_vault(
+        workbook_path: Option<&Path>,
+        vault_file_name: &Path,
+        workspace: &mut Workspace,
+    ) -> Result<WorkbookExtensions, ApicizeError> {
+        let stored = match workbook_path.map(Self::get_file_name) {
+            Some(file_name) if file_name.is_file() => {
+                open_data_file::<StoredWorkbookExtensions>(&file_name)?.data
+            }
+            _ => StoredWorkbookExtensions::default(),
+        };
+
+        let mut trusted_roots = stored.trusted_roots.unwrap_or_default();
+        for entry in trusted_roots.iter_mut() {
+            entry.perform_validation();
+        }
+
+        let mut extensions = WorkbookExtensions {
+            trusted_roots: IndexedEntities::<TrustedRoots> {
+                top_level_ids: trusted_roots.iter().map(|t| t.id.clone()).collect(),
+                child_ids: HashMap::new(),
+                entities: trusted_roots
+                    .into_iter()
+                    .map(|t| (t.id.clone(), t))
+                    .collect(),
+                parent_ids: HashMap::new(),
+            },
+            selected_trusted_roots: stored.selected_trusted_roots.unwrap_or_default(),
+            proxy_settings: stored.proxy_settings.unwrap_or_default(),
+            authorization_settings: stored.authorization_settings.unwrap_or_default(),
+            secret_variables: stored.secret_variables.unwrap_or_default(),
+        };
+
+        // Settings of vault parameters saved with the workbook (by earlier versions) are
+        // only used if the vault does not have them
+        if vault_file_name.is_file() {
+            let vault =
+                open_data_file::<StoredVaultExtensions>(&vault_file_name.to_path_buf())?.data;
+            for (id, settings) in vault.proxy_settings.unwrap_or_default() {
+                if Self::is_in_store(&workspace.proxies, PERSIST_VAULT, &id) {
+                    extensions.proxy_settings.insert(id, settings);
+                }
+            }
+            for (id, settings) in vault.authorization_settings.unwrap_or_default() {
+                if Self::is_in_store(&workspace.authorizations, PERSIST_VAULT, &id) {
+                    extensions.authorization_settings.insert(id, settings);
+                }
+            }
+        }
+
+        extensions.unlock_proxy_settings(workspace);
+        extensions.unlock_authorization_settings(workspace);
+        Ok(extensions)
+    }
+
+    /// Save extensions for the specified workbook, and extended settings of vault parameters
+    pub fn save(&self, workbook_path: &Path, workspace: &Workspace) -> Result<(), ApicizeError> {
+        self.save_workbook(workbook_path, workspace)?;
+        self.save_vault(workspace)
+    }
+
+    /// Save extended settings of vault parameters alongside the vault, removing the file if
+    /// there is nothing to store
+    pub fn save_vault(&self, workspace: &Workspace) -> Result<(), ApicizeError> {
+        self.save_vault_to(&Self::get_vault_file_name(), workspace)
+    }
+
+    /// Save extended settings of vault parameters to the specified file, removing it if
+    /// there is nothing to store
+    pub fn save_vault_to(
+        &self,
+        file_name: &Path,
+        workspace: &Workspace,
+    ) -> Result<(), ApicizeError> {
+        let file_name = file_name.to_path_buf();
+        let stored = StoredVaultExtensions {
+            version: 1.0,
+            proxy_settings: Some(self.get_stored_proxy_settings(workspace, true)?)
+                .filter(|settings| !settings.is_empty()),
+            authorization_settings: Some(self.get_stored_authorization_settings(workspace, true)?)
+                .filter(|settings| !settings.is_empty()),
+        };
+        if stored.proxy_settings.is_none() && stored.authorization_settings.is_none() {
+            delete_data_file(&file_name)?;
+        } else {
+            save_data_file(&file_name, &stored)?;
+        }
+        Ok(())
+    }
+
+    /// Return proxy settings to persist for either vault or other proxies.  Credentials of
+    /// proxies stored in password protected parameter files are encrypted with the same
+    /// password
+    fn get_stored_proxy_settings(
+        &self,
+        workspace: &Workspace,
+        vault: bool,
+    ) -> Result<BTreeMap<String, ProxySettings>, ApicizeError> {
+        let mut proxy_settings = BTreeMap::<String, ProxySettings>::new();
+        for (id, settings) in &self.proxy_settings {
+            if settings.is_empty()
+                || !workspace.proxies.entities.contains_key(id)
+                || Self::is_in_store(&workspace.proxies, PERSIST_VAULT, id) != vault
+            {
+                continue;
+            }
+            let mut settings = settings.clone();
+            if let Some((password, encryption)) =
+                Self::get_store_password(workspace, &workspace.proxies, id)
+            {
+                settings.encrypt_credentials(password, encryption)?;
+            }
+            proxy_settings.insert(id.clone(), settings);
+        }
+        Ok(proxy_settings)
+    }
+
+    /// Return extended authorization settings to persist for either vault or other
+    /// authorizations, encrypted along with their placeholders
+    fn get_stored_authorization_settings(
+        &self,
+        workspace: &Workspace,
+        vault: bool,
+    ) -> Result<BTreeMap<String, AuthorizationSettings>, ApicizeError> {
+        let mut authorization_settings = BTreeMap::<String, AuthorizationSettings>::new();
+        for (id, settings) in &self.authorization_settings {
+            if !workspace.authorizations.entities.contains_key(id)
+                || Self::is_in_store(&workspace.authorizations, PERSIST_VAULT, id) != vault
+            {
+                continue;
+            }
+            let mut settings = settings.clone();
+            if let Some((password, encryption)) =
+                Self::get_store_password(workspace, &workspace.authorizations, id)
+            {
+                settings.encrypt(password, encryption)?;
+            }
+            authorization_settings.insert(id.clone(), settings);
+        }
+        Ok(authorization_settings)
+    }
+
+    /// Save extensions for the specified workbook, excluding settings of vault parameters,
+    /// removing the extensions file if there is nothing to store
+    pub fn save_workbook(
+        &self,
+        workbook_path: &Path,
+        workspace: &Workspace,
+    ) -> Result<(), ApicizeError> {
+        let file_name = Self::get_file_name(workbook_path);
+
+        let trusted_roots = self
+            .trusted_roots
+            .top_level_ids
+            .iter()
+            .filter_map(|id| self.trusted_roots.entities.get(id))
+            .map(|entry| {
+                let mut stored = entry.clone();
+                stored.set_validation_errors(None);
+                stored.set_validation_warnings(None);
+                stored
+            })
+            .collect::<Vec<TrustedRoots>>();
+
+        let proxy_settings = self.get_stored_proxy_settings(workspace, false)?;
+        let authorization_settings = self.get_stored_authorization_settings(workspace, false)?;
+
+        // Only references are stored, values are resolved when executing requests
+        let secret_variables = self
+            .secret_variables
+            .iter()
+            .filter(|(id, secrets)| {
+                !secrets.is_empty() && workspace.scenarios.entities.contains_key(*id)
+            })
+            .map(|(id, secrets)| (id.clone(), secrets.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        let stored = StoredWorkbookExtensions {
+            version: 1.0,
+            trusted_roots: if trusted_roots.is_empty() {
+                None
+            } else {
+                Some(trusted_roots)
+            },
+            selected_trusted_roots: if self.selected_trusted_roots.is_empty() {
+                None
+            } else {
+                Some(self.selected_trusted_roots.clone())
+            },
+            proxy_settings: if proxy_settings.is_empty() {
+                None
+            } else {
+                Some(proxy_settings)
+            },
+            authorization_settings: if authorization_settings.is_empty() {
+                None
+            } else {
+                Some(authorization_settings)
+            },
+            secret_variables: if secret_variables.is_empty() {
+                None
+            } else {
+                Some(secret_variables)
+            },
+        };
+
+        if stored.is_empty() {
+            delete_data_file(&file_name)?;
+        } else {
+            save_data_file(&file_name, &stored)?;
+        }
+        Ok(())
+    }
+
+    /// Update the settings of private parameters in the workbook's saved extensions, i.e.
+    /// after the private parameter password changes, leaving other saved settings as they
+    /// are so that unsaved changes are not written
+    pub fn save_private_settings(
+        &self,
+        workbook_path: &Path,
+        workspace: &Workspace,
+    ) -> Result<(), ApicizeError> {
+        let file_name = Self::get_file_name(workbook_path);
+        let mut stored = if file_name.is_file() {
+            open_data_file::<StoredWorkbookExtensions>(&file_name)?.data
+        } else {
+            StoredWorkbookExtensions {
+                version: 1.0,
+                ..Default::default()
+            }
+        };
+
+        let is_private_proxy =
+            |id: &String| Self::is_in_store(&workspace.proxies, PERSIST_PRIVATE, id);
+        let mut proxy_settings = stored.proxy_settings.take().unwrap_or_default();
+        proxy_settings.retain(|id, _| !is_private_proxy(id));
+        proxy_settings.extend(
+            self.get_stored_proxy_settings(workspace, false)?
+                .into_iter()
+                .filter(|(id, _)| is_private_proxy(id)),
+        );
+        stored.proxy_settings = Some(proxy_settings).filter(|settings| !settings.is_empty());
+
+        let is_private_authorization =
+            |id: &String| Self::is_in_store(&workspace.authorizations, PERSIST_PRIVATE, id);
+        let mut authorization_settings = stored.authorization_settings.take().unwrap_or_default();
+        authorization_settings.retain(|id, _| !is_private_authorization(id));
+        authorization_settings.extend(
+            self.get_stored_authorization_settings(workspace, false)?
+                .into_iter()
+                .filter(|(id, _)| is_private_authorization(id)),
+        );
+        stored.authorization_settings =
+            Some(authorization_settings).filter(|settings| !settings.is_empty());
+
+        if stored.is_empty() {
+            delete_data_file(&file_name)?;
+        } else {
+            save_data_file(&file_name, &stored)?;
+        }
+        Ok(())
+    }
+
+    /// Return the trusted root selection for the specified request, group or defaults
+    pub fn get_selected_trusted_roots(&self, id: &str) -> Selection {
+        self.selected_trusted_roots
+            .get(id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Assign the trusted root selection for the specified request, group or defaults
+    pub fn set_selected_trusted_roots(&mut self, id: &str, selection: &Selection) {
+        if selection.is_default() {
+            self.selected_trusted_roots.remove(id);
+        } else {
+            self.selected_trusted_roots
+                .insert(id.to_string(), selection.clone());
+        }
+    }
+
+    /// Return settings for the specified proxy
+    pub fn get_proxy_settings(&self, proxy_id: &str) -> ProxySettings {
+        self.proxy_settings
+            .get(proxy_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Decrypt proxy credentials for parameter stores that are unlocked, and
+    /// validate proxy settings
+    pub fn unlock_proxy_settings(&mut self, workspace: &mut Workspace) {
+        for (id, settings) in self.proxy_settings.iter_mut() {
+            if let Some((password, encryption)) =
+                Self::get_store_password(workspace, &workspace.proxies, id)
+            {
+                // Credentials that cannot be decrypted remain encrypted
+                let _ = settings.decrypt_credentials(password, encryption);
+            }
+            if let Some(Proxy::Plain(proxy)) = workspace.proxies.entities.get_mut(id) {
+                settings.validate_proxy(proxy);
+            }
+        }
+    }
+
+    /// Decrypt extended authorization settings for parameter stores that are unlocked,
+    /// and validate them
+    pub fn unlock_authorization_settings(&mut self, workspace: &mut Workspace) {
+        for (id, settings) in self.authorization_settings.iter_mut() {
+            if let Some((password, encryption)) =
+                Self::get_store_password(workspace, &workspace.authorizations, id)
+            {
+                // Settings that cannot be decrypted remain encrypted
+                let _ = settings.decrypt(password, encryption);
+            }
+            if let Some(Authorization::Plain(auth)) = workspace.authorizations.entities.get_mut(id)
+            {
+                AuthorizationSettings::validate_authorization(Some(settings), auth);
+            }
+        }
+    }
+
+    /// Return the password and encryption method of the parameter store the entity
+    /// is saved in, if the store is password protected and unlocked
+    fn get_store_password<'a, T>(
+        workspace: &'a Workspace,
+        entities: &IndexedEntities<T>,
+        entity_id: &str,
+    ) -> Option<(&'a str, ParameterEncryption)> {
+        let in_store = |store: &str| Self::is_in_store(entities, store, entity_id);
+        let (password, encryption, lock_status) = if in_store(PERSIST_VAULT) {
+            (
+                &workspace.vault_password,
+                workspace.vault_encryption,
+                workspace.vault_lock_status,
+            )
+        } else if in_store(PERSIST_PRIVATE) {
+            (
+                &workspace.private_password,
+                workspace.private_encryption,
+                workspace.private_lock_status,
+            )
+        } else {
+            return None;
+        };
+        match password {
+            Some(password) if !password.is_empty() && !lock_status.is_locked() => {
+                Some((password.as_str(), encryption.unwrap_or_default()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the entity is saved in the specified parameter store
+    fn is_in_store<T>(entities: &IndexedEntities<T>, store: &str, entity_id: &str) -> bool {
+        entities
+            .child_ids
+            .get(store)
+            .is_some_and(|ids| ids.iter().any(|id| id == entity_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apicize_lib::{ParameterLockStatus, PersistedIndex, WorkbookDefaultParameters};
+
+    use super::*;
+    use crate::{authorization_settings::ExtendedAuthorization, digest_auth::DigestParameters};
+
+    fn digest(username: &str) -> AuthorizationSettings {
+        AuthorizationSettings::Plain(ExtendedAuthorization::Digest(DigestParameters {
+            username: username.to_string(),
+            password: String::default(),
+        }))
+    }
+
+    fn authorization(id: &str) -> Authorization {
+        Authorization::Plain(Box::new(
+            ExtendedAuthorization::Digest(DigestParameters::default()).create_placeholder(id, id),
+        ))
+    }
+
+    fn get_username(extensions: &WorkbookExtensions, id: &str) -> String {
+        match &extensions.authorization_settings[id] {
+            AuthorizationSettings::Plain(ExtendedAuthorization::Digest(parameters)) => {
+                parameters.username.clone()
+            }
+            _ => String::default(),
+        }
+    }
+
+    #[test]
+    fn saves_only_private_settings() {
+        let directory = tempfile::tempdir().unwrap();
+        let workbook_path = directory.path().join("test.apicize");
+        let mut workspace = Workspace {
+            requests: IndexedEntities::default(),
+            scenarios: IndexedEntities::default(),
+            authorizations: IndexedEntities::<Authorization>::new(
+                Some(vec![authorization("workbook")]),
+                Some(vec![authorization("private")]),
+                None,
+            ),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters::default(),
+            private_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        };
+
+        let saved = WorkbookExtensions {
+            authorization_settings: BTreeMap::from([
+                ("workbook".to_string(), digest("saved")),
+                ("private".to_string(), digest("saved")),
+            ]),
+            ..Default::default()
+        };
+        saved.save_workbook(&workbook_path, &workspace).unwrap();
+
+        let unsaved = WorkbookExtensions {
+            authorization_settings: BTreeMap::from([
+                ("workbook".to_string(), digest("unsaved")),
+                ("private".to_string(), digest("unsaved")),
+            ]),
+            selected_trusted_roots: BTreeMap::from([("request".to_string(), Selection::default())]),
+            ..Default::default()
+        };
+        unsaved
+            .save_private_settings(&workbook_path, &workspace)
+            .unwrap();
+
+        let opened = WorkbookExtensions::open_with_vault(
+            Some(&workbook_path),
+            &directory.path().join("vault.apicize-ext"),
+            &mut workspace,
+        )
+        .unwrap();
+        assert_eq!(get_username(&opened, "workbook"), "saved");
+        assert_eq!(get_username(&opened, "private"), "unsaved");
+        assert!(opened.selected_trusted_roots.is_empty());
+    }
+}
@@ -0,0 +1,836 @@
+//! Detection of changes made on disk to an open workbook, its private parameters, its
+//! extensions and its external data set files by something other than Apicize (e.g.
+//! pulling from source control), and three-way merging of those changes with unsaved
+//! edits
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use apicize_lib::{
+    DataSourceType, Identifiable, IndexedEntities, Parameters, Workspace, build_absolute_file_name,
+    editing::indexed_entities::IndexedEntityPosition,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    extensions::WorkbookExtensions,
+    navigation::{Navigation, get_ordered_ids},
+    undo_history::find_anchor,
+    workbook_directory::get_directory_name,
+    workspaces::EntityType,
+};
+
+/// File that is watched for external changes
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "camelCase")]
+pub enum WatchedFile {
+    /// Workbook (public) file
+    Workbook,
+    /// Directory storing entries of a workbook saved as a directory
+    WorkbookDirectory,
+    /// Private parameters stored alongside the workbook
+    Private,
+    /// Workbook extensions stored alongside the workbook
+    Extensions,
+    /// External file of the data set with the specified ID
+    DataSet(String),
+}
+
+/// Contents of a file at a point in time
+#[derive(Clone)]
+struct FileFingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: Vec<u8>,
+}
+
+impl FileFingerprint {
+    /// Fingerprint the file at the specified path, returning None if it does not exist; the
+    /// file is only re-read if its modification time or length differ from the previous
+    /// fingerprint
+    fn read(path: &Path, previous: Option<&FileFingerprint>) -> Option<FileFingerprint> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.is_dir() {
+            return Some(Self::read_directory(path));
+        }
+        if !metadata.is_file() {
+            return None;
+        }
+        let modified = metadata.modified().ok();
+        if let Some(previous) = previous
+            && modified.is_some()
+            && previous.modified == modified
+            && previous.len == metadata.len()
+        {
+            return Some(previous.clone());
+        }
+        let data = fs::read(path).ok()?;
+        Some(FileFingerprint {
+            modified,
+            len: data.len() as u64,
+            hash: Sha256::digest(&data).to_vec(),
+        })
+    }
+
+    /// Fingerprint the names, lengths and modification times of the files in a directory,
+    /// without reading them, since a directory workbook may contain many files
+    fn read_directory(path: &Path) -> FileFingerprint {
+        let mut files = Vec::<(PathBuf, u64, Option<SystemTime>)>::new();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(directory) = pending.pop() {
+            let Ok(items) = fs::read_dir(&directory) else {
+                continue;
+            };
+            for item in items.flatten() {
+                let Ok(metadata) = item.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    pending.push(item.path());
+                } else {
+                    files.push((item.path(), metadata.len(), metadata.modified().ok()));
+                }
+            }
+        }
+        files.sort();
+
+        let mut hasher = Sha256::new();
+        for (file, len, modified) in &files {
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update(len.to_le_bytes());
+            if let Some(since_epoch) =
+                modified.and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+            {
+                hasher.update(since_epoch.as_nanos().to_le_bytes());
+            }
+        }
+        FileFingerprint {
+            modified: None,
+            len: files.len() as u64,
+            hash: hasher.finalize().to_vec(),
+        }
+    }
+
+    /// Returns true if both fingerprints represent the same content (or absence)
+    fn same(a: &Option<FileFingerprint>, b: &Option<FileFingerprint>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => a.hash == b.hash,
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+struct WatchedFileState {
+    path: PathBuf,
+    /// Content as of when the workbook was last opened, saved or reloaded
+    known: Option<FileFingerprint>,
+    /// Content as of the last check
+    current: Option<FileFingerprint>,
+    /// Set when sessions have been notified about the current content
+    notified: bool,
+}
+
+/// Files of a workspace being watched for external changes
+#[derive(Default)]
+pub struct ExternalChanges {
+    /// Watched files, which can be polled while the workspace is only borrowed
+    files: Mutex<HashMap<WatchedFile, WatchedFileState>>,
+    /// Workspace and extensions as last opened from or saved to disk, used as the common
+    /// ancestor when merging external changes; None if unknown (i.e. restored from recovery)
+    base: Option<(Workspace, WorkbookExtensions)>,
+}
+
+impl ExternalChanges {
+    /// Treat the files currently on disk as the known version of the workbook
+    pub fn track(&mut self, file_name: &str, workspace: &Workspace) {
+        let files = self.files.get_mut().unwrap();
+        files.clear();
+        Self::refresh_files(files, file_name, workspace);
+    }
+
+    /// Set the workspace and extensions to use as the common ancestor when merging
+    pub fn set_base(&mut self, base: Option<(Workspace, WorkbookExtensions)>) {
+        self.base = base;
+    }
+
+    pub fn base(&self) -> Option<(&Workspace, &WorkbookExtensions)> {
+        self.base
+            .as_ref()
+            .map(|(workspace, extensions)| (workspace, extensions))
+    }
+
+    /// Treat the current content of a watched file as known, i.e. after Apicize saves it
+    pub fn acknowledge(&mut self, file: &WatchedFile) {
+        if let Some(state) = self.files.get_mut().unwrap().get_mut(file) {
+            state.known = FileFingerprint::read(&state.path, None);
+            state.current = state.known.clone();
+            state.notified = false;
+        }
+    }
+
+    /// Check watched files, returning those that have changed since the workbook was
+    /// opened or saved and that sessions have not yet been notified about
+    pub fn poll(&self, file_name: &str, workspace: &Workspace) -> Vec<WatchedFile> {
+        let mut files = self.files.lock().unwrap();
+        Self::refresh_files(&mut files, file_name, workspace);
+        let mut changed = Vec::new();
+        for (file, state) in files.iter_mut() {
+            let current = FileFingerprint::read(&state.path, state.current.as_ref());
+            if FileFingerprint::same(&current, &state.known) {
+                state.notified = false;
+            } else if !(state.notified && FileFingerprint::same(&current, &state.current)) {
+                state.notified = true;
+                changed.push(file.clone());
+            }
+            state.current = current;
+        }
+        changed
+    }
+
+    /// Return watched files that currently differ from the version on disk when the
+    /// workbook was opened or saved
+    pub fn get_changed_files(&self) -> Vec<WatchedFile> {
+        self.files
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter_map(|(file, state)| {
+                state.current = FileFingerprint::read(&state.path, state.current.as_ref());
+                if FileFingerprint::same(&state.current, &state.known) {
+                    None
+                } else {
+                    Some(file.clone())
+                }
+            })
+            .collect()
+    }
+
+    /// Add files referenced by the workspace that are not yet watched (treating their
+    /// content as known) and stop watching files no longer referenced
+    fn refresh_files(
+        files: &mut HashMap<WatchedFile, WatchedFileState>,
+        file_name: &str,
+        workspace: &Workspace,
+    ) {
+        if file_name.is_empty() {
+            files.clear();
+            return;
+        }
+
+        let workbook_path = PathBuf::from(file_name);
+        let mut paths = vec![
+            (WatchedFile::Workbook, workbook_path.clone()),
+            (
+                WatchedFile::WorkbookDirectory,
+                get_directory_name(&workbook_path),
+            ),
+            (
+                WatchedFile::Private,
+                Parameters::get_workbook_private_filename(&workbook_path),
+            ),
+            (
+                WatchedFile::Extensions,
+                WorkbookExtensions::get_file_name(&workbook_path),
+            ),
+        ];
+        if let Some(directory) = std::path::absolute(&workbook_path)
+            .ok()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        {
+            for data_set in workspace.data.entities.values() {
+                if matches!(
+                    data_set.source_type,
+                    DataSourceType::FileJSON | DataSourceType::FileCSV
+                ) && !data_set.source.is_empty()
+                    && let Ok(path) = build_absolute_file_name(&data_set.source, &directory)
+                {
+                    paths.push((WatchedFile::DataSet(data_set.id.clone()), path));
+                }
+            }
+        }
+
+        files.retain(|file, state| paths.iter().any(|(f, p)| f == file && *p == state.path));
+        for (file, path) in paths {
+            files.entry(file).or_insert_with(|| {
+                let known = FileFingerprint::read(&path, None);
+                WatchedFileState {
+                    path,
+                    current: known.clone(),
+                    known,
+                    notified: false,
+                }
+            });
+        }
+    }
+}
+
+/// Sent to a workspace's sessions when watched files have changed on disk
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalChangeNotification {
+    pub files: Vec<WatchedFile>,
+    /// True if the workspace has unsaved changes, which reloading would discard
+    pub dirty: bool,
+}
+
+/// Entity changed both locally and on disk, for which the local change is kept
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub name: String,
+}
+
+/// Merge changes made on disk since `base` into `local`.  Entities changed only on disk
+/// (including additions and deletions) are taken from disk, entities changed only locally
+/// are kept, and entities changed in both are kept as they are locally and reported as
+/// conflicts.  Positions of entities that exist in both are kept as they are locally.
+pub fn merge_workspace(
+    local: &mut Workspace,
+    base: Option<&Workspace>,
+    disk: &Workspace,
+) -> Vec<MergeConflict> {
+    let mut conflicts = Vec::new();
+    merge_entities(
+        EntityType::RequestEntry,
+        &mut local.requests,
+        base.map(|b| &b.requests),
+        &disk.requests,
+        &mut conflicts,
+    );
+    merge_entities(
+        EntityType::Scenario,
+        &mut local.scenarios,
+        base.map(|b| &b.scenarios),
+        &disk.scenarios,
+        &mut conflicts,
+    );
+    merge_entities(
+        EntityType::Authorization,
+        &mut local.authorizations,
+        base.map(|b| &b.authorizations),
+        &disk.authorizations,
+        &mut conflicts,
+    );
+    merge_entities(
+        EntityType::Certificate,
+        &mut local.certificates,
+        base.map(|b| &b.certificates),
+        &disk.certificates,
+        &mut conflicts,
+    );
+    merge_entities(
+        EntityType::Proxy,
+        &mut local.proxies,
+        base.map(|b| &b.proxies),
+        &disk.proxies,
+        &mut conflicts,
+    );
+    merge_entities(
+        EntityType::DataSet,
+        &mut local.data,
+        base.map(|b| &b.data),
+        &disk.data,
+        &mut conflicts,
+    );
+
+    let base_defaults = base.map(|b| &b.defaults);
+    if local.defaults != disk.defaults {
+        if base_defaults == Some(&local.defaults) {
+            local.defaults = disk.defaults.clone();
+        } else if base_defaults != Some(&disk.defaults) {
+            conflicts.push(MergeConflict {
+                entity_type: EntityType::Defaults,
+                entity_id: Navigation::DEFAULTS_ID.to_string(),
+                name: "Defaults".to_string(),
+            });
+        }
+    }
+
+    conflicts
+}
+
+fn merge_entities<T: Identifiable + Clone + PartialEq>(
+    entity_type: EntityType,
+    local: &mut IndexedEntities<T>,
+    base: Option<&IndexedEntities<T>>,
+    disk: &IndexedEntities<T>,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let get_base = |id: &str| base.and_then(|b| b.entities.get(id));
+    let mut add_conflict = |entity: &T| {
+        conflicts.push(MergeConflict {
+            entity_type,
+            entity_id: entity.get_id().to_string(),
+            name: entity.get_name().to_string(),
+        })
+    };
+
+    // Apply updates and deletions of entities that exist locally
+    for id in get_ordered_ids(local) {
+        // Entity may have been removed along with a parent
+        let Some(local_entity) = local.entities.get(&id) else {
+            continue;
+        };
+        let disk_entity = disk.entities.get(&id);
+        if disk_entity == Some(local_entity) {
+            continue;
+        }
+        let base_entity = get_base(&id);
+        if base_entity == Some(local_entity) {
+            match disk_entity {
+                Some(disk_entity) => {
+                    local.entities.insert(id, disk_entity.clone());
+                }
+                // Keep entities deleted on disk if descendants were changed or added
+                // locally, which are reported as conflicts themselves
+                None if has_local_descendant_changes(local, base, &id) => {
+                    add_conflict(local_entity);
+                }
+                None => {
+                    local.remove_entity(&id).ok();
+                }
+            }
+        } else if base_entity != disk_entity {
+            add_conflict(local_entity);
+        }
+    }
+
+    // Add entities that only exist on disk, in order so that each is positioned relative
+    // to one that has already been merged
+    for id in get_ordered_ids(disk) {
+        if local.entities.contains_key(&id) {
+            continue;
+        }
+        let disk_entity = &disk.entities[&id];
+        match get_base(&id) {
+            // Deleted locally and unchanged on disk
+            Some(base_entity) if base_entity == disk_entity => {}
+            // Deleted locally and changed on disk
+            Some(_) => add_conflict(disk_entity),
+            // Added on disk
+            None => {
+                let (relative_to_id, relative_position) = match find_anchor(disk, &id) {
+                    Some((anchor_id, position))
+                        if local.entities.contains_key(&anchor_id)
+                            || (position == IndexedEntityPosition::Under
+                                && !disk.entities.contains_key(&anchor_id)) =>
+                    {
+                        (Some(anchor_id), Some(position))
+                    }
+                    _ => (None, None),
+                };
+                local
+                    .add_entity(
+                        disk_entity.clone(),
+                        relative_to_id.as_deref(),
+                        relative_position,
+                    )
+                    .ok();
+            }
+        }
+    }
+}
+
+/// Returns true if any descendant of the entity differs from the base or was added locally
+fn has_local_descendant_changes<T: PartialEq>(
+    local: &IndexedEntities<T>,
+    base: Option<&IndexedEntities<T>>,
+    id: &str,
+) -> bool {
+    let mut pending = local.child_ids.get(id).cloned().unwrap_or_default();
+    while let Some(child_id) = pending.pop() {
+        if base.and_then(|b| b.entities.get(&child_id)) != local.entities.get(&child_id) {
+            return true;
+        }
+        if let Some(child_ids) = local.child_ids.get(&child_id) {
+            pending.extend(child_ids.iter().cloned());
+        }
+    }
+    false
+}
+
+/// Merge changes made on disk to workbook extensions since `base` into `local`, in the
+/// same way as workspace entities; conflicting settings are reported against the entity
+/// they belong to in the merged workspace
+pub fn merge_extensions(
+    local: &mut WorkbookExtensions,
+    base: Option<&WorkbookExtensions>,
+    disk: &WorkbookExtensions,
+    workspace: &Workspace,
+) -> Vec<MergeConflict> {
+    let mut conflicts = Vec::new();
+    merge_entities(
+        EntityType::TrustedRoots,
+        &mut local.trusted_roots,
+        base.map(|b| &b.trusted_roots),
+        &disk.trusted_roots,
+        &mut conflicts,
+    );
+
+    let mut conflict_ids = Vec::<(EntityType, String)>::new();
+    let mut merge = |entity_type: EntityType, conflicting: Vec<String>| {
+        conflict_ids.extend(conflicting.into_iter().map(|id| (entity_type, id)));
+    };
+    merge(
+        EntityType::Proxy,
+        merge_settings(
+            &mut local.proxy_settings,
+            base.map(|b| &b.proxy_settings),
+            &disk.proxy_settings,
+        ),
+    );
+    merge(
+        EntityType::Authorization,
+        merge_settings(
+            &mut local.authorization_settings,
+            base.map(|b| &b.authorization_settings),
+            &disk.authorization_settings,
+        ),
+    );
+    merge(
+        EntityType::Scenario,
+        merge_settings(
+            &mut local.secret_variables,
+            base.map(|b| &b.secret_variables),
+            &disk.secret_variables,
+        ),
+    );
+    merge(
+        EntityType::RequestEntry,
+        merge_settings(
+            &mut local.selected_trusted_roots,
+            base.map(|b| &b.selected_trusted_roots),
+            &disk.selected_trusted_roots,
+        ),
+    );
+
+    for (entity_type, entity_id) in conflict_ids {
+        let (entity_type, name) = match entity_type {
+            EntityType::Proxy => (
+                entity_type,
+                workspace
+                    .proxies
+                    .entities
+                    .get(&entity_id)
+                    .map(|e| e.get_name()),
+            ),
+            EntityType::Authorization => (
+                entity_type,
+                workspace
+                    .authorizations
+                    .entities
+                    .get(&entity_id)
+                    .map(|e| e.get_name()),
+            ),
+            EntityType::Scenario => (
+                entity_type,
+                workspace
+                    .scenarios
+                    .entities
+                    .get(&entity_id)
+                    .map(|e| e.get_name()),
+            ),
+            _ if entity_id == Navigation::DEFAULTS_ID => (EntityType::Defaults, Some("Defaults")),
+            _ => (
+                entity_type,
+                workspace
+                    .requests
+                    .entities
+                    .get(&entity_id)
+                    .map(|e| e.get_name()),
+            ),
+        };
+        conflicts.push(MergeConflict {
+            entity_type,
+            name: name.unwrap_or(&entity_id).to_string(),
+            entity_id,
+        });
+    }
+    conflicts
+}
+
+/// Merge settings indexed by entity ID, returning the IDs of settings changed both locally
+/// and on disk, which are kept as they are locally
+fn merge_settings<T: Clone + PartialEq>(
+    local: &mut BTreeMap<String, T>,
+    base: Option<&BTreeMap<String, T>>,
+    disk: &BTreeMap<String, T>,
+) -> Vec<String> {
+    let mut conflicting = Vec::new();
+    let ids = local
+        .keys()
+        .chain(disk.keys())
+        .cloned()
+        .collect::<std::collections::BTreeSet<_>>();
+    for id in ids {
+        let local_value = local.get(&id);
+        let disk_value = disk.get(&id);
+        if local_value == disk_value {
+            continue;
+        }
+        let base_value = base.and_then(|b| b.get(&id));
+        if base_value == local_value {
+            match disk_value {
+                Some(disk_value) => {
+                    local.insert(id, disk_value.clone());
+                }
+                None => {
+                    local.remove(&id);
+                }
+            }
+        } else if base_value != disk_value {
+            conflicting.push(id);
+        }
+    }
+    conflicting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apicize_lib::{
+        Authorization, ParameterLockStatus, ParameterStore, PersistedIndex, Request, RequestEntry,
+        RequestGroup, SaveWorkspaceParameters, Scenario, ScenarioPlain, WorkbookDefaultParameters,
+    };
+
+    use crate::{
+        authorization_settings::{AuthorizationSettings, ExtendedAuthorization},
+        digest_auth::DigestParameters,
+        workspaces::{PasswordLockType, Workspaces},
+    };
+
+    fn request(id: &str, url: &str) -> RequestEntry {
+        RequestEntry::Request(Request {
+            id: id.to_string(),
+            name: id.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn group(id: &str) -> RequestEntry {
+        RequestEntry::Group(RequestGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn index(entries: Vec<(RequestEntry, Option<&str>)>) -> IndexedEntities<RequestEntry> {
+        let mut index = IndexedEntities::<RequestEntry>::default();
+        for (entry, parent_id) in entries {
+            index
+                .add_entity(
+                    entry,
+                    parent_id,
+                    parent_id.map(|_| IndexedEntityPosition::Under),
+                )
+                .unwrap();
+        }
+        index
+    }
+
+    fn merge(
+        local: &mut IndexedEntities<RequestEntry>,
+        base: &IndexedEntities<RequestEntry>,
+        disk: &IndexedEntities<RequestEntry>,
+    ) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        merge_entities(
+            EntityType::RequestEntry,
+            local,
+            Some(base),
+            disk,
+            &mut conflicts,
+        );
+        conflicts.into_iter().map(|c| c.entity_id).collect()
+    }
+
+    #[test]
+    fn merges_changes_made_on_disk_and_locally() {
+        let base = index(vec![
+            (request("a", "a"), None),
+            (request("b", "b"), None),
+            (request("c", "c"), None),
+            (group("g"), None),
+        ]);
+        // Locally: update a, conflicting update to c
+        let mut local = index(vec![
+            (request("a", "a2"), None),
+            (request("b", "b"), None),
+            (request("c", "local"), None),
+            (group("g"), None),
+        ]);
+        // On disk: update b, conflicting update to c, add d under g
+        let disk = index(vec![
+            (request("a", "a"), None),
+            (request("b", "b2"), None),
+            (request("c", "disk"), None),
+            (group("g"), None),
+            (request("d", "d"), Some("g")),
+        ]);
+
+        assert_eq!(merge(&mut local, &base, &disk), vec!["c"]);
+        let url = |id: &str| match &local.entities[id] {
+            RequestEntry::Request(r) => r.url.clone(),
+            RequestEntry::Group(_) => String::default(),
+        };
+        assert_eq!(url("a"), "a2");
+        assert_eq!(url("b"), "b2");
+        assert_eq!(url("c"), "local");
+        assert_eq!(local.child_ids.get("g"), Some(&vec!["d".to_string()]));
+    }
+
+    #[test]
+    fn keeps_deleted_groups_with_locally_changed_descendants() {
+        let base = index(vec![
+            (group("g"), None),
+            (request("g1", "g1"), Some("g")),
+            (request("g2", "g2"), Some("g")),
+        ]);
+        let mut local = index(vec![
+            (group("g"), None),
+            (request("g1", "local"), Some("g")),
+            (request("g2", "g2"), Some("g")),
+        ]);
+        let disk = index(vec![]);
+
+        assert_eq!(merge(&mut local, &base, &disk), vec!["g", "g1"]);
+        assert_eq!(local.top_level_ids, vec!["g"]);
+        assert_eq!(local.child_ids.get("g"), Some(&vec!["g1".to_string()]));
+    }
+
+    #[test]
+    fn merges_settings_changed_on_disk() {
+        let base = BTreeMap::from([("a", 1), ("b", 1), ("c", 1)].map(|(k, v)| (k.to_string(), v)));
+        let mut local =
+            BTreeMap::from([("a", 2), ("b", 1), ("c", 2)].map(|(k, v)| (k.to_string(), v)));
+        let disk = BTreeMap::from([("a", 1), ("c", 3), ("d", 1)].map(|(k, v)| (k.to_string(), v)));
+
+        assert_eq!(merge_settings(&mut local, Some(&base), &disk), vec!["c"]);
+        assert_eq!(
+            local,
+            BTreeMap::from([("a", 2), ("c", 2), ("d", 1)].map(|(k, v)| (k.to_string(), v)))
+        );
+    }
+
+    #[test]
+    fn takes_deletions_only_of_unchanged_entities() {
+        let base = index(vec![(request("a", "a"), None), (request("b", "b"), None)]);
+        let mut local = index(vec![(request("a", "a"), None), (request("b", "b2"), None)]);
+        let disk = index(vec![]);
+
+        assert_eq!(merge(&mut local, &base, &disk), vec!["b"]);
+        assert_eq!(local.top_level_ids, vec!["b"]);
+    }
+
+    #[test]
+    fn ignores_files_written_when_changing_passwords() {
+        let directory = tempfile::tempdir().unwrap();
+        let workbook_path = directory.path().join("test.apicize");
+        let file_name = workbook_path.to_string_lossy().to_string();
+        let digest = ExtendedAuthorization::Digest(DigestParameters {
+            username: "user".to_string(),
+            password: "secret".to_string(),
+        });
+        let workspace = Workspace {
+            requests: IndexedEntities::default(),
+            scenarios: IndexedEntities::<Scenario>::new(
+                None,
+                Some(vec![Scenario::Plain(Box::new(ScenarioPlain {
+                    id: "scenario".to_string(),
+                    name: "Scenario".to_string(),
+                    ..Default::default()
+                }))]),
+                None,
+            ),
+            authorizations: IndexedEntities::<Authorization>::new(
+                None,
+                Some(vec![Authorization::Plain(Box::new(
+                    digest.create_placeholder("auth", "Auth"),
+                ))]),
+                None,
+            ),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters::default(),
+            private_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        };
+        let extensions = WorkbookExtensions {
+            authorization_settings: BTreeMap::from([(
+                "auth".to_string(),
+                AuthorizationSettings::Plain(digest),
+            )]),
+            ..Default::default()
+        };
+        workspace
+            .save(&SaveWorkspaceParameters {
+                workbook_path: Some(workbook_path.clone()),
+                include_workbook: true,
+                include_private: true,
+                include_vault: false,
+            })
+            .unwrap();
+        extensions
+            .save_workbook(&workbook_path, &workspace)
+            .unwrap();
+
+        let mut workspaces = Workspaces::default();
+        let workspace_id = workspaces
+            .add_workspace(workspace, extensions, &file_name, false)
+            .workspace_id;
+        let poll = |workspaces: &Workspaces| {
+            let info = workspaces.get_workspace_info(&workspace_id).unwrap();
+            info.external_changes
+                .poll(&file_name, &info.workspace)
+                .len()
+        };
+
+        workspaces
+            .set_parameters_password(
+                &workspace_id,
+                Some(workbook_path.clone()),
+                ParameterStore::Private,
+                PasswordLockType::Password {
+                    password: "first".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(poll(&workspaces), 0);
+
+        workspaces
+            .change_parameters_password(
+                &workspace_id,
+                Some(workbook_path.clone()),
+                ParameterStore::Private,
+                "first",
+                PasswordLockType::Password {
+                    password: "second".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(poll(&workspaces), 0);
+
+        fs::write(
+            Parameters::get_workbook_private_filename(&workbook_path),
+            "{}",
+        )
+        .unwrap();
+        assert_eq!(poll(&workspaces), 1);
+    }
+}
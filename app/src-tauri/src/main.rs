@@ -1,43 +1,84 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod authorization_settings;
+mod aws_sigv4;
+pub mod certificate_generation;
 pub mod clipboard;
 pub mod codegeneration;
+mod digest_auth;
 pub mod dragdrop;
 pub mod error;
+pub mod extensions;
+mod file_watching;
+mod jwt_inspection;
 pub mod navigation;
+mod oauth2_grants;
+mod oidc_discovery;
+mod password_keyring;
 pub mod pkce;
+mod proxy_resolution;
+mod proxy_settings;
+mod recovery;
+mod search;
+mod secret_scanning;
+mod secret_variables;
+mod selections;
 pub mod sessions;
 pub mod settings;
+mod substitution;
+mod token_cache;
 pub mod trace;
+pub mod trusted_roots;
+mod undo_history;
 pub mod updates;
+mod workbook_directory;
 pub mod workspaces;
 use apicize_lib::{
     ApicizeError, ApicizeRunner, Authorization, CachedTokenInfo, Certificate, DataSet,
     DataSourceType, ExecutionProgress, ExecutionResultDetail, ExecutionState, Identifiable,
-    IndexedEntities, OAuth2ClientCredentialParameters, OpenWorkbookOptions, PERSIST_PRIVATE,
-    PERSIST_VAULT, ParameterLockStatus, ParameterStore, Parameters, PkceTokenResult, Proxy,
-    RequestBody, RequestEntry, SaveWorkspaceParameters, Scenario, TestRunnerContext,
+    IndexedEntities, OAuth2ClientCredentialParameters, PERSIST_PRIVATE, PERSIST_VAULT,
+    ParameterLockStatus, ParameterStore, Parameters, PkceTokenResult, Proxy, RequestBody,
+    RequestEntry, SaveWorkspaceParameters, Scenario, Selection, TestRunnerContext,
     TestRunnerContextInit, TokenResult, Validated, Workspace, authorization::AuthorizationPlain,
     build_absolute_file_name, clear_all_oauth2_tokens_from_cache, clear_oauth2_token_from_cache,
     editing::indexed_entities::IndexedEntityPosition, get_existing_absolute_file_name,
-    get_oauth2_client_credentials, get_relative_file_name, store_oauth2_token_in_cache,
+    get_oauth2_client_credentials, get_relative_file_name, retrieve_oauth2_token_from_cache,
+    store_oauth2_token_in_cache,
 };
 
+use authorization_settings::{AuthorizationSettings, ExtendedAuthorization};
+use certificate_generation::{CertificateGenerationRequest, generate_certificate};
 use clipboard::{ClipboardData, ClipboardDataType, ClipboardState};
 use codegeneration::{CodeGenLanguage, generate_code};
 use dirs::home_dir;
 use dragdrop::DroppedFile;
 use error::ApicizeAppError;
-use navigation::{Navigation, UpdateResponse, UpdatedNavigationEntry};
+use extensions::WorkbookExtensions;
+use file_watching::{ExternalChangeNotification, MergeConflict, WatchedFile};
+use jwt_inspection::{JwtInspection, JwtSource, JwtVerificationKey};
+use navigation::{
+    Navigation, UpdateResponse, UpdateWithNavigationResponse, UpdatedNavigationEntry,
+};
+use oauth2_grants::{DeviceAuthorizationPrompt, build_client, get_certificate_pem};
+use oidc_discovery::{OidcConfiguration, OidcDiscoveryResponse};
 use pathdiff::diff_paths;
-use pkce::{OAuth2PkceInfo, OAuth2PkceRequest, OAuth2PkceService};
+use pkce::{OAuth2PkceInfo, OAuth2PkceRequest, OAuth2PkceService, PkceFlowOptions};
+use proxy_resolution::{ProxyResolution, resolve_proxy};
+use proxy_settings::ProxySettings;
+use recovery::{RecoverySnapshot, WorkspaceSnapshot};
 use rustc_hash::FxHashMap;
+use search::{
+    ReplaceOptions, ReplaceResults, SearchOptions, SearchResults, plan_replacement,
+    search_workspace,
+};
+use secret_scanning::{SecretFinding, scan_workspace};
+use secret_variables::{ScenarioSecretVariables, apply_secret_values, resolve_secret_variables};
 use serde::{Deserialize, Serialize};
 use sessions::{ExecutionResultViewState, Session, SessionSaveState, Sessions};
 use settings::{ApicizeSettings, ColorScheme};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env,
     fs::{self, create_dir_all, exists, remove_dir_all},
     io::{self, BufWriter},
@@ -52,26 +93,36 @@ use tauri::async_runtime::RwLock;
 use tauri::{
     AppHandle, Emitter, LogicalSize, Manager, PhysicalSize, State, WebviewWindowBuilder, Wry,
 };
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use token_cache::{CachedTokenSummary, TokenRenewalEvent};
 use tokio_util::sync::CancellationToken;
 use trace::{ReqwestEvent, ReqwestLogger};
+use trusted_roots::TrustedRoots;
+use undo_history::UndoOperation;
+use workbook_directory::WorkbookFormat;
 use workspaces::{
     BodyMimeInfo, ClipboardPayloadRequest, Entity, EntityType, ExecutionEvent,
     OpenDataSetFileResponse, OpenWorkspaceResult, PersistableData, RequestBodyInfo,
-    RequestEntryInfo, RequestExecution, WorkspaceInfo, WorkspaceInitialization, WorkspaceMode,
-    WorkspaceParameters, WorkspaceSaveStatus, Workspaces,
+    RequestEntryInfo, RequestExecution, SaveWorkspaceResponse, WorkspaceInfo, WorkspaceMode,
+    WorkspaceParameters, WorkspaceSaveStatus, Workspaces, open_workbook, save_workbook,
 };
 
 use crate::{
     sessions::SessionEntity,
     updates::{
-        AuthorizationUpdate, CertificateUpdate, DataSetUpdate, DefaultsUpdate, EntityUpdate,
-        ProxyUpdate, RequestGroupUpdate, RequestUpdate, ScenarioUpdate,
+        AuthorizationUpdate, AuthorizationUpdateType, BulkRequestUpdate, CertificateUpdate,
+        DataSetUpdate, EntityUpdate, ProxyUpdate, RequestGroupUpdate, RequestUpdate,
+        ScenarioUpdate,
     },
     workspaces::{DataSetContent, ExecutionCounterResult, PasswordLockType, increment_counters},
 };
 
 struct AuthState {
     pkce: Mutex<OAuth2PkceService>,
+    /// Cancellation tokens for device authorization flows being polled, indexed by device code
+    device_flows: Mutex<HashMap<String, CancellationToken>>,
 }
 
 struct SettingsState {
@@ -130,6 +181,7 @@ async fn main() {
             {
                 load_workbook = Some(file_argument.to_string());
             }
+            let opened_from_argument = load_workbook.is_some();
 
             let mut settings = if let Ok(loaded_settings) = ApicizeSettings::open() {
                 if load_workbook.is_none() {
@@ -189,18 +241,77 @@ async fn main() {
 
             log::set_max_level(log::LevelFilter::Trace);
 
-            create_workspace(
-                app.handle().clone(),
-                &mut sessions,
-                &mut workspaces,
-                &mut settings,
-                ClipboardDataType::None,
-                load_workbook.map(OpenExisting::FileName),
-                true,
-                None,
-                true,
-            )
-            .unwrap();
+            // Unsaved changes from a run that did not exit normally are restored ahead of the
+            // last workbook, unless a workbook was passed in as an argument
+            let mut snapshots = recovery::list_snapshots();
+            let mut recovered = Vec::<(String, RecoverySnapshot)>::new();
+            if !opened_from_argument && !snapshots.is_empty() {
+                let snapshot = snapshots.remove(0);
+                match create_workspace(
+                    app.handle().clone(),
+                    &mut sessions,
+                    &mut workspaces,
+                    &mut settings,
+                    ClipboardDataType::None,
+                    Some(OpenExisting::Recovery(snapshot.id.clone())),
+                    false,
+                    None,
+                    true,
+                ) {
+                    Ok(session_id) => recovered.push((session_id, snapshot)),
+                    Err(err) => {
+                        eprintln!("Unable to restore recovery snapshot {}: {err}", snapshot.id)
+                    }
+                }
+            }
+
+            if recovered.is_empty() {
+                create_workspace(
+                    app.handle().clone(),
+                    &mut sessions,
+                    &mut workspaces,
+                    &mut settings,
+                    ClipboardDataType::None,
+                    load_workbook.map(OpenExisting::FileName),
+                    true,
+                    None,
+                    true,
+                )
+                .unwrap();
+            }
+
+            for snapshot in snapshots {
+                match create_workspace(
+                    app.handle().clone(),
+                    &mut sessions,
+                    &mut workspaces,
+                    &mut settings,
+                    ClipboardDataType::None,
+                    Some(OpenExisting::Recovery(snapshot.id.clone())),
+                    false,
+                    None,
+                    true,
+                ) {
+                    Ok(session_id) => recovered.push((session_id, snapshot)),
+                    Err(err) => {
+                        eprintln!("Unable to restore recovery snapshot {}: {err}", snapshot.id)
+                    }
+                }
+            }
+
+            // Restore persisted OAuth2 tokens if the vault password is in the environment
+            if !settings.persisted_token_authorization_ids.is_empty()
+                && let Some(password) = workspaces.get_vault_password()
+            {
+                let authorization_ids = settings.persisted_token_authorization_ids.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) =
+                        token_cache::restore_tokens(&password, &authorization_ids).await
+                    {
+                        eprintln!("Unable to restore OAuth2 tokens: {err}");
+                    }
+                });
+            }
 
             // Set up sessions
             app.manage(SessionsState {
@@ -210,6 +321,7 @@ async fn main() {
             // Set up PKCE service
             let auth_state = AuthState {
                 pkce: Mutex::new(OAuth2PkceService::new(app.handle().clone())),
+                device_flows: Mutex::new(HashMap::new()),
             };
 
             if settings.pkce_listener_port > 0 {
@@ -234,6 +346,12 @@ async fn main() {
 
             app.manage(ClipboardState::new(app.handle().clone()));
 
+            if !recovered.is_empty() {
+                offer_recovery(app.handle().clone(), recovered);
+            }
+            tauri::async_runtime::spawn(snapshot_workspaces(app.handle().clone()));
+            tauri::async_runtime::spawn(watch_workbook_files(app.handle().clone()));
+
             Ok(())
         })
         .plugin(tauri_plugin_os::init())
@@ -264,13 +382,21 @@ async fn main() {
             save_workspace,
             close_workspace,
             set_parameters_password,
+            change_parameters_password,
+            forget_parameters_password,
             decrypt_parameters,
             show_session,
             get_workspace_save_status,
+            scan_workspace_secrets,
+            search,
+            preview_replace,
+            replace,
             open_settings,
             save_settings,
             start_execution,
             cancel_execution,
+            get_untrusted_secret_commands,
+            trust_secret_commands,
             get_execution,
             clear_execution,
             get_execution_result,
@@ -278,6 +404,7 @@ async fn main() {
             get_execution_result_view_state,
             update_execution_result_view_state,
             store_token,
+            set_oauth2_token_persistence,
             clear_all_cached_authorizations,
             clear_cached_authorization,
             // get_environment_variables,
@@ -288,9 +415,16 @@ async fn main() {
             update_request_body_from_clipboard,
             set_pkce_port,
             generate_authorization_info,
+            cancel_pkce_flow,
             // launch_pkce_window,
             retrieve_oauth2_client_token,
             retrieve_oauth2_pkce_token,
+            start_oauth2_device_flow,
+            cancel_oauth2_device_flow,
+            retrieve_oauth2_grant_token,
+            discover_oauth2_configuration,
+            list_cached_tokens,
+            inspect_jwt,
             refresh_token,
             get,
             update_active_entity,
@@ -300,12 +434,27 @@ async fn main() {
             get_title,
             get_dirty,
             get_request_active_authorization,
+            get_request_active_trusted_roots,
+            get_selected_trusted_roots,
+            get_proxy_settings,
+            get_authorization_settings,
+            get_secret_variables,
+            sign_aws_request,
+            respond_to_digest_challenge,
+            test_proxy_resolution,
             get_data_set_content,
             list_parameters,
             add,
+            generate_client_certificate,
             update,
+            bulk_update_requests,
             delete,
             move_entity,
+            undo,
+            redo,
+            reload_workspace,
+            merge_workspace,
+            move_parameter_to_store,
             list_logs,
             clear_logs,
             get_entity_type,
@@ -345,12 +494,292 @@ async fn main() {
                     for token in tokens.values() {
                         token.cancel();
                     }
+
+                    // Unsaved changes are only recovered if Apicize does not exit normally
+                    recovery::retain_snapshots(&HashSet::new());
                 }
                 _ => {}
             }
         });
 }
 
+/// Periodically snapshot workspaces with unsaved changes, so that they can be recovered
+/// if Apicize does not exit normally
+async fn snapshot_workspaces(app: AppHandle) {
+    loop {
+        let interval = app
+            .state::<SettingsState>()
+            .settings
+            .read()
+            .await
+            .recovery_interval;
+        // When disabled, check periodically in case snapshots are re-enabled
+        tokio::time::sleep(std::time::Duration::from_secs(if interval == 0 {
+            30
+        } else {
+            interval
+        }))
+        .await;
+
+        // Copy dirty workspaces while locked, and write them once the lock is released
+        let snapshots = if interval > 0 {
+            let workspaces_state = app.state::<WorkspacesState>();
+            let workspaces = workspaces_state.workspaces.read().await;
+            workspaces
+                .workspaces
+                .iter()
+                .filter(|(_, info)| info.dirty)
+                .map(|(workspace_id, info)| (workspace_id.clone(), WorkspaceSnapshot::from(info)))
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            for (workspace_id, snapshot) in &snapshots {
+                if let Err(err) = recovery::save_snapshot(workspace_id, snapshot) {
+                    eprintln!("Unable to save recovery snapshot for {workspace_id}: {err}");
+                }
+            }
+            let snapshot_ids = snapshots
+                .iter()
+                .map(|(workspace_id, _)| workspace_id.as_str())
+                .collect::<HashSet<_>>();
+            recovery::retain_snapshots(&snapshot_ids);
+        })
+        .await;
+        if let Err(err) = result {
+            eprintln!("Unable to save recovery snapshots: {err}");
+        }
+    }
+}
+
+/// Let the user keep or discard workspaces restored from recovery snapshots
+fn offer_recovery(app: AppHandle, recovered: Vec<(String, RecoverySnapshot)>) {
+    let names = recovered
+        .iter()
+        .map(|(_, snapshot)| {
+            if snapshot.display_name.is_empty() {
+                "(New)"
+            } else {
+                snapshot.display_name.as_str()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    app.dialog()
+        .message(format!(
+            "Apicize did not exit normally, unsaved changes to {names} have been restored. Do you want to keep these changes?"
+        ))
+        .title("Recover Unsaved Changes")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Keep".to_string(),
+            "Discard".to_string(),
+        ))
+        .show(move |keep| {
+            if !keep {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) = discard_recovered_workspaces(&app, recovered).await {
+                        eprintln!("Unable to discard recovered changes: {err}");
+                    }
+                });
+            }
+        });
+}
+
+/// Replace recovered workspaces with their last saved workbooks (or new workbooks)
+async fn discard_recovered_workspaces(
+    app: &AppHandle,
+    recovered: Vec<(String, RecoverySnapshot)>,
+) -> Result<(), ApicizeAppError> {
+    let sessions_state = app.state::<SessionsState>();
+    let workspaces_state = app.state::<WorkspacesState>();
+    let settings_state = app.state::<SettingsState>();
+    let clipboard_data_type = app.state::<ClipboardState>().get_data_type();
+
+    let mut sessions = sessions_state.sessions.write().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let mut settings = settings_state.settings.write().await;
+
+    for (session_id, snapshot) in recovered {
+        let Ok(session) = sessions.get_session(&session_id) else {
+            continue;
+        };
+        let recovered_workspace_id = session.workspace_id.clone();
+        create_workspace(
+            app.clone(),
+            &mut sessions,
+            &mut workspaces,
+            &mut settings,
+            clipboard_data_type,
+            if snapshot.file_name.is_empty() {
+                None
+            } else {
+                Some(OpenExisting::FileName(snapshot.file_name))
+            },
+            true,
+            Some(session_id),
+            false,
+        )?;
+        if sessions
+            .get_workspace_session_ids(&recovered_workspace_id)
+            .is_empty()
+        {
+            workspaces.remove_workspace(&recovered_workspace_id);
+        }
+    }
+    Ok(())
+}
+
+/// Periodically check open workbooks, their private parameters, extensions and external data
+/// set files for changes made outside of Apicize, notifying sessions and offering to reload or
+/// merge
+async fn watch_workbook_files(app: AppHandle) {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+        let sessions_state = app.state::<SessionsState>();
+        let workspaces_state = app.state::<WorkspacesState>();
+        let sessions = sessions_state.sessions.read().await;
+        let workspaces = workspaces_state.workspaces.read().await;
+        for (workspace_id, info) in workspaces.workspaces.iter() {
+            let files = info.external_changes.poll(&info.file_name, &info.workspace);
+            if files.is_empty() {
+                continue;
+            }
+            let session_ids = sessions.get_workspace_session_ids(workspace_id);
+            let notification = ExternalChangeNotification {
+                files,
+                dirty: info.dirty,
+            };
+            for session_id in &session_ids {
+                app.emit_to(*session_id, "external_change", &notification)
+                    .unwrap();
+            }
+            if let Some(session_id) = session_ids.first() {
+                offer_external_change_resolution(
+                    app.clone(),
+                    session_id.to_string(),
+                    &info.display_name,
+                    info.dirty,
+                );
+            }
+        }
+    }
+}
+
+/// Let the user reload or merge a workbook changed outside of Apicize
+fn offer_external_change_resolution(
+    app: AppHandle,
+    session_id: String,
+    display_name: &str,
+    dirty: bool,
+) {
+    let (message, buttons) = if dirty {
+        (
+            format!(
+                "{display_name} has been changed outside of Apicize. Do you want to reload it, discarding your unsaved changes, or merge the changes with your unsaved changes?"
+            ),
+            MessageDialogButtons::YesNoCancelCustom(
+                "Reload".to_string(),
+                "Merge".to_string(),
+                "Ignore".to_string(),
+            ),
+        )
+    } else {
+        (
+            format!(
+                "{display_name} has been changed outside of Apicize. Do you want to reload it?"
+            ),
+            MessageDialogButtons::OkCancelCustom("Reload".to_string(), "Ignore".to_string()),
+        )
+    };
+
+    app.dialog()
+        .message(message)
+        .title("Workbook Changed")
+        .kind(MessageDialogKind::Warning)
+        .buttons(buttons)
+        .show_with_result(move |result| {
+            let merge = match result {
+                MessageDialogResult::Yes | MessageDialogResult::Ok => false,
+                MessageDialogResult::No => true,
+                MessageDialogResult::Custom(label) if label == "Reload" => false,
+                MessageDialogResult::Custom(label) if label == "Merge" => true,
+                _ => return,
+            };
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = apply_external_changes(&app, &session_id, merge).await {
+                    app.dialog()
+                        .message(format!("Unable to apply changes: {err}"))
+                        .title("Workbook Changed")
+                        .kind(MessageDialogKind::Error)
+                        .show(|_| {});
+                }
+            });
+        });
+}
+
+/// Reload or merge changes made outside of Apicize into the session's workspace, and
+/// re-initialize the workspace's sessions
+async fn apply_external_changes(
+    app: &AppHandle,
+    session_id: &str,
+    merge: bool,
+) -> Result<Vec<MergeConflict>, ApicizeAppError> {
+    let sessions_state = app.state::<SessionsState>();
+    let workspaces_state = app.state::<WorkspacesState>();
+    let settings_state = app.state::<SettingsState>();
+    let clipboard_data_type = app.state::<ClipboardState>().get_data_type();
+
+    let mut sessions = sessions_state.sessions.write().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let settings = settings_state.settings.read().await;
+
+    let workspace_id = sessions.get_session(session_id)?.workspace_id.clone();
+    let applied = workspaces.apply_external_changes(&workspace_id, merge)?;
+    let private_env_var_set = workspaces.private_env_var_set;
+    let vault_env_var_set = workspaces.vault_env_var_set;
+    let info = workspaces.get_workspace_info(&workspace_id)?;
+
+    let session_ids = sessions
+        .get_workspace_session_ids(&workspace_id)
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>();
+    for session_id in &session_ids {
+        let Ok(session) = sessions.get_session_mut(session_id) else {
+            continue;
+        };
+        if applied.workbook_changed {
+            if session
+                .active_entity
+                .as_ref()
+                .is_some_and(|active| !info.contains_entity(active))
+            {
+                session.update_active_entity(&None);
+            }
+            let init = info.build_initialization(
+                session.clone(),
+                session_ids.len(),
+                private_env_var_set,
+                vault_env_var_set,
+                settings.clone(),
+                clipboard_data_type,
+            );
+            app.emit_to(session_id, "initialize", init).unwrap();
+        }
+        for update in &applied.data_set_updates {
+            app.emit_to(session_id, "update", update).unwrap();
+        }
+    }
+
+    dispatch_save_state(app, &sessions, &workspace_id, info, false);
+    Ok(applied.conflicts)
+}
+
 fn format_window_title(display_name: &str, dirty: bool) -> String {
     let name_part = if display_name.is_empty() {
         "(New)"
@@ -390,15 +819,29 @@ fn generate_settings_defaults(app: AppHandle) -> Result<ApicizeSettings, Apicize
         last_workbook_file_name: None,
         recent_workbook_file_names: None,
         pkce_listener_port: 8080,
+        pkce_redirect_path: String::default(),
+        pkce_ephemeral_port: false,
+        pkce_timeout: 300,
         always_hide_nav_tree: false,
         show_diagnostic_info: false,
         report_format: apicize_lib::ExecutionReportFormat::JSON,
         editor_indent_size: 3,
         editor_check_js_syntax: true,
         editor_detect_existing_indent: true,
+        persisted_token_authorization_ids: BTreeSet::new(),
+        trusted_secret_commands: BTreeMap::new(),
+        remember_passwords_in_keyring: false,
+        recovery_interval: 30,
     })
 }
 
+/// Create a new workspace, with extended settings of any vault parameters
+fn create_new_workspace() -> Result<(Workspace, WorkbookExtensions), ApicizeError> {
+    let mut workspace = Workspace::new()?;
+    let extensions = WorkbookExtensions::open(None, &mut workspace)?;
+    Ok((workspace, extensions))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn create_workspace(
     app: AppHandle,
@@ -430,7 +873,7 @@ fn create_workspace(
             .get_session(session_id)
             .ok()
             .map(|session| session.workspace_id.clone()),
-        None => None,
+        Some(OpenExisting::Recovery(_)) | None => None,
     };
 
     // If this is an open workspace, and we are on the last session, then close the workspace
@@ -471,23 +914,16 @@ fn create_workspace(
                 // If there is not a workspace already open for this file, open the file,
                 // create a workspace and add a session
                 let path = PathBuf::from(&file_name);
-                let defaults = OpenWorkbookOptions {
-                    override_default_scenario: Default::default(),
-                    override_default_authorization: Default::default(),
-                    override_default_certificate: Default::default(),
-                    override_default_proxy: Default::default(),
-                    override_data_seed: Default::default(),
-                    private_password: Default::default(),
-                    vault_password: workspaces.vault_password.clone(),
-                };
-                match Workspace::open(Some(&path), path.parent().unwrap(), defaults) {
-                    Ok(workspace) => {
+                match open_workbook(&path, None, workspaces.vault_password.clone()) {
+                    Ok((workspace, extensions)) => {
                         save_recent_file_name = Some(file_name.clone());
-                        Ok(workspaces.add_workspace(workspace, file_name, false))
+                        Ok(workspaces.add_workspace(workspace, extensions, file_name, false))
                     }
                     Err(err) => {
                         if create_new_if_error {
-                            let mut result = workspaces.add_workspace(Workspace::new()?, "", true);
+                            let (workspace, extensions) = create_new_workspace()?;
+                            let mut result =
+                                workspaces.add_workspace(workspace, extensions, "", true);
                             result.error = Some(format!("{err}"));
                             Ok(result)
                         } else {
@@ -514,7 +950,30 @@ fn create_workspace(
                 description: format!("Session {session_id} does not refer to a valid workspace"),
             })
         }
-        None => Ok(workspaces.add_workspace(Workspace::new()?, "", true)),
+        Some(OpenExisting::Recovery(snapshot_id)) => {
+            // Restore unsaved changes, keeping the snapshot under the new workspace ID
+            let restored =
+                recovery::restore_snapshot(snapshot_id, workspaces.vault_password.clone())?;
+            let result = workspaces.add_workspace(
+                restored.workspace,
+                restored.extensions,
+                &restored.file_name,
+                false,
+            );
+            let info = workspaces.get_workspace_info_mut(&result.workspace_id)?;
+            info.dirty = true;
+            info.data_set_content = restored.data_set_content;
+            // What was last opened or saved is unknown, so nothing can be merged automatically
+            info.external_changes.set_base(None);
+            if let Err(err) = recovery::rename_snapshot(snapshot_id, &result.workspace_id) {
+                eprintln!("Unable to rename recovery snapshot {snapshot_id}: {err}");
+            }
+            Ok(result)
+        }
+        None => {
+            let (workspace, extensions) = create_new_workspace()?;
+            Ok(workspaces.add_workspace(workspace, extensions, "", true))
+        }
     }?;
 
     // Update the recently accessed workbook list in settings
@@ -524,6 +983,20 @@ fn create_workspace(
         app.emit("update_settings", settings.clone()).unwrap();
     }
 
+    if settings.remember_passwords_in_keyring {
+        let vault_password = unlock_with_keyring(workspaces, &workspace_result.workspace_id)?;
+        if let Some(password) = vault_password
+            && !settings.persisted_token_authorization_ids.is_empty()
+        {
+            let authorization_ids = settings.persisted_token_authorization_ids.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = token_cache::restore_tokens(&password, &authorization_ids).await {
+                    eprintln!("Unable to restore OAuth2 tokens: {err}");
+                }
+            });
+        }
+    }
+
     let trace_title: String;
     let new_session_id: String;
     {
@@ -584,48 +1057,16 @@ fn create_workspace(
                 .set_title(format_window_title(&workspace_result.display_name, info.dirty).as_str())
                 .unwrap();
 
-            let init = WorkspaceInitialization {
-                session: session.clone(),
-                navigation: info.navigation.clone(),
-                save_state: SessionSaveState {
-                    file_name: info.file_name.clone(),
-                    directory: info.directory.clone(),
-                    display_name: info.display_name.clone(),
-                    dirty: info.dirty,
-                    editor_count: sessions
-                        .get_workspace_session_ids(&workspace_result.workspace_id)
-                        .len(),
-                },
-                private_lock_status: info.workspace.private_lock_status,
-                vault_lock_status: info.workspace.vault_lock_status,
+            let init = info.build_initialization(
+                session.clone(),
+                sessions
+                    .get_workspace_session_ids(&workspace_result.workspace_id)
+                    .len(),
                 private_env_var_set,
                 vault_env_var_set,
-                defaults: info.workspace.defaults.clone(),
-                settings: settings.clone(),
-                executions: info
-                    .executions
-                    .iter()
-                    .filter_map(|(id, exec)| match exec.execution_state {
-                        ExecutionState::RUNNING => Some((
-                            id.to_string(),
-                            ExecutionEvent::Start {
-                                execution_state: ExecutionState::RUNNING,
-                            },
-                        )),
-                        ExecutionState::ERROR => None,
-                        _ => Some((
-                            id.to_string(),
-                            ExecutionEvent::Complete(RequestExecution {
-                                menu: exec.menu.clone(),
-                                execution_state: exec.execution_state,
-                                active_summaries: exec.active_summaries.clone(),
-                            }),
-                        )),
-                    })
-                    .collect::<FxHashMap<String, ExecutionEvent>>(),
-                error: None,
+                settings.clone(),
                 clipboard_data_type,
-            };
+            );
 
             app.emit_to(active_session_id, "initialize", init).unwrap();
         } else {
@@ -685,48 +1126,16 @@ fn create_workspace(
                 execution_result_view_state: HashMap::default(),
             };
 
-            let init = WorkspaceInitialization {
-                session: session.clone(),
-                navigation: info.navigation.clone(),
-                save_state: SessionSaveState {
-                    file_name: info.file_name.clone(),
-                    directory: info.directory.clone(),
-                    display_name: info.display_name.clone(),
-                    dirty: info.dirty,
-                    editor_count: sessions
-                        .get_workspace_session_ids(&session.workspace_id)
-                        .len(),
-                },
-                private_lock_status: info.workspace.private_lock_status,
-                vault_lock_status: info.workspace.vault_lock_status,
+            let init = info.build_initialization(
+                session.clone(),
+                sessions
+                    .get_workspace_session_ids(&session.workspace_id)
+                    .len(),
                 private_env_var_set,
                 vault_env_var_set,
-                defaults: info.workspace.defaults.clone(),
-                settings: settings.clone(),
-                executions: info
-                    .executions
-                    .iter()
-                    .filter_map(|(id, exec)| match exec.execution_state {
-                        ExecutionState::RUNNING => Some((
-                            id.to_string(),
-                            ExecutionEvent::Start {
-                                execution_state: ExecutionState::RUNNING,
-                            },
-                        )),
-                        ExecutionState::ERROR => None,
-                        _ => Some((
-                            id.to_string(),
-                            ExecutionEvent::Complete(RequestExecution {
-                                menu: exec.menu.clone(),
-                                execution_state: exec.execution_state,
-                                active_summaries: exec.active_summaries.clone(),
-                            }),
-                        )),
-                    })
-                    .collect::<FxHashMap<String, ExecutionEvent>>(),
-                error: None,
+                settings.clone(),
                 clipboard_data_type,
-            };
+            );
 
             let init_data = serde_json::to_string(&init).unwrap();
             new_session_id = sessions.add_session(session);
@@ -939,6 +1348,7 @@ async fn open_workspace(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn save_workspace(
     app: AppHandle,
     sessions_state: State<'_, SessionsState>,
@@ -946,7 +1356,10 @@ async fn save_workspace(
     settings_state: State<'_, SettingsState>,
     session_id: &str,
     file_name: Option<String>,
-) -> Result<(), ApicizeAppError> {
+    allow_secrets: Option<bool>,
+    overwrite: Option<bool>,
+    format: Option<WorkbookFormat>,
+) -> Result<SaveWorkspaceResponse, ApicizeAppError> {
     IN_FLIGHT_SAVES.fetch_add(1, Ordering::SeqCst);
     let result = save_workspace_inner(
         app,
@@ -955,12 +1368,16 @@ async fn save_workspace(
         settings_state,
         session_id,
         file_name,
+        allow_secrets.unwrap_or(false),
+        overwrite.unwrap_or(false),
+        format,
     )
     .await;
     IN_FLIGHT_SAVES.fetch_sub(1, Ordering::SeqCst);
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn save_workspace_inner(
     app: AppHandle,
     sessions_state: State<'_, SessionsState>,
@@ -968,7 +1385,10 @@ async fn save_workspace_inner(
     settings_state: State<'_, SettingsState>,
     session_id: &str,
     file_name: Option<String>,
-) -> Result<(), ApicizeAppError> {
+    allow_secrets: bool,
+    overwrite: bool,
+    format: Option<WorkbookFormat>,
+) -> Result<SaveWorkspaceResponse, ApicizeAppError> {
     let sessions = sessions_state.sessions.write().await;
     let session = sessions.get_session(session_id)?;
 
@@ -1000,6 +1420,35 @@ async fn save_workspace_inner(
     let include_private = include_workbook && !info.workspace.private_lock_status.is_locked();
     let include_vault = !info.workspace.vault_lock_status.is_locked();
 
+    // Do not overwrite changes made outside of Apicize (i.e. by pulling from source control)
+    // unless the user has chosen to overwrite them
+    let same_file = save_as_file_name.as_ref() == Some(&info.file_name);
+    if include_workbook && same_file && !overwrite {
+        let changed_files = info.external_changes.get_changed_files();
+        if changed_files.contains(&WatchedFile::Workbook)
+            || changed_files.contains(&WatchedFile::WorkbookDirectory)
+            || changed_files.contains(&WatchedFile::Extensions)
+            || (include_private && changed_files.contains(&WatchedFile::Private))
+        {
+            return Ok(SaveWorkspaceResponse {
+                changed_on_disk: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Return likely secrets instead of saving them to the public workbook, unless the
+    // user has chosen to save anyway
+    if include_workbook && !allow_secrets {
+        let secret_findings = scan_workspace(&info.workspace, &info.extensions);
+        if !secret_findings.is_empty() {
+            return Ok(SaveWorkspaceResponse {
+                secret_findings,
+                ..Default::default()
+            });
+        }
+    }
+
     let save_params = SaveWorkspaceParameters {
         workbook_path,
         include_workbook,
@@ -1007,11 +1456,27 @@ async fn save_workspace_inner(
         include_vault,
     };
 
-    info.workspace.save(&save_params)?;
+    // Unless specified, keep the format of the workbook being overwritten
+    let format = format.unwrap_or_else(|| {
+        save_params
+            .workbook_path
+            .as_ref()
+            .filter(|path| path.is_file())
+            .and_then(|path| WorkbookFormat::detect(path).ok())
+            .unwrap_or_default()
+    });
+    save_workbook(&info.workspace, &save_params, format)?;
     if save_params.include_workbook
         && let Some(save_as) = &save_params.workbook_path
         && let Some(save_as_file_name) = &save_as_file_name
     {
+        // Drop selections for requests and groups that no longer exist
+        let requests = &info.workspace.requests.entities;
+        info.extensions
+            .selected_trusted_roots
+            .retain(|id, _| id == Navigation::DEFAULTS_ID || requests.contains_key(id));
+        info.extensions.save(save_as, &info.workspace)?;
+
         let mut settings = settings_state.settings.write().await;
         if settings.update_recent_workbook_file_name(save_as_file_name) {
             settings.save()?;
@@ -1025,10 +1490,14 @@ async fn save_workspace_inner(
             ))?
             .to_path_buf();
 
+        let mut saved_data_set_ids = Vec::new();
         for data_set in info.workspace.data.entities.values() {
             let Some(content) = info.data_set_content.get_mut(&data_set.id) else {
                 continue;
             };
+            if content.dirty {
+                saved_data_set_ids.push(data_set.id.clone());
+            }
             perform_save_data_set_file(data_set, content, &data_path, false)?;
         }
 
@@ -1042,9 +1511,29 @@ async fn save_workspace_inner(
             .to_string();
         info.directory = data_path.to_string_lossy().to_string();
 
+        // What was saved is now the version to detect external changes against
+        if same_file {
+            info.external_changes.acknowledge(&WatchedFile::Workbook);
+            info.external_changes
+                .acknowledge(&WatchedFile::WorkbookDirectory);
+            info.external_changes.acknowledge(&WatchedFile::Extensions);
+            if include_private {
+                info.external_changes.acknowledge(&WatchedFile::Private);
+            }
+            for data_set_id in saved_data_set_ids {
+                info.external_changes
+                    .acknowledge(&WatchedFile::DataSet(data_set_id));
+            }
+        } else {
+            info.external_changes
+                .track(&info.file_name, &info.workspace);
+        }
+        info.external_changes
+            .set_base(Some((info.workspace.clone(), info.extensions.clone())));
+
         dispatch_save_state(&app, &sessions, &session.workspace_id, info, false);
     }
-    Ok(())
+    Ok(SaveWorkspaceResponse::default())
 }
 
 fn perform_save_data_set_file(
@@ -1162,6 +1651,8 @@ fn dispatch_save_state(
             display_name: info.display_name.clone(),
             dirty: info.dirty,
             editor_count: sessions.get_workspace_session_ids(workspace_id).len(),
+            can_undo: info.undo_history.can_undo(),
+            can_redo: info.undo_history.can_redo(),
         };
 
         for session_id in session_ids {
@@ -1212,7 +1703,8 @@ async fn close_workspace(
         if editor_count == 0 {
             let workbook_auth_ids = workspaces.list_workbook_authorization_ids(&workspace_id)?;
             for auth_id in workbook_auth_ids {
-                clear_cached_authorization(auth_id).await;
+                // Persisted tokens are retained for when the workbook is reopened
+                clear_oauth2_token_from_cache(&auth_id).await;
             }
             workspaces.remove_workspace(&workspace_id);
         }
@@ -1229,6 +1721,7 @@ async fn set_parameters_password(
     app: AppHandle,
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
     session_id: &str,
     parameter_store: ParameterStore,
     lock_type: PasswordLockType,
@@ -1238,18 +1731,116 @@ async fn set_parameters_password(
     let workspace_id = session.workspace_id.as_str();
 
     let mut workspaces = workspaces_state.workspaces.write().await;
-    let workbook_path = {
-        let info = workspaces.get_workspace_info_mut(workspace_id)?;
-        if info.file_name.is_empty() {
-            None
-        } else {
-            Some(PathBuf::from(&info.file_name))
-        }
-    };
+    let workbook_path = get_workbook_path(&workspaces, workspace_id)?;
+    let use_keyring = settings_state
+        .settings
+        .read()
+        .await
+        .remember_passwords_in_keyring;
+
+    let old_vault_password = workspaces.get_vault_password();
+    workspaces.set_parameters_password(
+        workspace_id,
+        workbook_path.clone(),
+        parameter_store,
+        lock_type,
+    )?;
+    dispatch_password_change(
+        &app,
+        &sessions,
+        &workspaces,
+        workspace_id,
+        parameter_store,
+        old_vault_password,
+        use_keyring.then_some(workbook_path),
+    )
+}
+
+/// Change the password of a parameter store, verifying the current password first
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn change_parameters_password(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
+    session_id: &str,
+    parameter_store: ParameterStore,
+    current_password: &str,
+    lock_type: PasswordLockType,
+) -> Result<(), ApicizeAppError> {
+    let sessions = sessions_state.sessions.write().await;
+    let session = sessions.get_session(session_id)?;
+    let workspace_id = session.workspace_id.as_str();
 
-    workspaces.set_parameters_password(workspace_id, workbook_path, parameter_store, lock_type)?;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let workbook_path = get_workbook_path(&workspaces, workspace_id)?;
+    let use_keyring = settings_state
+        .settings
+        .read()
+        .await
+        .remember_passwords_in_keyring;
+
+    let old_vault_password = workspaces.get_vault_password();
+    workspaces.change_parameters_password(
+        workspace_id,
+        workbook_path.clone(),
+        parameter_store,
+        current_password,
+        lock_type,
+    )?;
+    dispatch_password_change(
+        &app,
+        &sessions,
+        &workspaces,
+        workspace_id,
+        parameter_store,
+        old_vault_password,
+        use_keyring.then_some(workbook_path),
+    )
+}
+
+/// Re-encrypt persisted tokens, update the keyring (if remembering passwords for the
+/// workbook path) and broadcast the lock status after a parameter store's password is set
+fn dispatch_password_change(
+    app: &AppHandle,
+    sessions: &Sessions,
+    workspaces: &Workspaces,
+    workspace_id: &str,
+    parameter_store: ParameterStore,
+    old_vault_password: Option<String>,
+    keyring_workbook_path: Option<Option<PathBuf>>,
+) -> Result<(), ApicizeAppError> {
+    // Persisted tokens are encrypted with the vault password, so keep them in sync
+    if parameter_store == ParameterStore::Vault
+        && let Some(old_vault_password) = old_vault_password
+        && let Err(err) = token_cache::reencrypt_persisted_tokens(
+            &old_vault_password,
+            workspaces.get_vault_password().as_deref(),
+            workspaces.get_vault_encryption(),
+        )
+    {
+        eprintln!("Unable to re-encrypt OAuth2 tokens: {err}");
+    }
 
     let workspace = workspaces.get_workspace(workspace_id)?;
+
+    // Only typed passwords are remembered, environment variables are read when needed
+    if let Some(workbook_path) = keyring_workbook_path {
+        let (password, status) = match parameter_store {
+            ParameterStore::Vault => (&workspace.vault_password, workspace.vault_lock_status),
+            ParameterStore::Private => (&workspace.private_password, workspace.private_lock_status),
+        };
+        let password = password
+            .as_deref()
+            .filter(|_| status == ParameterLockStatus::UnlockedWithPassword);
+        if let Err(err) =
+            password_keyring::set_password(parameter_store, workbook_path.as_deref(), password)
+        {
+            eprintln!("{err}");
+        }
+    }
+
     if parameter_store == ParameterStore::Vault {
         app.emit(
             "lock_status",
@@ -1276,35 +1867,167 @@ async fn set_parameters_password(
     Ok(())
 }
 
+/// Forget the parameter store password remembered in the keyring for the session's workbook
+#[tauri::command]
+async fn forget_parameters_password(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    parameter_store: ParameterStore,
+) -> Result<bool, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let workbook_path = get_workbook_path(&workspaces, &session.workspace_id)?;
+    password_keyring::delete_password(parameter_store, workbook_path.as_deref())
+        .map_err(ApicizeAppError::InvalidOperation)
+}
+
+/// Unlock the workspace's locked parameter stores with passwords remembered in the keyring,
+/// forgetting those that no longer decrypt their store.  Returns the vault password if the
+/// vault was unlocked
+fn unlock_with_keyring(
+    workspaces: &mut Workspaces,
+    workspace_id: &str,
+) -> Result<Option<String>, ApicizeAppError> {
+    let workbook_path = get_workbook_path(workspaces, workspace_id)?;
+    let mut vault_password = None;
+    for parameter_store in [ParameterStore::Vault, ParameterStore::Private] {
+        let workspace = workspaces.get_workspace(workspace_id)?;
+        let locked = match parameter_store {
+            ParameterStore::Vault => workspace.vault_lock_status.is_locked(),
+            ParameterStore::Private => workspace.private_lock_status.is_locked(),
+        };
+        if !locked {
+            continue;
+        }
+        let password =
+            match password_keyring::get_password(parameter_store, workbook_path.as_deref()) {
+                Ok(Some(password)) => password,
+                Ok(None) => continue,
+                Err(err) => {
+                    eprintln!("{err}");
+                    continue;
+                }
+            };
+        if workspaces
+            .decrypt_parameters(workspace_id, parameter_store, &password)
+            .is_ok()
+        {
+            let info = workspaces.get_workspace_info_mut(workspace_id)?;
+            info.extensions.unlock_proxy_settings(&mut info.workspace);
+            info.extensions
+                .unlock_authorization_settings(&mut info.workspace);
+            if parameter_store == ParameterStore::Vault {
+                vault_password = Some(password);
+            }
+        } else if let Err(err) =
+            password_keyring::delete_password(parameter_store, workbook_path.as_deref())
+        {
+            eprintln!("{err}");
+        }
+    }
+    Ok(vault_password)
+}
+
+/// Return the workspace's workbook path, if it has been saved
+fn get_workbook_path(
+    workspaces: &Workspaces,
+    workspace_id: &str,
+) -> Result<Option<PathBuf>, ApicizeAppError> {
+    let info = workspaces.get_workspace_info(workspace_id)?;
+    Ok(if info.file_name.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(&info.file_name))
+    })
+}
+
 #[tauri::command]
 async fn decrypt_parameters(
     app: AppHandle,
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
     session_id: &str,
     parameter_store: ParameterStore,
-    password: &str,
+    password: Option<String>,
 ) -> Result<(), ApicizeAppError> {
     let sessions = sessions_state.sessions.write().await;
     let session = sessions.get_session(session_id)?;
     let workspace_id = session.workspace_id.as_str();
 
     let mut workspaces = workspaces_state.workspaces.write().await;
-    let parameters = workspaces.decrypt_parameters(workspace_id, parameter_store, password)?;
-    let info = workspaces.get_workspace_info_mut(workspace_id)?;
-
-    let workspace_session_ids = sessions.get_workspace_session_ids(workspace_id);
+    let workbook_path = get_workbook_path(&workspaces, workspace_id)?;
 
-    let number_of_updates = parameters.get_parameter_count();
-    let mut entity_updates = Vec::<EntityUpdate>::with_capacity(number_of_updates);
-    let mut navigation_updates = Vec::<UpdatedNavigationEntry>::with_capacity(number_of_updates);
+    // If no password is specified, use the one remembered in the keyring (if enabled)
+    let use_keyring = settings_state
+        .settings
+        .read()
+        .await
+        .remember_passwords_in_keyring;
+    let (password, from_keyring) = match password.filter(|p| !p.is_empty()) {
+        Some(password) => (password, false),
+        None => {
+            let remembered = if use_keyring {
+                password_keyring::get_password(parameter_store, workbook_path.as_deref())
+                    .map_err(ApicizeAppError::InvalidOperation)?
+            } else {
+                None
+            };
+            match remembered {
+                Some(password) => (password, true),
+                None => {
+                    return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                        description: "Password is required".to_string(),
+                    }));
+                }
+            }
+        }
+    };
+
+    let parameters = match workspaces.decrypt_parameters(workspace_id, parameter_store, &password) {
+        Ok(parameters) => parameters,
+        Err(err) => {
+            // Forget a remembered password that no longer decrypts the store
+            if from_keyring
+                && let Err(keyring_err) =
+                    password_keyring::delete_password(parameter_store, workbook_path.as_deref())
+            {
+                eprintln!("{keyring_err}");
+            }
+            return Err(err);
+        }
+    };
+    // Only remember passwords once they have decrypted the store
+    if use_keyring
+        && !from_keyring
+        && let Err(err) = password_keyring::set_password(
+            parameter_store,
+            workbook_path.as_deref(),
+            Some(&password),
+        )
+    {
+        eprintln!("{err}");
+    }
+    let info = workspaces.get_workspace_info_mut(workspace_id)?;
+    info.extensions.unlock_proxy_settings(&mut info.workspace);
+    info.extensions
+        .unlock_authorization_settings(&mut info.workspace);
+
+    let workspace_session_ids = sessions.get_workspace_session_ids(workspace_id);
+
+    let number_of_updates = parameters.get_parameter_count();
+    let mut entity_updates = Vec::<EntityUpdate>::with_capacity(number_of_updates);
+    let mut navigation_updates = Vec::<UpdatedNavigationEntry>::with_capacity(number_of_updates);
 
     if let Some(scenarios) = parameters.scenarios {
         for scenario in scenarios {
             if let Scenario::Plain(scenario) = scenario {
-                entity_updates.push(EntityUpdate::Scenario(ScenarioUpdate::from(
-                    (*scenario).clone(),
-                )));
+                entity_updates.push(EntityUpdate::Scenario(
+                    ScenarioUpdate::from((*scenario).clone())
+                        .with_secret_variables(info.extensions.secret_variables.get(&scenario.id)),
+                ));
                 navigation_updates.push(UpdatedNavigationEntry {
                     id: scenario.id,
                     name: scenario.name,
@@ -1325,9 +2048,15 @@ async fn decrypt_parameters(
             let name = authorization.get_name().to_string();
             let validation_state = authorization.get_validation_state();
             if let Authorization::Plain(authorization) = authorization {
-                entity_updates.push(EntityUpdate::Authorization(AuthorizationUpdate::from(
-                    (*authorization).clone(),
-                )));
+                let update = AuthorizationUpdate::from((*authorization).clone());
+                entity_updates.push(EntityUpdate::Authorization(
+                    match info.extensions.authorization_settings.get(&id) {
+                        Some(AuthorizationSettings::Plain(settings)) => {
+                            update.with_settings(settings)
+                        }
+                        _ => update,
+                    },
+                ));
                 navigation_updates.push(UpdatedNavigationEntry {
                     id,
                     name,
@@ -1417,6 +2146,21 @@ async fn decrypt_parameters(
         }
     }
 
+    // Persisted tokens can be restored once the vault password is known
+    if parameter_store == ParameterStore::Vault {
+        let authorization_ids = settings_state
+            .settings
+            .read()
+            .await
+            .persisted_token_authorization_ids
+            .clone();
+        if !authorization_ids.is_empty()
+            && let Err(err) = token_cache::restore_tokens(&password, &authorization_ids).await
+        {
+            eprintln!("Unable to restore OAuth2 tokens: {err}");
+        }
+    }
+
     Ok(())
 }
 
@@ -1466,6 +2210,82 @@ async fn get_workspace_save_status(
     })
 }
 
+#[tauri::command]
+async fn scan_workspace_secrets(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+) -> Result<Vec<SecretFinding>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let session = sessions.get_session(session_id)?;
+    let info = workspaces.get_workspace_info(&session.workspace_id)?;
+    Ok(scan_workspace(&info.workspace, &info.extensions))
+}
+
+#[tauri::command]
+async fn search(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    options: SearchOptions,
+) -> Result<SearchResults, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let session = sessions.get_session(session_id)?;
+    let info = workspaces.get_workspace_info(&session.workspace_id)?;
+    search_workspace(&info.workspace, &options)
+}
+
+#[tauri::command]
+async fn preview_replace(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    options: ReplaceOptions,
+) -> Result<ReplaceResults, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let session = sessions.get_session(session_id)?;
+    let info = workspaces.get_workspace_info(&session.workspace_id)?;
+    let planned = plan_replacement(&info.workspace, &options)?;
+    Ok(ReplaceResults {
+        fields: planned.fields,
+        skipped: planned.skipped,
+    })
+}
+
+/// Replace matches of a search across the session's workspace and notify each of the
+/// workspace's sessions, returning the fields that were changed and those that were skipped
+#[tauri::command]
+async fn replace(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    options: ReplaceOptions,
+) -> Result<ReplaceResults, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let workspace_id = sessions.get_session(session_id)?.workspace_id.clone();
+
+    let result = workspaces.replace(&workspace_id, &options);
+
+    let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
+    if let Ok((_, applied)) = &result {
+        for session_id in sessions.get_workspace_session_ids(&workspace_id) {
+            for update in &applied.updates {
+                app.emit_to(session_id, "update", update).unwrap();
+            }
+        }
+    }
+
+    // Changes applied before any failure are still sent
+    dispatch_save_state(&app, &sessions, &workspace_id, info, true);
+    result.map(|(results, _)| results)
+}
+
 #[tauri::command]
 async fn open_settings() -> Result<ApicizeSettings, String> {
     match ApicizeSettings::open() {
@@ -1480,18 +2300,131 @@ async fn open_settings() -> Result<ApicizeSettings, String> {
 #[tauri::command]
 async fn save_settings(
     app: AppHandle,
-    updated_settings: ApicizeSettings,
+    mut updated_settings: ApicizeSettings,
     settings_state: State<'_, SettingsState>,
 ) -> Result<(), String> {
-    let mut settings = settings_state.settings.write().await;
-    settings.clone_from(&updated_settings);
-    match settings.save() {
-        Ok(..) => {
-            app.emit("update_settings", updated_settings).unwrap();
-            Ok(())
+    pkce::validate_pkce_timeout(updated_settings.pkce_timeout)?;
+    {
+        let mut settings = settings_state.settings.write().await;
+        // Trusted commands are only updated when the user confirms running them, and token
+        // persistence through set_oauth2_token_persistence
+        updated_settings
+            .trusted_secret_commands
+            .clone_from(&settings.trusted_secret_commands);
+        updated_settings
+            .persisted_token_authorization_ids
+            .clone_from(&settings.persisted_token_authorization_ids);
+        settings.clone_from(&updated_settings);
+        settings.save().map_err(|err| err.to_string())?;
+    }
+
+    app.emit("update_settings", updated_settings).unwrap();
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_oauth2_token_persistence(
+    app: AppHandle,
+    settings_state: State<'_, SettingsState>,
+    authorization_id: String,
+    persist: bool,
+) -> Result<(), String> {
+    let updated_settings = {
+        let mut settings = settings_state.settings.write().await;
+        let changed = if persist {
+            settings
+                .persisted_token_authorization_ids
+                .insert(authorization_id)
+        } else {
+            settings
+                .persisted_token_authorization_ids
+                .remove(&authorization_id)
+        };
+        if !changed {
+            return Ok(());
+        }
+        settings.save().map_err(|err| err.to_string())?;
+        settings.clone()
+    };
+
+    persist_cached_tokens(&app).await;
+    app.emit("update_settings", updated_settings).unwrap();
+    Ok(())
+}
+
+/// Save cached OAuth2 tokens of authorizations that opted into persistence, if the vault
+/// password is available, removing those that no longer opt in; must not be called while
+/// holding settings or workspaces locks
+async fn persist_cached_tokens(app: &AppHandle) {
+    let authorization_ids = app
+        .state::<SettingsState>()
+        .settings
+        .read()
+        .await
+        .persisted_token_authorization_ids
+        .clone();
+    let workspaces_state = app.state::<WorkspacesState>();
+    let (password, encryption) = {
+        let workspaces = workspaces_state.workspaces.read().await;
+        (
+            workspaces.get_vault_password(),
+            workspaces.get_vault_encryption(),
+        )
+    };
+    let result = match password {
+        Some(password) => {
+            token_cache::persist_tokens(&password, encryption, &authorization_ids).await
+        }
+        None => token_cache::retain_persisted_tokens(&authorization_ids),
+    };
+    if let Err(err) = result {
+        eprintln!("Unable to save OAuth2 tokens: {err}");
+    }
+}
+
+#[tauri::command]
+async fn get_untrusted_secret_commands(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
+    session_id: &str,
+) -> Result<Vec<String>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let settings = settings_state.settings.read().await;
+    let info = workspaces.get_workspace_info(&sessions.get_session(session_id)?.workspace_id)?;
+    Ok(info
+        .get_untrusted_secret_commands(&settings.trusted_secret_commands)
+        .into_iter()
+        .collect())
+}
+
+#[tauri::command]
+async fn trust_secret_commands(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
+    session_id: &str,
+) -> Result<(), ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let info =
+        workspaces.get_workspace_info_mut(&sessions.get_session(session_id)?.workspace_id)?;
+    let fingerprint = info.trust_secret_commands();
+
+    // Trust for saved workbooks is remembered until their commands change
+    if !info.file_name.is_empty() {
+        let mut settings = settings_state.settings.write().await;
+        if settings.trusted_secret_commands.get(&info.file_name) != Some(&fingerprint) {
+            settings
+                .trusted_secret_commands
+                .insert(info.file_name.clone(), fingerprint);
+            settings.save()?;
+            app.emit("update_settings", settings.clone()).unwrap();
         }
-        Err(err) => Err(err.to_string()),
     }
+    Ok(())
 }
 
 fn cancellation_tokens() -> &'static StdRwLock<FxHashMap<String, CancellationToken>> {
@@ -1500,10 +2433,12 @@ fn cancellation_tokens() -> &'static StdRwLock<FxHashMap<String, CancellationTok
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn start_execution(
     app: AppHandle,
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
     session_id: &str,
     request_or_group_id: &str,
     workbook_full_name: String,
@@ -1538,14 +2473,50 @@ async fn start_execution(
         session.workspace_id.clone()
     };
 
+    // Resolve secret variables before the run starts, so that a missing secret fails the
+    // run without dispatching requests.  Values are only set in the cloned workspace
+    let secret_values = {
+        let secrets = {
+            let workspaces = workspaces_state.workspaces.read().await;
+            let info = workspaces.get_workspace_info(&workspace_id)?;
+            let secrets = info.get_execution_secret_variables(request_or_group_id);
+            if !secrets.is_empty() {
+                let settings = settings_state.settings.read().await;
+                let untrusted =
+                    info.get_untrusted_secret_commands(&settings.trusted_secret_commands);
+                if !untrusted.is_empty() {
+                    return Err(ApicizeAppError::UntrustedSecretCommands(
+                        untrusted.into_iter().collect::<Vec<_>>().join(", "),
+                    ));
+                }
+            }
+            secrets
+        };
+        if secrets.is_empty() {
+            Default::default()
+        } else {
+            let directory = allowed_data_path.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                resolve_secret_variables(&secrets, directory.as_deref())
+            })
+            .await
+            .map_err(|err| ApicizeAppError::InvalidOperation(err.to_string()))?
+            .map_err(|description| {
+                ApicizeAppError::ApicizeError(ApicizeError::Error { description })
+            })?
+        }
+    };
+
     // Phase 2: Quick read to get workspace data, with # of run overrides if specified, then release lock immediately
     // Acquire write lock for minimal time - just to update execution state and save data sets
-    let cloned_workspace = {
+    let (cloned_workspace, token_renewals) = {
         let mut workspaces = workspaces_state.workspaces.write().await;
         let info = workspaces.get_workspace_info_mut(&workspace_id)?;
         let active_data_set_ids = info.get_request_data_set_ids(request_or_group_id)?;
+        let token_renewals = info.get_token_renewals(request_or_group_id);
 
         let mut cloned_workspace = info.workspace.clone();
+        apply_secret_values(&mut cloned_workspace, secret_values);
 
         // Save any active data sets to a temp directory
         if !active_data_set_ids.is_empty() {
@@ -1628,9 +2599,44 @@ async fn start_execution(
             }
         }
 
-        cloned_workspace
+        (cloned_workspace, token_renewals)
     }; // Write lock released here
 
+    // Phase 3: Renew OAuth2 tokens that are missing or about to expire, so that current
+    // tokens are sent when dispatching requests
+    for renewal in token_renewals {
+        let authorization_id = renewal.authorization_id.clone();
+        let name = renewal.name.clone();
+        match renewal.renew().await {
+            Some(Ok(token_info)) => {
+                app.emit(
+                    "oauth2-token-refreshed",
+                    TokenRenewalEvent {
+                        authorization_id,
+                        name,
+                        expiration: token_info.expiration,
+                        error: None,
+                    },
+                )
+                .unwrap();
+            }
+            Some(Err(err)) => {
+                // Requests will be dispatched with the existing token (if any), so report but continue
+                app.emit(
+                    "oauth2-token-error",
+                    TokenRenewalEvent {
+                        authorization_id,
+                        name,
+                        expiration: None,
+                        error: Some(err),
+                    },
+                )
+                .unwrap();
+            }
+            None => {}
+        }
+    }
+
     // We are going to keep track of counters for this run as well as for the workspace as a whole,
     // because if the user cancels, we don't ever get the "end" event and we'll have a "ghost"
     // running request/group
@@ -1750,6 +2756,9 @@ async fn start_execution(
     // Phase 5: Execute request (no locks held)
     let responses = context.run(vec![request_or_group_id.to_string()]).await;
 
+    // Tokens may have been renewed before, or retrieved during, the run
+    persist_cached_tokens(&app).await;
+
     // Clean up cancellation token and temp directory
     cancellation_tokens()
         .write()
@@ -2037,114 +3046,506 @@ async fn update_execution_result_view_state(
 }
 
 #[tauri::command]
-async fn store_token(authorization_id: String, token_info: CachedTokenInfo) {
-    store_oauth2_token_in_cache(&authorization_id, token_info).await
+async fn store_token(app: AppHandle, authorization_id: String, token_info: CachedTokenInfo) {
+    store_oauth2_token_in_cache(&authorization_id, token_info).await;
+    persist_cached_tokens(&app).await;
 }
 
 #[tauri::command]
 async fn clear_all_cached_authorizations() -> usize {
+    if let Err(err) = token_cache::delete_persisted_tokens() {
+        eprintln!("Unable to delete OAuth2 tokens: {err}");
+    }
     clear_all_oauth2_tokens_from_cache().await
 }
 
 #[tauri::command]
 async fn clear_cached_authorization(authorization_id: String) -> bool {
+    if let Err(err) = token_cache::remove_persisted_token(&authorization_id) {
+        eprintln!("Unable to delete OAuth2 token: {err}");
+    }
     clear_oauth2_token_from_cache(authorization_id.as_str()).await
 }
 
 #[tauri::command]
-async fn retrieve_oauth2_client_token(
+async fn list_cached_tokens(
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
     session_id: &str,
-    authorization_id: &str,
-) -> Result<TokenResult, ApicizeAppError> {
-    let sessions = sessions_state.sessions.read().await;
-    let session = sessions.get_session(session_id)?;
-    let workspaces = workspaces_state.workspaces.read().await;
-    let workspace = workspaces.get_workspace(&session.workspace_id)?;
+) -> Result<Vec<CachedTokenSummary>, ApicizeAppError> {
+    let authorizations = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        workspaces
+            .get_workspace(&session.workspace_id)?
+            .authorizations
+            .entities
+            .iter()
+            .map(|(id, auth)| (id.clone(), auth.get_name().to_string()))
+            .collect::<HashMap<String, String>>()
+    };
+    Ok(token_cache::list_cached_tokens(&authorizations).await)
+}
 
-    let auth = workspaces.get_authorization(&session.workspace_id, authorization_id)?;
+/// Decode a JWT from a token, cached OAuth2 token or execution result, validate its
+/// time claims and optionally verify its signature
+#[tauri::command]
+async fn inspect_jwt(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    source: JwtSource,
+    verification: Option<JwtVerificationKey>,
+) -> Result<JwtInspection, ApicizeAppError> {
+    let (token, certificate, proxy) = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        let find_in_response = |exec_ctr: usize| -> Result<_, ApicizeAppError> {
+            match workspaces.get_execution_result(&session.workspace_id, exec_ctr)? {
+                ExecutionResultDetail::Request(detail) => {
+                    detail.test_context.response.ok_or_else(|| {
+                        ApicizeAppError::InvalidOperation(
+                            "Execution result does not include a response".to_string(),
+                        )
+                    })
+                }
+                ExecutionResultDetail::Grouped(_) => {
+                    Err(ApicizeAppError::InvalidExecution(exec_ctr))
+                }
+            }
+        };
+        let token = match &source {
+            JwtSource::Token { token } => Some(token.clone()),
+            JwtSource::CachedToken { .. } => None,
+            JwtSource::ResponseHeader { exec_ctr, header } => Some(
+                jwt_inspection::find_in_response_header(&find_in_response(*exec_ctr)?, header)
+                    .map_err(ApicizeAppError::InvalidOperation)?,
+            ),
+            JwtSource::ResponseBody { exec_ctr, path } => Some(
+                jwt_inspection::find_in_response_body(&find_in_response(*exec_ctr)?, path)
+                    .map_err(ApicizeAppError::InvalidOperation)?,
+            ),
+        };
+        let workspace = workspaces.get_workspace(&session.workspace_id)?;
+        let (certificate, proxy) = match &verification {
+            Some(JwtVerificationKey::Certificate { certificate_id }) => (
+                Some(
+                    workspace
+                        .certificates
+                        .get_optional(certificate_id)
+                        .ok_or_else(|| ApicizeAppError::InvalidCertificate(certificate_id.clone()))?
+                        .clone(),
+                ),
+                None,
+            ),
+            Some(JwtVerificationKey::Jwks { proxy_id, .. }) => (
+                None,
+                workspace
+                    .proxies
+                    .get_optional(
+                        proxy_id
+                            .as_deref()
+                            .unwrap_or(&workspace.defaults.selected_proxy.id),
+                    )
+                    .cloned(),
+            ),
+            None => (None, None),
+        };
+        (token, certificate, proxy)
+    };
 
-    match auth {
-        Authorization::Cipher(_auth) => {
-            Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
-                description: "Unable to retrieve tokens from encrypted authorization configuration"
-                    .to_string(),
-            }))
+    let token = match (token, &source) {
+        (Some(token), _) => token,
+        (None, JwtSource::CachedToken { authorization_id }) => {
+            retrieve_oauth2_token_from_cache(authorization_id)
+                .await
+                .ok_or_else(|| {
+                    ApicizeAppError::InvalidOperation(
+                        "Authorization does not have a cached token".to_string(),
+                    )
+                })?
+                .access_token
         }
-        Authorization::Plain(auth) => {
-            if let AuthorizationPlain::OAuth2Client {
-                id,
-                access_token_url,
-                client_id,
-                client_secret,
-                send_credentials_in_body,
-                scope,
-                audience,
-                selected_certificate,
-                selected_proxy,
-                ..
-            } = auth.as_ref()
-            {
-                let certificate = workspace
-                    .certificates
-                    .get_optional(&selected_certificate.id);
-                let proxy = workspace.proxies.get_optional(&selected_proxy.id);
+        (None, _) => {
+            return Err(ApicizeAppError::InvalidOperation(
+                "Unable to locate token".to_string(),
+            ));
+        }
+    };
 
-                clear_oauth2_token_from_cache(id).await;
-                Ok(get_oauth2_client_credentials(
-                    id,
-                    OAuth2ClientCredentialParameters {
-                        token_url: access_token_url,
-                        client_id,
-                        client_secret,
-                        send_credentials_in_body: send_credentials_in_body.unwrap_or_default(),
-                        scope,
-                        audience,
-                        certificate,
-                        proxy,
-                        enable_trace: true,
-                    },
-                )
-                .await?)
-            } else {
-                Err(ApicizeAppError::InvalidAuthorization(
-                    "Not an OAuth2 client authorization".to_string(),
-                ))
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut inspection =
+        JwtInspection::new(&token, now).map_err(ApicizeAppError::InvalidOperation)?;
+    match verification {
+        Some(JwtVerificationKey::Jwks { url, .. }) => {
+            let client =
+                build_client(None, proxy.as_ref()).map_err(ApicizeAppError::InvalidOperation)?;
+            inspection.verify_with_jwks(&client, &token, &url).await;
+        }
+        Some(JwtVerificationKey::Certificate { .. }) => {
+            if let Some(certificate) = certificate {
+                match get_certificate_pem(&certificate) {
+                    Ok(pem) => inspection.verify_with_pem(&token, &pem),
+                    Err(err) => return Err(ApicizeAppError::InvalidCertificate(err)),
+                }
             }
         }
+        None => {}
     }
+    Ok(inspection)
 }
 
 #[tauri::command]
-async fn get_request_body(
+async fn retrieve_oauth2_client_token(
+    app: AppHandle,
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
     session_id: &str,
-    request_id: &str,
-) -> Result<RequestBodyInfo, ApicizeAppError> {
-    let mut workspaces = workspaces_state.workspaces.write().await;
-    let sessions = sessions_state.sessions.read().await;
-    let session = sessions.get_session(session_id)?;
-    workspaces.get_request_body(&session.workspace_id, request_id)
+    authorization_id: &str,
+) -> Result<TokenResult, ApicizeAppError> {
+    let result = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        let workspace = workspaces.get_workspace(&session.workspace_id)?;
+
+        let auth = workspaces.get_authorization(&session.workspace_id, authorization_id)?;
+
+        match auth {
+            Authorization::Cipher(_auth) => {
+                Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                    description:
+                        "Unable to retrieve tokens from encrypted authorization configuration"
+                            .to_string(),
+                }))
+            }
+            Authorization::Plain(auth) => {
+                if let AuthorizationPlain::OAuth2Client {
+                    id,
+                    access_token_url,
+                    client_id,
+                    client_secret,
+                    send_credentials_in_body,
+                    scope,
+                    audience,
+                    selected_certificate,
+                    selected_proxy,
+                    ..
+                } = auth.as_ref()
+                {
+                    let certificate = workspace
+                        .certificates
+                        .get_optional(&selected_certificate.id);
+                    let proxy = workspace.proxies.get_optional(&selected_proxy.id);
+
+                    clear_oauth2_token_from_cache(id).await;
+                    Ok(get_oauth2_client_credentials(
+                        id,
+                        OAuth2ClientCredentialParameters {
+                            token_url: access_token_url,
+                            client_id,
+                            client_secret,
+                            send_credentials_in_body: send_credentials_in_body.unwrap_or_default(),
+                            scope,
+                            audience,
+                            certificate,
+                            proxy,
+                            enable_trace: true,
+                        },
+                    )
+                    .await?)
+                } else {
+                    Err(ApicizeAppError::InvalidAuthorization(
+                        "Not an OAuth2 client authorization".to_string(),
+                    ))
+                }
+            }
+        }
+    };
+
+    if result.is_ok() {
+        persist_cached_tokens(&app).await;
+    }
+    result
 }
 
 #[tauri::command]
-async fn update_request_body(
+async fn start_oauth2_device_flow(
     app: AppHandle,
+    auth_state: State<'_, AuthState>,
     sessions_state: State<'_, SessionsState>,
     workspaces_state: State<'_, WorkspacesState>,
     session_id: &str,
-    request_id: &str,
-    body: Option<RequestBody>,
-) -> Result<BodyMimeInfo, ApicizeAppError> {
-    let mut workspaces = workspaces_state.workspaces.write().await;
-    let sessions = sessions_state.sessions.read().await;
-    let session = sessions.get_session(session_id)?;
-
-    let (body_mime_type, body_length) = if let Some(body) = &body {
-        (
-            Some(Workspaces::get_body_type(body)),
+    authorization_id: &str,
+) -> Result<CachedTokenInfo, ApicizeAppError> {
+    // Collect settings so that locks are not held while waiting on the user
+    let (parameters, client) = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        let info = workspaces.get_workspace_info(&session.workspace_id)?;
+        let workspace = &info.workspace;
+        match workspaces.get_authorization_settings(&session.workspace_id, authorization_id)? {
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::OAuth2Device(parameters))) => {
+                let parameters = parameters.substitute(&info.get_substitutions(None));
+                let client = build_client(
+                    workspace
+                        .certificates
+                        .get_optional(&parameters.selected_certificate.id),
+                    workspace
+                        .proxies
+                        .get_optional(&parameters.selected_proxy.id),
+                )
+                .map_err(ApicizeAppError::InvalidOperation)?;
+                (parameters, client)
+            }
+            Some(AuthorizationSettings::Cipher { .. }) => {
+                return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                    description:
+                        "Unable to retrieve tokens from encrypted authorization configuration"
+                            .to_string(),
+                }));
+            }
+            _ => {
+                return Err(ApicizeAppError::InvalidAuthorization(
+                    "Not an OAuth2 device authorization".to_string(),
+                ));
+            }
+        }
+    };
+
+    let result = async {
+        let device = parameters.request_device_authorization(&client).await?;
+        let cancellation = CancellationToken::new();
+        auth_state
+            .device_flows
+            .lock()
+            .unwrap()
+            .insert(device.device_code.clone(), cancellation.clone());
+        app.emit(
+            "oauth2-device-authorization",
+            DeviceAuthorizationPrompt::new(authorization_id, &device),
+        )
+        .unwrap();
+        let result = tokio::select! {
+            _ = cancellation.cancelled() => Err("Device authorization was cancelled".to_string()),
+            result = parameters.poll_for_token(&client, &device) => result,
+        };
+        auth_state
+            .device_flows
+            .lock()
+            .unwrap()
+            .remove(&device.device_code);
+        result
+    }
+    .await;
+
+    match result {
+        Ok(token_info) => {
+            store_oauth2_token_in_cache(authorization_id, token_info.clone()).await;
+            persist_cached_tokens(&app).await;
+            app.emit("oauth2-device-success", authorization_id).unwrap();
+            Ok(token_info)
+        }
+        Err(err) => {
+            app.emit("oauth2-device-error", &err).unwrap();
+            Err(ApicizeAppError::InvalidOperation(err))
+        }
+    }
+}
+
+#[tauri::command]
+async fn retrieve_oauth2_grant_token(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    authorization_id: &str,
+) -> Result<CachedTokenInfo, ApicizeAppError> {
+    let token_request = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        let info = workspaces.get_workspace_info(&session.workspace_id)?;
+        match workspaces.get_authorization_settings(&session.workspace_id, authorization_id)? {
+            Some(AuthorizationSettings::Plain(settings)) => settings
+                .create_token_request(&info.workspace, &info.get_substitutions(None))
+                .ok_or_else(|| {
+                    ApicizeAppError::InvalidAuthorization(
+                        "Not an OAuth2 password or JWT bearer authorization".to_string(),
+                    )
+                })?
+                .map_err(ApicizeAppError::InvalidOperation)?,
+            Some(AuthorizationSettings::Cipher { .. }) => {
+                return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                    description:
+                        "Unable to retrieve tokens from encrypted authorization configuration"
+                            .to_string(),
+                }));
+            }
+            None => {
+                return Err(ApicizeAppError::InvalidAuthorization(
+                    "Not an OAuth2 password or JWT bearer authorization".to_string(),
+                ));
+            }
+        }
+    };
+
+    clear_oauth2_token_from_cache(authorization_id).await;
+    let token_info = token_request
+        .send()
+        .await
+        .map_err(ApicizeAppError::InvalidOperation)?;
+    store_oauth2_token_in_cache(authorization_id, token_info.clone()).await;
+    persist_cached_tokens(&app).await;
+    Ok(token_info)
+}
+
+#[tauri::command]
+async fn discover_oauth2_configuration(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    authorization_id: &str,
+    issuer_url: &str,
+) -> Result<OidcDiscoveryResponse, ApicizeAppError> {
+    let (auth_type, scope, client) = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        let workspace = workspaces.get_workspace(&session.workspace_id)?;
+        let encrypted_error = || {
+            ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Unable to discover configuration for encrypted authorization"
+                    .to_string(),
+            })
+        };
+        let unsupported_error = || {
+            ApicizeAppError::InvalidAuthorization(
+                "Authorization does not support OpenID discovery".to_string(),
+            )
+        };
+        let (auth_type, scope, selected_certificate, selected_proxy) =
+            match workspaces.get_authorization_settings(&session.workspace_id, authorization_id)? {
+                Some(AuthorizationSettings::Plain(settings)) => match &settings {
+                    ExtendedAuthorization::OAuth2Device(parameters) => (
+                        settings.get_update_type(),
+                        parameters.scope.clone(),
+                        parameters.selected_certificate.clone(),
+                        parameters.selected_proxy.clone(),
+                    ),
+                    ExtendedAuthorization::OAuth2Password(parameters) => (
+                        settings.get_update_type(),
+                        parameters.scope.clone(),
+                        parameters.selected_certificate.clone(),
+                        parameters.selected_proxy.clone(),
+                    ),
+                    ExtendedAuthorization::OAuth2JwtBearer(parameters) => (
+                        settings.get_update_type(),
+                        parameters.scope.clone(),
+                        parameters.selected_certificate.clone(),
+                        parameters.selected_proxy.clone(),
+                    ),
+                    _ => return Err(unsupported_error()),
+                },
+                Some(AuthorizationSettings::Cipher { .. }) => return Err(encrypted_error()),
+                None => match workspace.authorizations.entities.get(authorization_id) {
+                    Some(Authorization::Plain(auth)) => match auth.as_ref() {
+                        AuthorizationPlain::OAuth2Client {
+                            scope,
+                            selected_certificate,
+                            selected_proxy,
+                            ..
+                        } => (
+                            AuthorizationUpdateType::OAuth2Client,
+                            scope.clone(),
+                            selected_certificate.clone(),
+                            selected_proxy.clone(),
+                        ),
+                        AuthorizationPlain::OAuth2Pkce { scope, .. } => (
+                            AuthorizationUpdateType::OAuth2Pkce,
+                            scope.clone(),
+                            Selection::new_none(),
+                            Selection::new_none(),
+                        ),
+                        _ => return Err(unsupported_error()),
+                    },
+                    Some(Authorization::Cipher(_)) => return Err(encrypted_error()),
+                    None => {
+                        return Err(ApicizeAppError::InvalidAuthorization(
+                            authorization_id.to_string(),
+                        ));
+                    }
+                },
+            };
+        let client = build_client(
+            workspace
+                .certificates
+                .get_optional(&selected_certificate.id),
+            workspace.proxies.get_optional(&selected_proxy.id),
+        )
+        .map_err(ApicizeAppError::InvalidOperation)?;
+        (auth_type, scope, client)
+    };
+
+    let configuration = OidcConfiguration::discover(&client, issuer_url)
+        .await
+        .map_err(ApicizeAppError::InvalidOperation)?;
+    let mut authorization =
+        configuration.create_update(issuer_url, authorization_id, &auth_type, &scope);
+
+    // Apply the update as if it were made by the sending session
+    let response = update(
+        app,
+        sessions_state,
+        workspaces_state,
+        session_id,
+        EntityUpdate::Authorization(authorization.clone()),
+    )
+    .await?;
+    authorization.validation_warnings = response.validation_warnings;
+
+    Ok(OidcDiscoveryResponse {
+        configuration,
+        authorization,
+    })
+}
+
+#[tauri::command]
+async fn get_request_body(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    request_id: &str,
+) -> Result<RequestBodyInfo, ApicizeAppError> {
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    workspaces.get_request_body(&session.workspace_id, request_id)
+}
+
+#[tauri::command]
+async fn update_request_body(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    request_id: &str,
+    body: Option<RequestBody>,
+) -> Result<BodyMimeInfo, ApicizeAppError> {
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+
+    let (body_mime_type, body_length) = if let Some(body) = &body {
+        (
+            Some(Workspaces::get_body_type(body)),
             Some(Workspaces::get_body_length(body)),
         )
     } else {
@@ -2157,7 +3558,14 @@ async fn update_request_body(
         body_mime_type: body_mime_type.clone(),
         body_length,
     };
+    let previous_body = workspaces
+        .get_request_body(&session.workspace_id, request_id)?
+        .body;
     workspaces.update_request_body(&session.workspace_id, &body_info)?;
+    workspaces
+        .get_workspace_info_mut(&session.workspace_id)?
+        .undo_history
+        .record_body_update(request_id, previous_body);
 
     let response = BodyMimeInfo {
         body_mime_type: body_info.body_mime_type.clone(),
@@ -2206,7 +3614,17 @@ async fn update_request_body_from_clipboard(
             body_length,
         };
 
+        let previous_body = workspaces
+            .get_request_body(&session.workspace_id, request_id)?
+            .body;
         workspaces.update_request_body(&session.workspace_id, &body_info)?;
+        workspaces
+            .get_workspace_info_mut(&session.workspace_id)?
+            .undo_history
+            .record(vec![UndoOperation::UpdateBody {
+                request_id: request_id.to_string(),
+                body: previous_body,
+            }]);
 
         let response = body_info.clone();
 
@@ -2239,13 +3657,40 @@ fn set_pkce_port(state: State<'_, AuthState>, port: u16) {
 }
 
 #[tauri::command]
-fn generate_authorization_info(
+async fn generate_authorization_info(
     state: State<'_, AuthState>,
+    settings_state: State<'_, SettingsState>,
     auth: OAuth2PkceInfo,
     port: u16,
 ) -> Result<OAuth2PkceRequest, String> {
+    let options = {
+        let settings = settings_state.settings.read().await;
+        PkceFlowOptions {
+            port,
+            redirect_path: settings.pkce_redirect_path.clone(),
+            ephemeral_port: settings.pkce_ephemeral_port,
+            timeout: settings.pkce_timeout,
+        }
+    };
+    let pkce = state.pkce.lock().unwrap();
+    pkce.generate_authorization_info(auth, options)
+}
+
+#[tauri::command]
+fn cancel_oauth2_device_flow(state: State<'_, AuthState>, device_code: String) -> bool {
+    match state.device_flows.lock().unwrap().remove(&device_code) {
+        Some(cancellation) => {
+            cancellation.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+fn cancel_pkce_flow(state: State<'_, AuthState>, csrf_token: String) -> bool {
     let pkce = state.pkce.lock().unwrap();
-    pkce.generate_authorization_info(auth, port)
+    pkce.cancel_flow(&csrf_token)
 }
 
 // #[tauri::command]
@@ -2338,6 +3783,171 @@ async fn get_request_active_authorization(
         .clone())
 }
 
+#[tauri::command]
+async fn get_request_active_trusted_roots(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    request_id: &str,
+) -> Result<Option<TrustedRoots>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    workspaces.get_request_active_trusted_roots(&session.workspace_id, request_id)
+}
+
+#[tauri::command]
+async fn get_selected_trusted_roots(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    entity_id: &str,
+) -> Result<Selection, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    workspaces.get_selected_trusted_roots(&session.workspace_id, entity_id)
+}
+
+#[tauri::command]
+async fn get_proxy_settings(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    proxy_id: &str,
+) -> Result<ProxySettings, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    workspaces.get_proxy_settings(&session.workspace_id, proxy_id)
+}
+
+#[tauri::command]
+async fn get_secret_variables(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    scenario_id: &str,
+) -> Result<ScenarioSecretVariables, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    workspaces.get_secret_variables(&session.workspace_id, scenario_id)
+}
+
+#[tauri::command]
+async fn get_authorization_settings(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    authorization_id: &str,
+) -> Result<Option<AuthorizationSettings>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    workspaces.get_authorization_settings(&session.workspace_id, authorization_id)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn sign_aws_request(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    authorization_id: &str,
+    method: &str,
+    url: &str,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+) -> Result<Vec<(String, String)>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    match workspaces.get_authorization_settings(&session.workspace_id, authorization_id)? {
+        Some(AuthorizationSettings::Plain(ExtendedAuthorization::AwsSigV4(parameters))) => {
+            parameters
+                .sign(
+                    method,
+                    url,
+                    &headers,
+                    body.unwrap_or_default().as_bytes(),
+                    chrono::Utc::now(),
+                )
+                .map_err(ApicizeAppError::InvalidOperation)
+        }
+        Some(AuthorizationSettings::Cipher { .. }) => {
+            Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Unable to sign with encrypted authorization".to_string(),
+            }))
+        }
+        _ => Err(ApicizeAppError::InvalidAuthorization(
+            authorization_id.to_string(),
+        )),
+    }
+}
+
+#[tauri::command]
+async fn respond_to_digest_challenge(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    authorization_id: &str,
+    method: &str,
+    url: &str,
+    www_authenticate: &str,
+) -> Result<String, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspaces = workspaces_state.workspaces.read().await;
+    let nonce_counts = &workspaces
+        .get_workspace_info(&session.workspace_id)?
+        .nonce_counts;
+    match workspaces.get_authorization_settings(&session.workspace_id, authorization_id)? {
+        Some(AuthorizationSettings::Plain(ExtendedAuthorization::Digest(parameters))) => parameters
+            .respond(
+                nonce_counts,
+                authorization_id,
+                method,
+                url,
+                www_authenticate,
+            )
+            .map_err(ApicizeAppError::InvalidOperation),
+        Some(AuthorizationSettings::Cipher { .. }) => {
+            Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Unable to respond with encrypted authorization".to_string(),
+            }))
+        }
+        _ => Err(ApicizeAppError::InvalidAuthorization(
+            authorization_id.to_string(),
+        )),
+    }
+}
+
+#[tauri::command]
+async fn test_proxy_resolution(
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    proxy_id: &str,
+    url: String,
+) -> Result<ProxyResolution, ApicizeAppError> {
+    let (proxy, settings) = {
+        let sessions = sessions_state.sessions.read().await;
+        let session = sessions.get_session(session_id)?;
+        let workspaces = workspaces_state.workspaces.read().await;
+        workspaces.get_proxy_for_resolution(&session.workspace_id, proxy_id)?
+    };
+
+    // PAC evaluation and DNS lookups block, so keep them off of the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        resolve_proxy(&proxy, &settings, &url)
+            .map(ProxyResolution::mask_passwords)
+            .map_err(ApicizeAppError::ProxyResolutionError)
+    })
+    .await
+    .map_err(|err| ApicizeAppError::ProxyResolutionError(err.to_string()))?
+}
+
 #[tauri::command]
 async fn get_data_set_content(
     sessions_state: State<'_, SessionsState>,
@@ -2418,6 +4028,11 @@ async fn get(
         EntityType::DataSet => {
             Entity::DataSet(workspaces.get_data_set(&session.workspace_id, entity_id)?)
         }
+        EntityType::TrustedRoots => Entity::TrustedRoots(
+            workspaces
+                .get_trusted_roots(&session.workspace_id, entity_id)?
+                .clone(),
+        ),
         _ => {
             return Err(ApicizeAppError::InvalidTypeForOperation(entity_type));
         }
@@ -2496,6 +4111,9 @@ async fn get_title(
         }
         EntityType::Proxy => workspaces.get_proxy_title(&session.workspace_id, entity_id)?,
         EntityType::DataSet => workspaces.get_data_title(&session.workspace_id, entity_id)?,
+        EntityType::TrustedRoots => {
+            workspaces.get_trusted_roots_title(&session.workspace_id, entity_id)?
+        }
         _ => {
             return Err(ApicizeAppError::InvalidTypeForOperation(entity_type));
         }
@@ -2574,19 +4192,85 @@ async fn add(
             relative_position,
             clone_from_id,
         ),
+        EntityType::TrustedRoots => workspaces.add_trusted_roots(
+            &workspace_id,
+            relative_to_id,
+            relative_position,
+            clone_from_id,
+        ),
         _ => Err(ApicizeAppError::InvalidOperation(format!(
             "Unable to add {entity_type}",
         ))),
     }?;
 
     let info = workspaces.get_workspace_info_mut(&workspace_id)?;
-    info.navigation = Navigation::new(&info.workspace, &info.executions);
+    info.undo_history.record(vec![UndoOperation::Delete {
+        entity_type,
+        entity_id: id.clone(),
+    }]);
+    info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
 
     dispatch_save_state(&app, &sessions, &workspace_id, info, true);
 
     Ok(id)
 }
 
+/// Generated certificate information returned to the UI
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateCertificateResponse {
+    /// ID of the added certificate
+    id: String,
+    /// PEM encoded certificate servers should trust
+    ca_certificate_pem: String,
+    /// PEM encoded private key of a newly generated authority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ca_key_pem: Option<String>,
+}
+
+#[tauri::command]
+async fn generate_client_certificate(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    relative_to_id: Option<&str>,
+    relative_position: Option<IndexedEntityPosition>,
+    request: CertificateGenerationRequest,
+) -> Result<GenerateCertificateResponse, ApicizeAppError> {
+    let generated = generate_certificate(&request)?;
+
+    if let Some(export_file_name) = &request.export_ca_file_name {
+        apicize_lib::save_file_atomically(
+            &PathBuf::from(export_file_name),
+            &generated.ca_certificate_pem,
+        )?;
+    }
+
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspace_id = session.workspace_id.clone();
+    let mut workspaces = workspaces_state.workspaces.write().await;
+
+    let id = workspaces.add_generated_certificate(
+        &workspace_id,
+        relative_to_id,
+        relative_position,
+        generated.certificate,
+    )?;
+
+    let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
+
+    dispatch_save_state(&app, &sessions, &workspace_id, info, true);
+
+    Ok(GenerateCertificateResponse {
+        id,
+        ca_certificate_pem: generated.ca_certificate_pem,
+        ca_key_pem: generated.ca_key_pem,
+    })
+}
+
 fn is_entity_shared<T>(id: &String, entities: &IndexedEntities<T>) -> bool {
     if let Some(vault) = entities.child_ids.get(PERSIST_VAULT)
         && vault.contains(id)
@@ -2613,64 +4297,50 @@ async fn update(
     let session = sessions.get_session(session_id)?;
     let mut workspaces = workspaces_state.workspaces.write().await;
 
-    let result = match &entity_update {
-        EntityUpdate::Request(request) => {
-            workspaces.update_request(&session.workspace_id, request)?
-        }
-        EntityUpdate::RequestGroup(group) => {
-            workspaces.update_group(&session.workspace_id, group)?
-        }
-        EntityUpdate::Scenario(scenario) => {
-            workspaces.update_scenario(&session.workspace_id, scenario)?
-        }
-        EntityUpdate::Authorization(authorization) => {
-            workspaces.update_authorization(&session.workspace_id, authorization)?
-        }
-        EntityUpdate::Certificate(certificate) => {
-            workspaces.update_certificate(&session.workspace_id, certificate)?
-        }
-        EntityUpdate::Proxy(proxy) => workspaces.update_proxy(&session.workspace_id, proxy)?,
-        EntityUpdate::DataSet(data_set) => {
-            workspaces.update_data_set(&session.workspace_id, data_set)?
-        }
-        EntityUpdate::Defaults(defaults) => {
-            let response = workspaces.update_defaults(&session.workspace_id, defaults)?;
-            // Trigger an update so that requests and groups will update their default screens
-            let workspace_session_ids =
-                get_workspace_sessions(&session.workspace_id, &sessions, None);
-            if let Some(session_ids) = workspace_session_ids {
-                for session_id in session_ids {
-                    if let Ok(session) = sessions.get_session(session_id)
-                        && let Some(request_id) = match &session.active_entity {
-                            Some(entity) => match entity.entity_type {
-                                EntityType::Request => Some(entity.entity_id.clone()),
-                                EntityType::Group => Some(entity.entity_id.clone()),
-                                _ => None,
-                            },
-                            None => None,
+    let undo = workspaces.generate_undo_update(&session.workspace_id, &entity_update)?;
+    let result = workspaces.update_entity(&session.workspace_id, &entity_update)?;
+    // Edits of encrypted parameters cannot be reverted, so are not recorded
+    if let Some(undo) = undo {
+        workspaces
+            .get_workspace_info_mut(&session.workspace_id)?
+            .undo_history
+            .record_update(undo);
+    }
+
+    if let EntityUpdate::Defaults(..) = &entity_update {
+        // Trigger an update so that requests and groups will update their default screens
+        let workspace_session_ids = get_workspace_sessions(&session.workspace_id, &sessions, None);
+        if let Some(session_ids) = workspace_session_ids {
+            for session_id in session_ids {
+                if let Ok(session) = sessions.get_session(session_id)
+                    && let Some(request_id) = match &session.active_entity {
+                        Some(entity) => match entity.entity_type {
+                            EntityType::Request => Some(entity.entity_id.clone()),
+                            EntityType::Group => Some(entity.entity_id.clone()),
+                            _ => None,
+                        },
+                        None => None,
+                    }
+                {
+                    let workspace = workspaces.get_workspace(&session.workspace_id)?;
+                    match workspace.requests.entities.get(&request_id) {
+                        Some(RequestEntry::Request(request)) => {
+                            let notification =
+                                EntityUpdate::Request(RequestUpdate::from_selections(request));
+                            app.emit_to(session_id, "update", notification).unwrap();
                         }
-                    {
-                        let workspace = workspaces.get_workspace(&session.workspace_id)?;
-                        match workspace.requests.entities.get(&request_id) {
-                            Some(RequestEntry::Request(request)) => {
-                                let notification =
-                                    EntityUpdate::Request(RequestUpdate::from_selections(request));
-                                app.emit_to(session_id, "update", notification).unwrap();
-                            }
-                            Some(RequestEntry::Group(group)) => {
-                                let notification = EntityUpdate::RequestGroup(
-                                    RequestGroupUpdate::from_selections(group),
-                                );
-                                app.emit_to(session_id, "update", notification).unwrap();
-                            }
-                            _ => {}
+                        Some(RequestEntry::Group(group)) => {
+                            let notification = EntityUpdate::RequestGroup(
+                                RequestGroupUpdate::from_selections(group),
+                            );
+                            app.emit_to(session_id, "update", notification).unwrap();
                         }
+                        _ => {}
                     }
                 }
             }
-            response
         }
-    };
+    }
 
     let info = &workspaces.get_workspace_info(&session.workspace_id)?;
 
@@ -2689,6 +4359,7 @@ async fn update(
         EntityUpdate::Proxy(proxy) => is_entity_shared(&proxy.id, &info.workspace.proxies),
         EntityUpdate::DataSet(..) => false,
         EntityUpdate::Defaults(..) => false,
+        EntityUpdate::TrustedRoots(..) => false,
     };
 
     let display_name = &info.display_name;
@@ -2769,81 +4440,24 @@ async fn delete(
 
     let workspace_id = sessions.get_session(session_id)?.workspace_id.to_string();
 
-    let mut deleted_request = false;
+    let deleted_request = matches!(
+        entity_type,
+        EntityType::RequestEntry | EntityType::Request | EntityType::Group
+    );
 
-    let entities_with_invalid_selections = match entity_type {
-        EntityType::RequestEntry => {
-            deleted_request = true;
-            workspaces.delete_request_entry(&workspace_id, entity_id)
-        }
-        EntityType::Request => {
-            deleted_request = true;
-            workspaces.delete_request_entry(&workspace_id, entity_id)
-        }
-        EntityType::Group => {
-            deleted_request = true;
-            workspaces.delete_request_entry(&workspace_id, entity_id)
-        }
-        EntityType::Scenario => workspaces.delete_scenario(&workspace_id, entity_id),
-        EntityType::Authorization => workspaces.delete_authorization(&workspace_id, entity_id),
-        EntityType::Certificate => workspaces.delete_certificate(&workspace_id, entity_id),
-        EntityType::Proxy => workspaces.delete_proxy(&workspace_id, entity_id),
-        EntityType::DataSet => workspaces.delete_data_set(&workspace_id, entity_id),
-        _ => Err(ApicizeAppError::InvalidOperation(
-            "Unable to perform delete on entity".to_owned(),
-        )),
-    }?;
+    let deleted = workspaces.capture_deleted_entity(&workspace_id, entity_type, entity_id)?;
+    let entities_with_invalid_selections =
+        workspaces.delete_entity(&workspace_id, entity_type, entity_id)?;
 
     // Collect notifications of requests/groups that had their selections updated
-    let notifications = if let Some(entities) = entities_with_invalid_selections {
-        // Set up update notifications for requests and authorizations
-        let mut notifications = entities
-            .request_or_group_ids
-            .iter()
-            .filter_map(|id| {
-                workspaces
-                    .generate_request_selection_update(&workspace_id, id)
-                    .ok()
-            })
-            .chain(entities.authorization_ids.iter().filter_map(|id| {
-                workspaces
-                    .generate_authorization_selection_update(&workspace_id, id)
-                    .ok()
-            }))
-            .collect::<Vec<(EntityUpdate, UpdatedNavigationEntry)>>();
-
-        // Set up update notifications for workspace defaults
-        if entities.defaults {
-            let defaults = workspaces.get_defaults(&workspace_id)?;
-            notifications.push((
-                EntityUpdate::Defaults(DefaultsUpdate {
-                    entity_type: EntityType::Defaults,
-                    selected_scenario: Some(defaults.selected_scenario),
-                    selected_authorization: Some(defaults.selected_authorization),
-                    selected_certificate: Some(defaults.selected_certificate),
-                    selected_proxy: Some(defaults.selected_proxy),
-                    selected_data: Some(defaults.selected_data),
-                    validation_warnings: defaults.validation_warnings.clone(),
-                }),
-                UpdatedNavigationEntry {
-                    entity_type: EntityType::Defaults,
-                    execution_state: ExecutionState::empty(),
-                    disabled: false,
-                    id: Navigation::DEFAULTS_ID.to_string(),
-                    name: Navigation::DEFAULTS_NAME.to_string(),
-                    validation_state: defaults.validation_state,
-                    encrypted: false,
-                    parameter_store: None,
-                },
-            ))
-        }
-
-        notifications
-    } else {
-        Vec::default()
+    let notifications = match entities_with_invalid_selections {
+        Some(entities) => workspaces.generate_selection_updates(&workspace_id, &entities)?,
+        None => Vec::default(),
     };
 
     let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    info.undo_history
+        .record(vec![UndoOperation::Restore(Box::new(deleted))]);
 
     // Clear execution state for deleted requests/groups
     let deleted_executed_request_ids = if deleted_request {
@@ -2908,51 +4522,21 @@ async fn move_entity(
     let workspace_id = session.workspace_id.clone();
     let mut workspaces = workspaces_state.workspaces.write().await;
 
+    let undo = workspaces.generate_undo_move(&workspace_id, entity_type, entity_id)?;
     let nav_position = relative_position.clone();
-    let was_moved = match entity_type {
-        EntityType::RequestEntry => workspaces.move_request_entry(
-            &workspace_id,
-            entity_id,
-            relative_to_id,
-            relative_position,
-        ),
-        EntityType::Request => workspaces.move_request_entry(
-            &workspace_id,
-            entity_id,
-            relative_to_id,
-            relative_position,
-        ),
-        EntityType::Group => workspaces.move_request_entry(
-            &workspace_id,
-            entity_id,
-            relative_to_id,
-            relative_position,
-        ),
-        EntityType::Scenario => {
-            workspaces.move_scenario(&workspace_id, entity_id, relative_to_id, relative_position)
-        }
-        EntityType::Authorization => workspaces.move_authorization(
-            &workspace_id,
-            entity_id,
-            relative_to_id,
-            relative_position,
-        ),
-        EntityType::Certificate => {
-            workspaces.move_certificate(&workspace_id, entity_id, relative_to_id, relative_position)
-        }
-        EntityType::DataSet => {
-            workspaces.move_data_set(&workspace_id, entity_id, relative_to_id, relative_position)
-        }
-        EntityType::Proxy => {
-            workspaces.move_proxy(&workspace_id, entity_id, relative_to_id, relative_position)
-        }
-        _ => Err(ApicizeAppError::InvalidOperation(format!(
-            "Unable to move {entity_type}",
-        ))),
-    }?;
+    let was_moved = workspaces.move_entity(
+        &workspace_id,
+        entity_type,
+        entity_id,
+        relative_to_id,
+        relative_position,
+    )?;
 
     let results = if was_moved {
         let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+        if let Some(undo) = undo {
+            info.undo_history.record(vec![undo]);
+        }
         info.navigation.move_navigation_entity(
             entity_id,
             relative_to_id,
@@ -2968,6 +4552,193 @@ async fn move_entity(
     Ok(results)
 }
 
+#[tauri::command]
+async fn undo(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+) -> Result<bool, ApicizeAppError> {
+    apply_undo_history(&app, &sessions_state, &workspaces_state, session_id, false).await
+}
+
+#[tauri::command]
+async fn redo(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+) -> Result<bool, ApicizeAppError> {
+    apply_undo_history(&app, &sessions_state, &workspaces_state, session_id, true).await
+}
+
+/// Reload the session's workbook and data set files from disk, discarding unsaved changes
+#[tauri::command]
+async fn reload_workspace(app: AppHandle, session_id: &str) -> Result<(), ApicizeAppError> {
+    apply_external_changes(&app, session_id, false).await?;
+    Ok(())
+}
+
+/// Merge changes made on disk to the session's workbook and data set files with unsaved
+/// changes, returning entities changed in both (which are left as they are locally)
+#[tauri::command]
+async fn merge_workspace(
+    app: AppHandle,
+    session_id: &str,
+) -> Result<Vec<MergeConflict>, ApicizeAppError> {
+    apply_external_changes(&app, session_id, true).await
+}
+
+/// Undo (or redo) the last edit to the session's workspace and notify each of the
+/// workspace's sessions, returns false if there was nothing to undo (or redo)
+async fn apply_undo_history(
+    app: &AppHandle,
+    sessions_state: &SessionsState,
+    workspaces_state: &WorkspacesState,
+    session_id: &str,
+    redo: bool,
+) -> Result<bool, ApicizeAppError> {
+    let mut sessions = sessions_state.sessions.write().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+
+    let workspace_id = sessions.get_session(session_id)?.workspace_id.to_string();
+
+    let applied = if redo {
+        workspaces.redo(&workspace_id)
+    } else {
+        workspaces.undo(&workspace_id)
+    };
+
+    let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
+
+    let applied = match applied {
+        Ok(Some(applied)) => applied,
+        Ok(None) => return Ok(false),
+        Err(err) => {
+            // Operations applied before the failure can still be reverted
+            dispatch_save_state(app, &sessions, &workspace_id, info, true);
+            return Err(err);
+        }
+    };
+
+    // Clear execution state for deleted requests/groups
+    let mut deleted_executed_request_ids = Vec::<String>::new();
+    for (entity_type, entity_id) in &applied.deleted {
+        if matches!(
+            entity_type,
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group
+        ) {
+            deleted_executed_request_ids.extend(info.delete_executions(entity_id, true));
+        }
+    }
+
+    for session_id in sessions
+        .get_workspace_session_ids(&workspace_id)
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>()
+    {
+        for update in &applied.updates {
+            app.emit_to(&session_id, "update", update).unwrap();
+        }
+        if let Ok(session) = sessions.get_session_mut(&session_id) {
+            if session.active_entity.as_ref().is_some_and(|active| {
+                applied
+                    .deleted
+                    .iter()
+                    .any(|(_, entity_id)| active.entity_id == *entity_id)
+            }) {
+                session.update_active_entity(&None);
+            }
+            for deleted_request_id in &deleted_executed_request_ids {
+                session.remove_request_exec_ctr(deleted_request_id);
+                session.remove_execution_result_view_state(deleted_request_id);
+            }
+        }
+    }
+
+    dispatch_save_state(app, &sessions, &workspace_id, info, true);
+    Ok(true)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn move_parameter_to_store(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    settings_state: State<'_, SettingsState>,
+    session_id: &str,
+    entity_type: EntityType,
+    entity_id: &str,
+    parameter_store: Option<ParameterStore>,
+    password: Option<String>,
+) -> Result<Vec<String>, ApicizeAppError> {
+    // Unlock a locked destination store with the password the user was prompted for (or
+    // the one remembered in the keyring), so that moved parameters can be saved to it
+    if let Some(store) = parameter_store {
+        let locked = {
+            let sessions = sessions_state.sessions.read().await;
+            let workspaces = workspaces_state.workspaces.read().await;
+            let workspace =
+                workspaces.get_workspace(&sessions.get_session(session_id)?.workspace_id)?;
+            match store {
+                ParameterStore::Vault => workspace.vault_lock_status.is_locked(),
+                ParameterStore::Private => workspace.private_lock_status.is_locked(),
+            }
+        };
+        if locked {
+            let has_password = password.as_ref().is_some_and(|p| !p.is_empty());
+            if let Err(err) = decrypt_parameters(
+                app.clone(),
+                sessions_state.clone(),
+                workspaces_state.clone(),
+                settings_state,
+                session_id,
+                store,
+                password,
+            )
+            .await
+            {
+                return Err(if has_password {
+                    err
+                } else {
+                    ApicizeAppError::ParameterStoreLocked(
+                        match store {
+                            ParameterStore::Vault => "Vault",
+                            ParameterStore::Private => "Private",
+                        }
+                        .to_string(),
+                    )
+                });
+            }
+        }
+    }
+
+    let sessions = sessions_state.sessions.read().await;
+    let session = sessions.get_session(session_id)?;
+    let workspace_id = session.workspace_id.clone();
+    let mut workspaces = workspaces_state.workspaces.write().await;
+
+    let undo = workspaces.generate_undo_move(&workspace_id, entity_type, entity_id)?;
+    if !workspaces.move_parameter_to_store(
+        &workspace_id,
+        entity_type,
+        entity_id,
+        parameter_store,
+    )? {
+        return Ok(vec![]);
+    }
+
+    let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    if let Some(undo) = undo {
+        info.undo_history.record(vec![undo]);
+    }
+    dispatch_save_state(&app, &sessions, &workspace_id, info, true);
+    workspaces.find_parent_ids(&workspace_id, entity_type, entity_id)
+}
+
 #[tauri::command]
 async fn list_logs() -> Result<Vec<ReqwestEvent>, ApicizeAppError> {
     match REQWEST_LOGGER.get() {
@@ -3001,6 +4772,54 @@ async fn get_entity_type(
     workspaces.get_entity_type(&session.workspace_id, entity_id)
 }
 
+/// Apply a bulk update to requests, returning the response for each updated request
+#[tauri::command]
+async fn bulk_update_requests(
+    app: AppHandle,
+    sessions_state: State<'_, SessionsState>,
+    workspaces_state: State<'_, WorkspacesState>,
+    session_id: &str,
+    bulk_update: BulkRequestUpdate,
+) -> Result<HashMap<String, UpdateWithNavigationResponse>, ApicizeAppError> {
+    let sessions = sessions_state.sessions.read().await;
+    let mut workspaces = workspaces_state.workspaces.write().await;
+    let workspace_id = sessions.get_session(session_id)?.workspace_id.clone();
+
+    let result = workspaces.bulk_update_requests(&workspace_id, &bulk_update);
+    let info = workspaces.get_workspace_info_mut(&workspace_id)?;
+    if result.is_err() {
+        // Rebuild navigation to reflect any updates applied before the failure
+        info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
+    }
+
+    if let Ok(results) = &result {
+        for workspace_session_id in sessions.get_workspace_session_ids(&workspace_id) {
+            for (update, response) in results {
+                if let Some(updated_navigation) = &response.navigation {
+                    app.emit_to(workspace_session_id, "navigation_entry", updated_navigation)
+                        .unwrap();
+                }
+                if workspace_session_id != session_id {
+                    app.emit_to(
+                        workspace_session_id,
+                        "update",
+                        EntityUpdate::Request(update.clone()),
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    dispatch_save_state(&app, &sessions, &workspace_id, info, result.is_err());
+    result.map(|results| {
+        results
+            .into_iter()
+            .map(|(update, response)| (update.id, response))
+            .collect()
+    })
+}
+
 #[tauri::command]
 async fn find_descendant_groups(
     sessions_state: State<'_, SessionsState>,
@@ -3241,6 +5060,8 @@ async fn save_data_set_file_inner(
             data_set.source = old_source;
             return Err(err);
         }
+        info.external_changes
+            .acknowledge(&WatchedFile::DataSet(data_set_id.to_string()));
     }
 
     // Broadcast the update
@@ -3478,7 +5299,11 @@ async fn clipboard_paste_data(
     }?;
 
     let info = workspaces.get_workspace_info_mut(&workspace_id)?;
-    info.navigation = Navigation::new(&info.workspace, &info.executions);
+    info.undo_history.record(vec![UndoOperation::Delete {
+        entity_type,
+        entity_id: id.clone(),
+    }]);
+    info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
 
     dispatch_save_state(&app, &sessions, &workspace_id, info, true);
 
@@ -3507,4 +5332,6 @@ enum LockStatusUpdate {
 enum OpenExisting {
     FileName(String),
     SessionId(String),
+    /// Recovery snapshot ID
+    Recovery(String),
 }
@@ -8,7 +8,10 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::workspaces::{EntityType, RequestExecution};
+use crate::{
+    extensions::WorkbookExtensions,
+    workspaces::{EntityType, RequestExecution},
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -91,6 +94,7 @@ pub struct Navigation {
     pub certificates: ParamNavigationSection,
     pub proxies: ParamNavigationSection,
     pub data_sets: Vec<NavigationEntry>,
+    pub trusted_roots: Vec<NavigationEntry>,
     pub defaults: NavigationEntry,
 }
 
@@ -271,6 +275,7 @@ impl Navigation {
 
     pub fn new(
         workspace: &Workspace,
+        extensions: &WorkbookExtensions,
         executions: &FxHashMap<String, RequestExecution>,
     ) -> Navigation {
         Navigation {
@@ -295,6 +300,20 @@ impl Navigation {
                     }
                 })
                 .collect(),
+            trusted_roots: extensions
+                .trusted_roots
+                .top_level_ids
+                .iter()
+                .filter_map(|id| extensions.trusted_roots.entities.get(id))
+                .map(|entity| NavigationEntry {
+                    id: entity.id.clone(),
+                    name: entity.get_title(),
+                    validation_state: entity.get_validation_state(),
+                    execution_state: ExecutionState::empty(),
+                    parameter_store: None,
+                    encrypted: false,
+                })
+                .collect(),
             defaults: NavigationEntry {
                 id: Self::DEFAULTS_ID.to_string(),
                 name: Self::DEFAULTS_NAME.to_string(),
@@ -356,6 +375,15 @@ impl Navigation {
                     self.data_sets.remove(idx);
                 }
             }
+            EntityType::TrustedRoots => {
+                if let Some(idx) = self
+                    .trusted_roots
+                    .iter()
+                    .position(|entity| entity.id == entity_id)
+                {
+                    self.trusted_roots.remove(idx);
+                }
+            }
             _ => {}
         }
     }
@@ -411,6 +439,16 @@ impl Navigation {
                     entry.execution_state = update.execution_state;
                 }
             }
+            EntityType::TrustedRoots => {
+                if let Some(entry) = self
+                    .trusted_roots
+                    .iter_mut()
+                    .find(|entity| entity.id == update.id)
+                {
+                    entry.name = update.name.to_string();
+                    entry.validation_state = update.validation_state;
+                }
+            }
             _ => {}
         };
     }
@@ -499,36 +537,40 @@ impl Navigation {
             }
         }
 
-        let entry = match removed {
+        let mut entry = match removed {
             Some(e) => e,
             None => return false,
         };
 
-        // Insert at new position
+        // Insert at new position, updating the store if moved to a different one
         match relative_to_id {
             PERSIST_WORKBOOK => {
+                entry.parameter_store = None;
                 section.public.push(entry);
                 return true;
             }
             PERSIST_PRIVATE => {
+                entry.parameter_store = Some(ParameterStore::Private);
                 section.private.push(entry);
                 return true;
             }
             PERSIST_VAULT => {
+                entry.parameter_store = Some(ParameterStore::Vault);
                 section.vault.push(entry);
                 return true;
             }
             _ => {
-                for list in [
-                    &mut section.public,
-                    &mut section.private,
-                    &mut section.vault,
+                for (list, parameter_store) in [
+                    (&mut section.public, None),
+                    (&mut section.private, Some(ParameterStore::Private)),
+                    (&mut section.vault, Some(ParameterStore::Vault)),
                 ] {
                     if let Some(idx) = list.iter().position(|e| e.id == relative_to_id) {
                         let insert_idx = match position {
                             IndexedEntityPosition::Before => idx,
                             _ => idx + 1,
                         };
+                        entry.parameter_store = parameter_store;
                         list.insert(insert_idx, entry);
                         return true;
                     }
@@ -539,6 +581,31 @@ impl Navigation {
         false
     }
 
+    /// Move an entry within a flat navigation list
+    fn move_list_entry(
+        entity_id: &str,
+        relative_to_id: &str,
+        position: &IndexedEntityPosition,
+        list: &mut Vec<NavigationEntry>,
+    ) -> bool {
+        if let Some(idx) = list.iter().position(|e| e.id == entity_id) {
+            let entry = list.remove(idx);
+            if let Some(rel_idx) = list.iter().position(|e| e.id == relative_to_id) {
+                let insert_idx = match position {
+                    IndexedEntityPosition::Before => rel_idx,
+                    _ => rel_idx + 1,
+                };
+                list.insert(insert_idx, entry);
+                true
+            } else {
+                list.push(entry);
+                false
+            }
+        } else {
+            false
+        }
+    }
+
     /// Incrementally move an entity within the navigation tree
     pub fn move_navigation_entity(
         &mut self,
@@ -571,26 +638,36 @@ impl Navigation {
                 Self::move_param_entry(entity_id, relative_to_id, position, &mut self.proxies)
             }
             EntityType::DataSet => {
-                if let Some(idx) = self.data_sets.iter().position(|e| e.id == entity_id) {
-                    let entry = self.data_sets.remove(idx);
-                    if let Some(rel_idx) =
-                        self.data_sets.iter().position(|e| e.id == relative_to_id)
-                    {
-                        let insert_idx = match position {
-                            IndexedEntityPosition::Before => rel_idx,
-                            _ => rel_idx + 1,
-                        };
-                        self.data_sets.insert(insert_idx, entry);
-                        true
-                    } else {
-                        self.data_sets.push(entry);
-                        false
-                    }
-                } else {
-                    false
-                }
+                Self::move_list_entry(entity_id, relative_to_id, position, &mut self.data_sets)
+            }
+            EntityType::TrustedRoots => {
+                Self::move_list_entry(entity_id, relative_to_id, position, &mut self.trusted_roots)
             }
             _ => false,
         }
     }
 }
+
+/// Return IDs of indexed entities with each parent preceding its children, including
+/// entities under groupings that are not themselves entities (i.e. parameter stores)
+pub fn get_ordered_ids<T>(index: &IndexedEntities<T>) -> Vec<String> {
+    let mut groupings = index
+        .child_ids
+        .keys()
+        .filter(|id| !index.entities.contains_key(*id))
+        .collect::<Vec<_>>();
+    groupings.sort();
+
+    let mut ids = index.top_level_ids.clone();
+    for grouping in groupings {
+        ids.extend(index.child_ids[grouping].iter().cloned());
+    }
+    let mut next = 0;
+    while next < ids.len() {
+        if let Some(child_ids) = index.child_ids.get(&ids[next]) {
+            ids.extend(child_ids.iter().cloned());
+        }
+        next += 1;
+    }
+    ids
+}
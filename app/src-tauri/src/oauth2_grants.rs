@@ -0,0 +1,917 @@
+//! OAuth2 grant types performed by Apicize, with resulting tokens stored in the
+//! apicize_lib OAuth2 token cache so they are used when dispatching requests
+
+use std::{
+    collections::HashMap,
+    ops::Add,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use apicize_lib::{CachedTokenInfo, Certificate, Proxy, Selection, certificate::CertificatePlain};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use p12_keystore::{KeyStore, KeyStoreEntry, Pkcs12ImportPolicy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::substitution::clone_and_sub;
+
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+pub const JWT_BEARER_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// Default lifetime of JWT assertions, in seconds
+const DEFAULT_ASSERTION_LIFETIME: u64 = 300;
+
+/// Default polling interval when the provider does not specify one (RFC 8628 section 3.5)
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+/// Settings for the OAuth2 Device Authorization Grant (RFC 8628)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2DeviceParameters {
+    /// URL to request device and user codes from
+    pub device_authorization_url: String,
+    /// URL to retrieve access token from
+    pub access_token_url: String,
+    /// Client ID
+    pub client_id: String,
+    /// Client secret (allowed to be blank)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_secret: String,
+    /// Scope to add to token (multiple scopes should be space-delimited)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    /// Audience to add to token
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub audience: String,
+    /// Selected certificate, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_certificate: Selection,
+    /// Selected proxy, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_proxy: Selection,
+}
+
+impl Default for OAuth2DeviceParameters {
+    fn default() -> Self {
+        OAuth2DeviceParameters {
+            device_authorization_url: String::default(),
+            access_token_url: String::default(),
+            client_id: String::default(),
+            client_secret: String::default(),
+            scope: String::default(),
+            audience: String::default(),
+            selected_certificate: Selection::new_none(),
+            selected_proxy: Selection::new_none(),
+        }
+    }
+}
+
+impl OAuth2DeviceParameters {
+    /// Return validation errors, indexed by field name
+    pub fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        if self.device_authorization_url.trim().is_empty() {
+            errors.push((
+                "deviceAuthorizationUrl",
+                "Device authorization URL is required".to_string(),
+            ));
+        }
+        if self.access_token_url.trim().is_empty() {
+            errors.push(("accessTokenUrl", "Access token URL is required".to_string()));
+        }
+        if self.client_id.trim().is_empty() {
+            errors.push(("clientId", "Client ID is required".to_string()));
+        }
+        errors
+    }
+
+    /// Return a copy of the parameters with substitution values applied
+    pub fn substitute(&self, subs: &HashMap<String, String>) -> Self {
+        OAuth2DeviceParameters {
+            device_authorization_url: clone_and_sub(&self.device_authorization_url, subs),
+            access_token_url: clone_and_sub(&self.access_token_url, subs),
+            client_id: clone_and_sub(&self.client_id, subs),
+            client_secret: clone_and_sub(&self.client_secret, subs),
+            scope: clone_and_sub(&self.scope, subs),
+            audience: clone_and_sub(&self.audience, subs),
+            ..self.clone()
+        }
+    }
+
+    /// Request device and user codes from the provider
+    pub async fn request_device_authorization(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<DeviceAuthorization, String> {
+        let mut form = vec![("client_id", self.client_id.as_str())];
+        if !self.scope.is_empty() {
+            form.push(("scope", self.scope.as_str()));
+        }
+        if !self.audience.is_empty() {
+            form.push(("audience", self.audience.as_str()));
+        }
+        let response = client
+            .post(&self.device_authorization_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|err| format!("Unable to request device authorization: {err}"))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|err| err.to_string())?;
+        if !status.is_success() {
+            return Err(describe_error_response(status, &body));
+        }
+        serde_json::from_str::<DeviceAuthorization>(&body)
+            .map_err(|err| format!("Invalid device authorization response: {err}"))
+    }
+
+    /// Poll the token endpoint until the user completes authorization, the device
+    /// code expires or the provider reports an error
+    pub async fn poll_for_token(
+        &self,
+        client: &reqwest::Client,
+        device: &DeviceAuthorization,
+    ) -> Result<CachedTokenInfo, String> {
+        let expires_at = Instant::now() + Duration::from_secs(device.expires_in);
+        let mut interval = device.interval.unwrap_or(DEFAULT_POLL_INTERVAL);
+
+        let mut form = vec![
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ("device_code", device.device_code.as_str()),
+            ("client_id", self.client_id.as_str()),
+        ];
+        if !self.client_secret.is_empty() {
+            form.push(("client_secret", self.client_secret.as_str()));
+        }
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if Instant::now() >= expires_at {
+                return Err("Device code expired before authorization completed".to_string());
+            }
+
+            let response = client
+                .post(&self.access_token_url)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .form(&form)
+                .send()
+                .await
+                .map_err(|err| format!("Unable to request access token: {err}"))?;
+            let status = response.status();
+            let body = response.text().await.map_err(|err| err.to_string())?;
+            match parse_poll_response(status, &body) {
+                PollOutcome::Token(token) => return Ok(token),
+                PollOutcome::Pending => {}
+                PollOutcome::SlowDown => interval += DEFAULT_POLL_INTERVAL,
+                PollOutcome::Failed(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Settings for the OAuth2 Resource Owner Password Credentials Grant (RFC 6749 section 4.3)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2PasswordParameters {
+    /// URL to retrieve access token from
+    pub access_token_url: String,
+    /// Client ID
+    pub client_id: String,
+    /// Client secret (allowed to be blank)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_secret: String,
+    /// Resource owner user name
+    pub username: String,
+    /// Resource owner password
+    #[serde(default)]
+    pub password: String,
+    /// Scope to add to token (multiple scopes should be space-delimited)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    /// Audience to add to token
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub audience: String,
+    /// If true, client credentials are sent in body instead of header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_credentials_in_body: Option<bool>,
+    /// Selected certificate, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_certificate: Selection,
+    /// Selected proxy, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_proxy: Selection,
+}
+
+impl Default for OAuth2PasswordParameters {
+    fn default() -> Self {
+        OAuth2PasswordParameters {
+            access_token_url: String::default(),
+            client_id: String::default(),
+            client_secret: String::default(),
+            username: String::default(),
+            password: String::default(),
+            scope: String::default(),
+            audience: String::default(),
+            send_credentials_in_body: None,
+            selected_certificate: Selection::new_none(),
+            selected_proxy: Selection::new_none(),
+        }
+    }
+}
+
+impl OAuth2PasswordParameters {
+    /// Return validation errors, indexed by field name
+    pub fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        if self.access_token_url.trim().is_empty() {
+            errors.push(("accessTokenUrl", "Access token URL is required".to_string()));
+        }
+        if self.client_id.trim().is_empty() {
+            errors.push(("clientId", "Client ID is required".to_string()));
+        }
+        if self.username.trim().is_empty() {
+            errors.push(("username", "User name is required".to_string()));
+        }
+        errors
+    }
+
+    /// Return a copy of the parameters with substitution values applied
+    pub fn substitute(&self, subs: &HashMap<String, String>) -> Self {
+        OAuth2PasswordParameters {
+            access_token_url: clone_and_sub(&self.access_token_url, subs),
+            client_id: clone_and_sub(&self.client_id, subs),
+            client_secret: clone_and_sub(&self.client_secret, subs),
+            username: clone_and_sub(&self.username, subs),
+            password: clone_and_sub(&self.password, subs),
+            scope: clone_and_sub(&self.scope, subs),
+            audience: clone_and_sub(&self.audience, subs),
+            ..self.clone()
+        }
+    }
+
+    /// Build the token request for this grant
+    pub fn token_request(&self, client: reqwest::Client) -> TokenRequest {
+        let mut form = vec![
+            ("grant_type".to_string(), "password".to_string()),
+            ("username".to_string(), self.username.clone()),
+            ("password".to_string(), self.password.clone()),
+        ];
+        if !self.scope.is_empty() {
+            form.push(("scope".to_string(), self.scope.clone()));
+        }
+        if !self.audience.is_empty() {
+            form.push(("audience".to_string(), self.audience.clone()));
+        }
+        let basic_auth = if self.send_credentials_in_body.unwrap_or(false) {
+            form.push(("client_id".to_string(), self.client_id.clone()));
+            if !self.client_secret.is_empty() {
+                form.push(("client_secret".to_string(), self.client_secret.clone()));
+            }
+            None
+        } else {
+            Some((self.client_id.clone(), self.client_secret.clone()))
+        };
+        TokenRequest {
+            client,
+            url: self.access_token_url.clone(),
+            form,
+            basic_auth,
+        }
+    }
+}
+
+/// Algorithm used to sign JWT assertions
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Default)]
+pub enum JwtSigningAlgorithm {
+    #[default]
+    #[serde(rename = "RS256")]
+    Rs256,
+    #[serde(rename = "RS384")]
+    Rs384,
+    #[serde(rename = "RS512")]
+    Rs512,
+    #[serde(rename = "PS256")]
+    Ps256,
+    #[serde(rename = "PS384")]
+    Ps384,
+    #[serde(rename = "PS512")]
+    Ps512,
+    #[serde(rename = "ES256")]
+    Es256,
+    #[serde(rename = "ES384")]
+    Es384,
+}
+
+impl JwtSigningAlgorithm {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            JwtSigningAlgorithm::Rs256 => Algorithm::RS256,
+            JwtSigningAlgorithm::Rs384 => Algorithm::RS384,
+            JwtSigningAlgorithm::Rs512 => Algorithm::RS512,
+            JwtSigningAlgorithm::Ps256 => Algorithm::PS256,
+            JwtSigningAlgorithm::Ps384 => Algorithm::PS384,
+            JwtSigningAlgorithm::Ps512 => Algorithm::PS512,
+            JwtSigningAlgorithm::Es256 => Algorithm::ES256,
+            JwtSigningAlgorithm::Es384 => Algorithm::ES384,
+        }
+    }
+}
+
+/// Settings for the OAuth2 JWT Bearer Grant (RFC 7523), using an assertion signed by
+/// a certificate's private key
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2JwtBearerParameters {
+    /// URL to retrieve access token from
+    pub access_token_url: String,
+    /// Client ID, if required by the provider
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_id: String,
+    /// Scope to add to token (multiple scopes should be space-delimited)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    /// Certificate whose private key signs the assertion
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub signing_certificate: Selection,
+    /// Algorithm used to sign the assertion
+    #[serde(default)]
+    pub algorithm: JwtSigningAlgorithm,
+    /// Assertion issuer (iss) claim
+    pub issuer: String,
+    /// Assertion subject (sub) claim
+    pub subject: String,
+    /// Assertion audience (aud) claim, defaults to the access token URL
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assertion_audience: String,
+    /// Number of seconds until the assertion expires (exp)
+    #[serde(default = "default_assertion_lifetime")]
+    pub assertion_lifetime: u64,
+    /// Selected certificate, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_certificate: Selection,
+    /// Selected proxy, if applicable
+    #[serde(
+        skip_serializing_if = "Selection::is_none",
+        default = "Selection::new_none"
+    )]
+    pub selected_proxy: Selection,
+}
+
+fn default_assertion_lifetime() -> u64 {
+    DEFAULT_ASSERTION_LIFETIME
+}
+
+impl Default for OAuth2JwtBearerParameters {
+    fn default() -> Self {
+        OAuth2JwtBearerParameters {
+            access_token_url: String::default(),
+            client_id: String::default(),
+            scope: String::default(),
+            signing_certificate: Selection::new_none(),
+            algorithm: JwtSigningAlgorithm::default(),
+            issuer: String::default(),
+            subject: String::default(),
+            assertion_audience: String::default(),
+            assertion_lifetime: DEFAULT_ASSERTION_LIFETIME,
+            selected_certificate: Selection::new_none(),
+            selected_proxy: Selection::new_none(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AssertionClaims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    exp: u64,
+    iat: u64,
+    jti: String,
+}
+
+impl OAuth2JwtBearerParameters {
+    /// Return validation errors, indexed by field name
+    pub fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        if self.access_token_url.trim().is_empty() {
+            errors.push(("accessTokenUrl", "Access token URL is required".to_string()));
+        }
+        if self.signing_certificate.is_none() {
+            errors.push((
+                "signingCertificate",
+                "Signing certificate is required".to_string(),
+            ));
+        }
+        if self.issuer.trim().is_empty() {
+            errors.push(("issuer", "Issuer is required".to_string()));
+        }
+        if self.subject.trim().is_empty() {
+            errors.push(("subject", "Subject is required".to_string()));
+        }
+        if self.assertion_lifetime == 0 {
+            errors.push((
+                "assertionLifetime",
+                "Assertion lifetime must be greater than zero".to_string(),
+            ));
+        }
+        errors
+    }
+
+    /// Return a copy of the parameters with substitution values applied
+    pub fn substitute(&self, subs: &HashMap<String, String>) -> Self {
+        OAuth2JwtBearerParameters {
+            access_token_url: clone_and_sub(&self.access_token_url, subs),
+            client_id: clone_and_sub(&self.client_id, subs),
+            scope: clone_and_sub(&self.scope, subs),
+            issuer: clone_and_sub(&self.issuer, subs),
+            subject: clone_and_sub(&self.subject, subs),
+            assertion_audience: clone_and_sub(&self.assertion_audience, subs),
+            ..self.clone()
+        }
+    }
+
+    /// Create a signed assertion using the certificate's private key
+    pub fn create_assertion(&self, signing_certificate: &Certificate) -> Result<String, String> {
+        let pem = get_private_key_pem(signing_certificate)?;
+        let key = match self.algorithm {
+            JwtSigningAlgorithm::Es256 | JwtSigningAlgorithm::Es384 => {
+                EncodingKey::from_ec_pem(&pem)
+            }
+            _ => EncodingKey::from_rsa_pem(&pem),
+        }
+        .map_err(|err| format!("Unable to use signing certificate's private key: {err}"))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = AssertionClaims {
+            iss: &self.issuer,
+            sub: &self.subject,
+            aud: if self.assertion_audience.is_empty() {
+                &self.access_token_url
+            } else {
+                &self.assertion_audience
+            },
+            exp: now + self.assertion_lifetime,
+            iat: now,
+            jti: Uuid::new_v4().to_string(),
+        };
+        jsonwebtoken::encode(&Header::new(self.algorithm.algorithm()), &claims, &key)
+            .map_err(|err| format!("Unable to sign assertion: {err}"))
+    }
+
+    /// Build the token request for this grant, signing a new assertion
+    pub fn token_request(
+        &self,
+        client: reqwest::Client,
+        signing_certificate: Option<&Certificate>,
+    ) -> Result<TokenRequest, String> {
+        let signing_certificate =
+            signing_certificate.ok_or("Signing certificate is not available")?;
+        let mut form = vec![
+            ("grant_type".to_string(), JWT_BEARER_GRANT_TYPE.to_string()),
+            (
+                "assertion".to_string(),
+                self.create_assertion(signing_certificate)?,
+            ),
+        ];
+        if !self.client_id.is_empty() {
+            form.push(("client_id".to_string(), self.client_id.clone()));
+        }
+        if !self.scope.is_empty() {
+            form.push(("scope".to_string(), self.scope.clone()));
+        }
+        Ok(TokenRequest {
+            client,
+            url: self.access_token_url.clone(),
+            form,
+            basic_auth: None,
+        })
+    }
+}
+
+/// Request to a token endpoint that does not require user interaction
+pub struct TokenRequest {
+    client: reqwest::Client,
+    url: String,
+    form: Vec<(String, String)>,
+    basic_auth: Option<(String, String)>,
+}
+
+impl TokenRequest {
+    /// Build a request exchanging a refresh token for a new access token (RFC 6749 section 6)
+    pub fn refresh(
+        client: reqwest::Client,
+        url: &str,
+        client_id: &str,
+        client_secret: &str,
+        refresh_token: &str,
+    ) -> Self {
+        let mut form = vec![
+            ("grant_type".to_string(), "refresh_token".to_string()),
+            ("refresh_token".to_string(), refresh_token.to_string()),
+            ("client_id".to_string(), client_id.to_string()),
+        ];
+        if !client_secret.is_empty() {
+            form.push(("client_secret".to_string(), client_secret.to_string()));
+        }
+        TokenRequest {
+            client,
+            url: url.to_string(),
+            form,
+            basic_auth: None,
+        }
+    }
+
+    /// Send the request, returning token info suitable for caching
+    pub async fn send(&self) -> Result<CachedTokenInfo, String> {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&self.form);
+        if let Some((client_id, client_secret)) = &self.basic_auth {
+            builder = builder.basic_auth(client_id, Some(client_secret));
+        }
+        let response = builder
+            .send()
+            .await
+            .map_err(|err| format!("Unable to request access token: {err}"))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|err| err.to_string())?;
+        if status.is_success() {
+            parse_token_response(&body)
+        } else {
+            Err(describe_error_response(status, &body))
+        }
+    }
+}
+
+/// Return the PEM-encoded private key of a certificate
+fn get_private_key_pem(certificate: &Certificate) -> Result<Vec<u8>, String> {
+    match certificate {
+        Certificate::Cipher(_) => Err("Signing certificate is encrypted".to_string()),
+        Certificate::Plain(plain) => match plain.as_ref() {
+            CertificatePlain::PKCS8PEM { key, .. } => Ok(key.clone()),
+            CertificatePlain::PKCS12 { pfx, password, .. } => {
+                let keystore = KeyStore::from_pkcs12(
+                    pfx,
+                    password.as_deref().unwrap_or_default(),
+                    Pkcs12ImportPolicy::Relaxed,
+                )
+                .map_err(|err| format!("Unable to open PKCS12 signing certificate: {err}"))?;
+                let der = keystore
+                    .entries()
+                    .find_map(|(_, entry)| match entry {
+                        KeyStoreEntry::PrivateKeyChain(chain) => Some(chain.key().as_der()),
+                        _ => None,
+                    })
+                    .ok_or("PKCS12 signing certificate does not include a private key")?;
+                Ok(encode_pem("PRIVATE KEY", der))
+            }
+            CertificatePlain::PEM { pem, .. } => find_private_key_pem(pem)
+                .ok_or_else(|| "PEM certificate does not include a private key".to_string()),
+        },
+    }
+}
+
+/// Return the first private key block (PKCS8, RSA or EC) embedded in PEM content
+fn find_private_key_pem(pem: &[u8]) -> Option<Vec<u8>> {
+    let pem = std::str::from_utf8(pem).ok()?;
+    let begin = pem.find("-----BEGIN ")?;
+    let mut remaining = &pem[begin..];
+    loop {
+        let label_end = remaining[11..].find("-----")? + 11;
+        let label = &remaining[11..label_end];
+        let footer = format!("-----END {label}-----");
+        let end = remaining.find(&footer)? + footer.len();
+        if label.ends_with("PRIVATE KEY") && !label.contains("ENCRYPTED") {
+            return Some(format!("{}\n", &remaining[..end]).into_bytes());
+        }
+        remaining = &remaining[end..];
+        remaining = &remaining[remaining.find("-----BEGIN ")?..];
+    }
+}
+
+/// Return the PEM encoded X.509 certificate, used to verify signatures
+pub fn get_certificate_pem(certificate: &Certificate) -> Result<Vec<u8>, String> {
+    match certificate {
+        Certificate::Cipher(_) => Err("Certificate is encrypted".to_string()),
+        Certificate::Plain(plain) => match plain.as_ref() {
+            CertificatePlain::PKCS8PEM { pem, .. } | CertificatePlain::PEM { pem, .. } => {
+                Ok(pem.clone())
+            }
+            CertificatePlain::PKCS12 { pfx, password, .. } => {
+                let keystore = KeyStore::from_pkcs12(
+                    pfx,
+                    password.as_deref().unwrap_or_default(),
+                    Pkcs12ImportPolicy::Relaxed,
+                )
+                .map_err(|err| format!("Unable to open PKCS12 certificate: {err}"))?;
+                let der = keystore
+                    .entries()
+                    .find_map(|(_, entry)| match entry {
+                        KeyStoreEntry::PrivateKeyChain(chain) => {
+                            chain.certs().first().map(|cert| cert.as_der())
+                        }
+                        KeyStoreEntry::Certificate(cert) => Some(cert.as_der()),
+                        _ => None,
+                    })
+                    .ok_or("PKCS12 file does not include a certificate")?;
+                Ok(encode_pem("CERTIFICATE", der))
+            }
+        },
+    }
+}
+
+fn encode_pem(label: &str, der: &[u8]) -> Vec<u8> {
+    let encoded = BASE64_STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {label}-----\n"));
+    pem.into_bytes()
+}
+
+/// Device authorization response (RFC 8628 section 3.2)
+#[derive(Deserialize, Clone)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    pub interval: Option<u64>,
+}
+
+/// Information the user needs to complete device authorization, sent to the UI
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAuthorizationPrompt {
+    pub authorization_id: String,
+    /// Used to cancel the flow while polling
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+}
+
+impl DeviceAuthorizationPrompt {
+    pub fn new(authorization_id: &str, device: &DeviceAuthorization) -> Self {
+        DeviceAuthorizationPrompt {
+            authorization_id: authorization_id.to_string(),
+            device_code: device.device_code.clone(),
+            user_code: device.user_code.clone(),
+            verification_uri: device.verification_uri.clone(),
+            verification_uri_complete: device.verification_uri_complete.clone(),
+            expires_in: device.expires_in,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+enum PollOutcome {
+    Token(CachedTokenInfo),
+    Pending,
+    SlowDown,
+    Failed(String),
+}
+
+fn parse_poll_response(status: reqwest::StatusCode, body: &str) -> PollOutcome {
+    if status.is_success() {
+        return match parse_token_response(body) {
+            Ok(token) => PollOutcome::Token(token),
+            Err(err) => PollOutcome::Failed(err),
+        };
+    }
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(error) if error.error == "authorization_pending" => PollOutcome::Pending,
+        Ok(error) if error.error == "slow_down" => PollOutcome::SlowDown,
+        _ => PollOutcome::Failed(describe_error_response(status, body)),
+    }
+}
+
+/// Convert a successful token endpoint response into cached token info
+pub fn parse_token_response(body: &str) -> Result<CachedTokenInfo, String> {
+    let token = serde_json::from_str::<TokenResponse>(body)
+        .map_err(|err| format!("Invalid token response: {err}"))?;
+    Ok(CachedTokenInfo {
+        access_token: token.access_token,
+        refresh_token: token.refresh_token,
+        expiration: token.expires_in.map(|expires_in| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                .add(expires_in)
+        }),
+    })
+}
+
+/// Describe an unsuccessful OAuth2 response, using the standard error fields if present
+pub fn describe_error_response(status: reqwest::StatusCode, body: &str) -> String {
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(ErrorResponse {
+            error,
+            error_description: Some(description),
+        }) => format!("{error}: {description}"),
+        Ok(ErrorResponse { error, .. }) => error,
+        Err(_) => format!("{status} {body}").trim().to_string(),
+    }
+}
+
+/// Build an HTTP client for token requests, using the certificate and proxy if specified
+pub fn build_client(
+    certificate: Option<&Certificate>,
+    proxy: Option<&Proxy>,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::ClientBuilder::new().redirect(reqwest::redirect::Policy::none());
+    if let Some(certificate) = certificate {
+        builder = certificate
+            .append_to_builder(builder)
+            .map_err(|err| format!("Unable to assign OAuth certificate: {err}"))?;
+    }
+    if let Some(proxy) = proxy {
+        builder = proxy
+            .append_to_builder(builder)
+            .map_err(|err| format!("Unable to assign OAuth proxy: {err}"))?;
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn handles_poll_responses() {
+        assert!(matches!(
+            parse_poll_response(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"authorization_pending"}"#
+            ),
+            PollOutcome::Pending
+        ));
+        assert!(matches!(
+            parse_poll_response(StatusCode::BAD_REQUEST, r#"{"error":"slow_down"}"#),
+            PollOutcome::SlowDown
+        ));
+        assert!(matches!(
+            parse_poll_response(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"access_denied","error_description":"User declined"}"#
+            ),
+            PollOutcome::Failed(err) if err == "access_denied: User declined"
+        ));
+        match parse_poll_response(
+            StatusCode::OK,
+            r#"{"access_token":"abc","token_type":"Bearer","refresh_token":"def","expires_in":60}"#,
+        ) {
+            PollOutcome::Token(token) => {
+                assert_eq!(token.access_token, "abc");
+                assert_eq!(token.refresh_token.as_deref(), Some("def"));
+                assert!(token.expiration.is_some());
+            }
+            _ => panic!("expected token"),
+        }
+    }
+
+    #[test]
+    fn signs_assertions() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let certificate = Certificate::Plain(Box::new(CertificatePlain::PKCS8PEM {
+            id: "cert".to_string(),
+            name: "Signing".to_string(),
+            pem: Vec::new(),
+            key: key.serialize_pem().into_bytes(),
+            validation_state: Default::default(),
+            validation_warnings: None,
+            validation_errors: None,
+        }));
+        let parameters = OAuth2JwtBearerParameters {
+            access_token_url: "https://example.com/token".to_string(),
+            algorithm: JwtSigningAlgorithm::Es256,
+            issuer: "client".to_string(),
+            subject: "user".to_string(),
+            ..Default::default()
+        };
+        let assertion = parameters.create_assertion(&certificate).unwrap();
+        let parts = assertion.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let claims = serde_json::from_slice::<serde_json::Value>(
+            &base64::prelude::BASE64_URL_SAFE_NO_PAD
+                .decode(parts[1])
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claims["iss"], "client");
+        assert_eq!(claims["sub"], "user");
+        assert_eq!(claims["aud"], "https://example.com/token");
+        assert_eq!(
+            claims["exp"].as_u64().unwrap() - claims["iat"].as_u64().unwrap(),
+            DEFAULT_ASSERTION_LIFETIME
+        );
+
+        // RSA algorithms cannot use an EC key
+        let parameters = OAuth2JwtBearerParameters {
+            algorithm: JwtSigningAlgorithm::Rs256,
+            ..parameters
+        };
+        assert!(parameters.create_assertion(&certificate).is_err());
+    }
+
+    #[test]
+    fn signs_assertions_with_pem_embedded_keys() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["client".to_string()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+        let pem_certificate = |pem: String| {
+            Certificate::Plain(Box::new(CertificatePlain::PEM {
+                id: "cert".to_string(),
+                name: "Signing".to_string(),
+                pem: pem.into_bytes(),
+                validation_state: Default::default(),
+                validation_warnings: None,
+                validation_errors: None,
+            }))
+        };
+        let parameters = OAuth2JwtBearerParameters {
+            access_token_url: "https://{{host}}/token".to_string(),
+            algorithm: JwtSigningAlgorithm::Es256,
+            issuer: "{{client}}".to_string(),
+            subject: "user".to_string(),
+            ..Default::default()
+        }
+        .substitute(&HashMap::from([
+            ("{{host}}".to_string(), "example.com".to_string()),
+            ("{{client}}".to_string(), "client".to_string()),
+        ]));
+
+        let certificate = pem_certificate(format!("{}{}", cert.pem(), key.serialize_pem()));
+        let assertion = parameters.create_assertion(&certificate).unwrap();
+        let claims = serde_json::from_slice::<serde_json::Value>(
+            &base64::prelude::BASE64_URL_SAFE_NO_PAD
+                .decode(assertion.split('.').nth(1).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claims["iss"], "client");
+        assert_eq!(claims["aud"], "https://example.com/token");
+
+        assert!(
+            parameters
+                .create_assertion(&pem_certificate(cert.pem()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_device_authorization() {
+        let device = serde_json::from_str::<DeviceAuthorization>(
+            r#"{"device_code":"d","user_code":"WDJB-MJHT","verification_url":"https://example.com/device","expires_in":1800}"#,
+        )
+        .unwrap();
+        assert_eq!(device.verification_uri, "https://example.com/device");
+        assert_eq!(device.interval, None);
+    }
+}
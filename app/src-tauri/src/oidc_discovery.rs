@@ -0,0 +1,276 @@
+//! OpenID Connect provider discovery (OpenID Connect Discovery 1.0 section 4), used to
+//! populate OAuth2 authorization endpoints from an issuer URL
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    oauth2_grants::{DEVICE_CODE_GRANT_TYPE, JWT_BEARER_GRANT_TYPE},
+    updates::{AuthorizationUpdate, AuthorizationUpdateType},
+};
+
+const DISCOVERY_PATH: &str = ".well-known/openid-configuration";
+
+/// Provider metadata used by Apicize, other values are ignored
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcConfiguration {
+    pub issuer: String,
+    #[serde(
+        alias = "authorization_endpoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub authorization_endpoint: Option<String>,
+    #[serde(
+        alias = "token_endpoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub token_endpoint: Option<String>,
+    #[serde(
+        alias = "device_authorization_endpoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub device_authorization_endpoint: Option<String>,
+    #[serde(
+        alias = "scopes_supported",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scopes_supported: Option<Vec<String>>,
+    #[serde(
+        alias = "grant_types_supported",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub grant_types_supported: Option<Vec<String>>,
+    #[serde(
+        alias = "code_challenge_methods_supported",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+/// Discovered configuration, and the authorization update applied from it
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcDiscoveryResponse {
+    pub configuration: OidcConfiguration,
+    pub authorization: AuthorizationUpdate,
+}
+
+/// Return the discovery document URL for an issuer, preserving any issuer path
+pub fn get_discovery_url(issuer: &str) -> Result<Url, String> {
+    let trimmed = issuer.trim();
+    let is_discovery_url = trimmed.contains(DISCOVERY_PATH);
+    let mut url = if is_discovery_url {
+        Url::parse(trimmed)
+    } else {
+        Url::parse(&format!("{}/", trimmed.trim_end_matches('/')))
+    }
+    .map_err(|err| format!("Issuer URL is invalid: {err}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Issuer URL scheme \"{}\" is invalid", url.scheme()));
+    }
+    if is_discovery_url {
+        return Ok(url);
+    }
+    url.set_query(None);
+    url.set_fragment(None);
+    url.join(DISCOVERY_PATH)
+        .map_err(|err| format!("Issuer URL is invalid: {err}"))
+}
+
+impl OidcConfiguration {
+    /// Retrieve the provider's configuration
+    pub async fn discover(client: &reqwest::Client, issuer: &str) -> Result<Self, String> {
+        let url = get_discovery_url(issuer)?;
+        let response = client
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|err| format!("Unable to retrieve {url}: {err}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Unable to retrieve {url}: {status}"));
+        }
+        let body = response.text().await.map_err(|err| err.to_string())?;
+        serde_json::from_str::<OidcConfiguration>(&body)
+            .map_err(|err| format!("Invalid OpenID configuration: {err}"))
+    }
+
+    /// Return an update populating endpoints for the specified authorization type
+    pub fn create_update(
+        &self,
+        issuer: &str,
+        authorization_id: &str,
+        auth_type: &AuthorizationUpdateType,
+        scope: &str,
+    ) -> AuthorizationUpdate {
+        let mut update = AuthorizationUpdate::new(authorization_id);
+        update.access_token_url = self.token_endpoint.clone();
+        match auth_type {
+            AuthorizationUpdateType::OAuth2Pkce => {
+                update.authorize_url = self.authorization_endpoint.clone();
+            }
+            AuthorizationUpdateType::OAuth2Device => {
+                update.device_authorization_url = self.device_authorization_endpoint.clone();
+            }
+            _ => {}
+        }
+        update.validation_warnings = Some(self.get_warnings(issuer, auth_type, scope));
+        update
+    }
+
+    /// Return warnings if the provider does not advertise support for the authorization
+    pub fn get_warnings(
+        &self,
+        issuer: &str,
+        auth_type: &AuthorizationUpdateType,
+        scope: &str,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.issuer.trim_end_matches('/') != issuer.trim().trim_end_matches('/') {
+            warnings.push(format!(
+                "Provider issuer \"{}\" does not match \"{}\"",
+                self.issuer,
+                issuer.trim()
+            ));
+        }
+        if self.token_endpoint.is_none() {
+            warnings.push("Provider does not publish a token endpoint".to_string());
+        }
+
+        // Per RFC 8414, providers that omit grant types support authorization code and implicit
+        let (grant_type, description) = match auth_type {
+            AuthorizationUpdateType::OAuth2Client => ("client_credentials", "client credentials"),
+            AuthorizationUpdateType::OAuth2Pkce => ("authorization_code", "authorization code"),
+            AuthorizationUpdateType::OAuth2Password => ("password", "password"),
+            AuthorizationUpdateType::OAuth2JwtBearer => (JWT_BEARER_GRANT_TYPE, "JWT bearer"),
+            AuthorizationUpdateType::OAuth2Device => (DEVICE_CODE_GRANT_TYPE, "device code"),
+            _ => return warnings,
+        };
+        let grant_supported = match &self.grant_types_supported {
+            Some(grant_types) => grant_types.iter().any(|g| g == grant_type),
+            None => grant_type == "authorization_code",
+        };
+        if !grant_supported {
+            warnings.push(format!(
+                "Provider does not advertise support for the {description} grant"
+            ));
+        }
+
+        match auth_type {
+            AuthorizationUpdateType::OAuth2Pkce => {
+                if self.authorization_endpoint.is_none() {
+                    warnings
+                        .push("Provider does not publish an authorization endpoint".to_string());
+                }
+                match &self.code_challenge_methods_supported {
+                    Some(methods) if methods.iter().any(|m| m == "S256") => {}
+                    Some(_) => warnings
+                        .push("Provider does not support S256 PKCE code challenges".to_string()),
+                    None => warnings.push("Provider does not advertise PKCE support".to_string()),
+                }
+            }
+            AuthorizationUpdateType::OAuth2Device
+                if self.device_authorization_endpoint.is_none() =>
+            {
+                warnings
+                    .push("Provider does not publish a device authorization endpoint".to_string());
+            }
+            _ => {}
+        }
+
+        if let Some(scopes_supported) = &self.scopes_supported {
+            let unsupported = scope
+                .split_whitespace()
+                .filter(|s| !s.starts_with("{{") && !scopes_supported.iter().any(|x| x == s))
+                .collect::<Vec<_>>();
+            if !unsupported.is_empty() {
+                warnings.push(format!(
+                    "Provider does not advertise scope(s): {}",
+                    unsupported.join(", ")
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configuration() -> OidcConfiguration {
+        serde_json::from_str(
+            r#"{
+                "issuer": "https://login.example.com/tenant",
+                "authorization_endpoint": "https://login.example.com/tenant/authorize",
+                "token_endpoint": "https://login.example.com/tenant/token",
+                "scopes_supported": ["openid", "profile"],
+                "code_challenge_methods_supported": ["plain"],
+                "response_types_supported": ["code"]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_discovery_urls() {
+        assert_eq!(
+            get_discovery_url("https://login.example.com/tenant/")
+                .unwrap()
+                .as_str(),
+            "https://login.example.com/tenant/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            get_discovery_url("https://login.example.com")
+                .unwrap()
+                .as_str(),
+            "https://login.example.com/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            get_discovery_url("https://login.example.com/.well-known/openid-configuration")
+                .unwrap()
+                .as_str(),
+            "https://login.example.com/.well-known/openid-configuration"
+        );
+        assert!(get_discovery_url("ftp://login.example.com").is_err());
+        assert!(get_discovery_url("file:///etc/.well-known/openid-configuration").is_err());
+    }
+
+    #[test]
+    fn warns_about_unsupported_flows() {
+        let config = configuration();
+        let issuer = "https://login.example.com/tenant/";
+
+        let update = config.create_update(
+            issuer,
+            "auth",
+            &AuthorizationUpdateType::OAuth2Pkce,
+            "openid email",
+        );
+        assert_eq!(
+            update.authorize_url.as_deref(),
+            Some("https://login.example.com/tenant/authorize")
+        );
+        assert_eq!(
+            update.validation_warnings.unwrap(),
+            vec![
+                "Provider does not support S256 PKCE code challenges",
+                "Provider does not advertise scope(s): email",
+            ]
+        );
+
+        let warnings = config.get_warnings(issuer, &AuthorizationUpdateType::OAuth2Client, "");
+        assert_eq!(
+            warnings,
+            vec!["Provider does not advertise support for the client credentials grant"]
+        );
+    }
+}
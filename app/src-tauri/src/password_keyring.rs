@@ -0,0 +1,95 @@
+//! Parameter store passwords remembered in the operating system keyring (Secret Service on
+//! Linux, Keychain on macOS and Credential Manager on Windows)
+
+use std::path::Path;
+
+use apicize_lib::ParameterStore;
+use keyring::Entry;
+
+const KEYRING_SERVICE: &str = "apicize";
+
+/// Return the keyring user name for the store, the vault is shared while private stores
+/// are remembered per workbook.  Returns None for private stores of unsaved workbooks
+fn get_keyring_user(store: ParameterStore, workbook_path: Option<&Path>) -> Option<String> {
+    match store {
+        ParameterStore::Vault => Some("vault".to_string()),
+        ParameterStore::Private => workbook_path.map(|path| {
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+            format!("private:{}", path.to_string_lossy())
+        }),
+    }
+}
+
+fn get_entry(store: ParameterStore, workbook_path: Option<&Path>) -> Result<Option<Entry>, String> {
+    match get_keyring_user(store, workbook_path) {
+        Some(user) => Entry::new(KEYRING_SERVICE, &user)
+            .map(Some)
+            .map_err(|err| format!("Unable to access keyring: {err}")),
+        None => Ok(None),
+    }
+}
+
+/// Return the remembered password for the store, if any
+pub fn get_password(
+    store: ParameterStore,
+    workbook_path: Option<&Path>,
+) -> Result<Option<String>, String> {
+    let Some(entry) = get_entry(store, workbook_path)? else {
+        return Ok(None);
+    };
+    match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(format!("Unable to retrieve password from keyring: {err}")),
+    }
+}
+
+/// Remember the password for the store, or forget it if there is no password
+pub fn set_password(
+    store: ParameterStore,
+    workbook_path: Option<&Path>,
+    password: Option<&str>,
+) -> Result<(), String> {
+    let Some(password) = password.filter(|p| !p.is_empty()) else {
+        return delete_password(store, workbook_path).map(|_| ());
+    };
+    match get_entry(store, workbook_path)? {
+        Some(entry) => entry
+            .set_password(password)
+            .map_err(|err| format!("Unable to save password to keyring: {err}")),
+        None => Ok(()),
+    }
+}
+
+/// Forget the remembered password for the store, returning true if there was one
+pub fn delete_password(
+    store: ParameterStore,
+    workbook_path: Option<&Path>,
+) -> Result<bool, String> {
+    let Some(entry) = get_entry(store, workbook_path)? else {
+        return Ok(false);
+    };
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(format!("Unable to delete password from keyring: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_keyring_users() {
+        assert_eq!(
+            get_keyring_user(ParameterStore::Vault, Some(Path::new("/tmp/a.apicize"))).as_deref(),
+            Some("vault")
+        );
+        assert_eq!(
+            get_keyring_user(ParameterStore::Private, Some(Path::new("/tmp/a.apicize"))).as_deref(),
+            Some("private:/tmp/a.apicize")
+        );
+        assert!(get_keyring_user(ParameterStore::Private, None).is_none());
+    }
+}
@@ -1,12 +1,11 @@
 //! PKCE support submodule
 //!
 
-use std::thread::JoinHandle;
+use std::{collections::HashMap, net::TcpListener, sync::Arc, thread::JoinHandle, time::Duration};
 
 use actix_web::{
     App, HttpRequest, HttpResponse, HttpServer, Result,
     dev::ServerHandle,
-    get,
     http::StatusCode,
     web::{self, Data, Query},
 };
@@ -23,6 +22,46 @@ pub struct PkceAuthParams {
     state: String,
 }
 
+/// Query parameters sent to the redirect URI, including errors (RFC 6749 section 4.1.2.1)
+#[derive(Deserialize)]
+struct PkceRedirectParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Error sent to the UI for a PKCE flow
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PkceFlowError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<String>,
+    error: String,
+}
+
+/// Options for starting a PKCE flow
+pub struct PkceFlowOptions {
+    /// Listener port, ignored if using an ephemeral port
+    pub port: u16,
+    /// Redirect URI path
+    pub redirect_path: String,
+    /// If true, start a listener on a free port for this flow
+    pub ephemeral_port: bool,
+    /// Seconds to wait before abandoning the flow
+    pub timeout: u64,
+}
+
+/// PKCE flow awaiting redirect, indexed by CSRF state
+struct PendingPkceFlow {
+    redirect_path: String,
+    port: u16,
+    /// Listener started for this flow only
+    stop: Option<Data<StopHandle>>,
+}
+
+type PendingPkceFlows = Arc<parking_lot::Mutex<HashMap<String, PendingPkceFlow>>>;
+
 // #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 // /// Anticipated response from PKCE redirect
 // struct OAuth2PkceResponse {
@@ -63,11 +102,49 @@ pub struct OAuth2PkceService {
     port: Option<u16>,
     stop: Option<web::Data<StopHandle>>,
     server: Option<JoinHandle<Result<(), std::io::Error>>>,
+    flows: PendingPkceFlows,
 }
 
 #[derive(Clone)]
 struct OAuth2PkceServiceData {
     tauri: AppHandle,
+    flows: PendingPkceFlows,
+}
+
+/// Minimum number of seconds to wait for a PKCE flow to complete
+pub const MIN_PKCE_TIMEOUT: u64 = 10;
+
+/// Maximum number of seconds to wait for a PKCE flow to complete
+pub const MAX_PKCE_TIMEOUT: u64 = 3600;
+
+/// Return an error if the PKCE timeout is out of range
+pub fn validate_pkce_timeout(timeout: u64) -> Result<(), String> {
+    if (MIN_PKCE_TIMEOUT..=MAX_PKCE_TIMEOUT).contains(&timeout) {
+        Ok(())
+    } else {
+        Err(format!(
+            "PKCE timeout must be between {MIN_PKCE_TIMEOUT} and {MAX_PKCE_TIMEOUT} seconds"
+        ))
+    }
+}
+
+/// Return the redirect path with a leading slash, or "/" if blank
+pub fn normalize_redirect_path(path: &str) -> Result<String, String> {
+    let path = path.trim();
+    if path.is_empty() || path == "/" {
+        return Ok("/".to_string());
+    }
+    if !path
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/-._~%".contains(c))
+    {
+        return Err(format!("PKCE redirect path \"{path}\" is invalid"));
+    }
+    Ok(if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    })
 }
 
 impl OAuth2PkceService {
@@ -77,6 +154,7 @@ impl OAuth2PkceService {
             stop: None,
             server: None,
             port: None,
+            flows: Default::default(),
         }
     }
 
@@ -106,32 +184,82 @@ impl OAuth2PkceService {
         if let Some(s) = server
             && let Err(err) = s.join()
         {
-            self.tauri
-                .emit("oauth2-pkce-error", format!("{err:?}"))
-                .unwrap();
+            emit_pkce_error(&self.tauri, None, format!("{err:?}"));
         }
 
-        let app_handle = self.tauri.clone();
-        let stop_handle = web::Data::new(StopHandle::default());
-        let cloned_stop_handle = stop_handle.clone();
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                emit_pkce_error(
+                    &self.tauri,
+                    None,
+                    format!("Unable to start server at http://127.0.0.1:{port}, {err}"),
+                );
+                return;
+            }
+        };
+        self.tauri
+            .emit(
+                "oauth2-pkce-success",
+                format!("Server started at http://127.0.0.1:{port}").to_string(),
+            )
+            .unwrap();
 
-        self.server = Some(std::thread::spawn(move || {
-            init_pkce_server(app_handle, port, cloned_stop_handle)
-        }));
+        let (server, stop_handle) = self.start_server(listener, None);
+        self.server = Some(server);
         self.stop = Some(stop_handle);
     }
 
-    /// Generate
+    fn start_server(
+        &self,
+        listener: TcpListener,
+        workers: Option<usize>,
+    ) -> (JoinHandle<Result<(), std::io::Error>>, Data<StopHandle>) {
+        let data = OAuth2PkceServiceData {
+            tauri: self.tauri.clone(),
+            flows: self.flows.clone(),
+        };
+        let stop_handle = web::Data::new(StopHandle::default());
+        let cloned_stop_handle = stop_handle.clone();
+        let server = std::thread::spawn(move || {
+            init_pkce_server(data, listener, workers, cloned_stop_handle)
+        });
+        (server, stop_handle)
+    }
+
+    /// Generate authorization info for a new flow, which will be abandoned if the
+    /// redirect is not received before the timeout
     pub fn generate_authorization_info(
         &self,
         auth: OAuth2PkceInfo,
-        port: u16,
+        options: PkceFlowOptions,
     ) -> Result<OAuth2PkceRequest, String> {
-        if port == 0 {
+        validate_pkce_timeout(options.timeout)?;
+        let redirect_path = normalize_redirect_path(&options.redirect_path)?;
+
+        let (port, stop) = if options.ephemeral_port {
+            let listener = TcpListener::bind(("127.0.0.1", 0))
+                .map_err(|err| format!("Unable to start PKCE listener: {err}"))?;
+            let port = listener
+                .local_addr()
+                .map_err(|err| format!("Unable to start PKCE listener: {err}"))?
+                .port();
+            let (_, stop) = self.start_server(listener, Some(1));
+            (port, Some(stop))
+        } else if options.port == 0 {
             return Err("PKCE port is set to 0 in settings, disabling PKCE".to_string());
-        }
-        let redirect_uri = format!("http://localhost:{port}");
-        match generate_authorization(
+        } else {
+            (options.port, None)
+        };
+
+        // Retain the historical redirect URI (without trailing slash) if no path is set
+        let redirect_uri = if redirect_path == "/" {
+            format!("http://localhost:{port}")
+        } else {
+            format!("http://localhost:{port}{redirect_path}")
+        };
+
+        let request = match generate_authorization(
             auth.authorize_url.as_str(),
             redirect_uri.as_str(),
             auth.client_id.as_str(),
@@ -139,13 +267,60 @@ impl OAuth2PkceService {
             auth.scope,
             auth.audience,
         ) {
-            Ok((url, csrf_token, verifier)) => Ok(OAuth2PkceRequest {
+            Ok((url, csrf_token, verifier)) => OAuth2PkceRequest {
                 url,
                 csrf_token: csrf_token.into_secret(),
                 verifier,
                 redirect_url: redirect_uri,
-            }),
-            Err(err) => Err(format!("{err:?}")),
+            },
+            Err(err) => {
+                if let Some(stop) = stop {
+                    stop.stop(false);
+                }
+                return Err(format!("{err:?}"));
+            }
+        };
+
+        let state = request.csrf_token.clone();
+        self.flows.lock().insert(
+            state.clone(),
+            PendingPkceFlow {
+                redirect_path,
+                port,
+                stop,
+            },
+        );
+
+        // Abandon the flow if the redirect is not received in time
+        let flows = self.flows.clone();
+        let tauri = self.tauri.clone();
+        let timeout = Duration::from_secs(options.timeout);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            let flow = flows.lock().remove(&state);
+            if let Some(flow) = flow {
+                flow.close();
+                close_pkce_windows(&tauri);
+                emit_pkce_error(
+                    &tauri,
+                    Some(state),
+                    "PKCE authorization timed out".to_string(),
+                );
+            }
+        });
+
+        Ok(request)
+    }
+
+    /// Abandon an in-flight PKCE flow, returning true if it was pending
+    pub fn cancel_flow(&self, state: &str) -> bool {
+        let flow = self.flows.lock().remove(state);
+        match flow {
+            Some(flow) => {
+                flow.close();
+                true
+            }
+            None => false,
         }
     }
 
@@ -175,45 +350,28 @@ impl OAuth2PkceService {
 
 #[actix_web::main]
 async fn init_pkce_server(
-    tauri: AppHandle,
-    port: u16,
+    data: OAuth2PkceServiceData,
+    listener: TcpListener,
+    workers: Option<usize>,
     stop_handle: Data<StopHandle>,
 ) -> Result<(), std::io::Error> {
-    let app_data = web::Data::new(OAuth2PkceServiceData {
-        tauri: tauri.clone(),
-    });
+    let port = listener.local_addr()?.port();
+    let app_data = web::Data::new(data);
 
-    let http_server = HttpServer::new(move || {
+    // Redirects are matched against pending flows, so accept any path here
+    let mut server = HttpServer::new(move || {
         App::new()
             .app_data(app_data.clone())
-            .service(process_pkce_response)
-    })
-    .bind(("127.0.0.1", port));
-
-    match http_server {
-        Ok(server) => {
-            tauri
-                .emit(
-                    "oauth2-pkce-success",
-                    format!("Server started at http://127.0.0.1:{port}").to_string(),
-                )
-                .unwrap();
-            println!("*** Started PKCE listener at 127.0.0.1:{port}");
-            let running_server = server.run();
-            stop_handle.register(running_server.handle());
-            running_server.await
-        }
-        Err(err) => {
-            tauri
-                .emit(
-                    "oauth2-pkce-error",
-                    format!("Unable to start server at http://127.0.0.1:{port}, {err}"),
-                )
-                .unwrap();
-            eprintln!("Unable to start server at http://127.0.0.1:{port}, {err}");
-            Err(err)
-        }
+            .default_service(web::get().to(process_pkce_response))
+    });
+    if let Some(workers) = workers {
+        server = server.workers(workers);
     }
+
+    println!("*** Started PKCE listener at 127.0.0.1:{port}");
+    let running_server = server.listen(listener)?.run();
+    stop_handle.register(running_server.handle());
+    running_server.await
 }
 
 fn close_pkce_windows(app: &AppHandle) {
@@ -224,40 +382,137 @@ fn close_pkce_windows(app: &AppHandle) {
     }
 }
 
-#[get("/")]
+impl PendingPkceFlow {
+    /// Stop the flow's listener, if it has its own
+    fn close(self) {
+        if let Some(stop) = self.stop {
+            stop.stop(false);
+        }
+    }
+}
+
+/// Send an error to the UI, including the CSRF state if the error is for a specific flow
+fn emit_pkce_error(tauri: &AppHandle, state: Option<String>, error: String) {
+    eprintln!("PKCE error: {error}");
+    tauri
+        .emit("oauth2-pkce-error", PkceFlowError { state, error })
+        .unwrap();
+}
+
 async fn process_pkce_response(
     (request, data): (HttpRequest, Data<OAuth2PkceServiceData>),
 ) -> Result<HttpResponse, actix_web::Error> {
+    // Get query string....
+    // ?code=0b32dbe7-30f7-433b-9f81-05e8851627ab&state=aaabbbccc
+    let Ok(Query(params)) = Query::<PkceRedirectParams>::from_query(request.query_string()) else {
+        return Ok(HttpResponse::new(StatusCode::BAD_REQUEST));
+    };
+
+    // Only accept redirects for in-flight flows, on the flow's port and path
+    let Some(state) = params.state else {
+        return Ok(HttpResponse::new(StatusCode::BAD_REQUEST));
+    };
+    let port = request.app_config().local_addr().port();
+    let flow = {
+        let mut flows = data.flows.lock();
+        match flows.get(&state) {
+            Some(flow) if flow.port == port && flow.redirect_path == request.path() => {
+                flows.remove(&state)
+            }
+            _ => None,
+        }
+    };
+    let Some(flow) = flow else {
+        emit_pkce_error(
+            &data.tauri,
+            None,
+            "PKCE redirect received with unknown or expired state".to_string(),
+        );
+        return Ok(HttpResponse::new(StatusCode::BAD_REQUEST));
+    };
+
+    flow.close();
     close_pkce_windows(data.tauri.app_handle());
-    if let Ok(params) = Query::<PkceAuthParams>::from_query(request.query_string()) {
-        // Get query string....
-        // ?code=0b32dbe7-30f7-433b-9f81-05e8851627ab&state=aaabbbccc
-        println!("PKCE response: {:?}", params.0);
-        data.tauri
-            .emit("oauth2-pkce-auth-response", params.0)
-            .unwrap();
-        Ok(HttpResponse::new(StatusCode::OK))
-    } else {
-        Ok(HttpResponse::new(StatusCode::BAD_REQUEST))
+
+    if let Some(error) = params.error {
+        let error = match params.error_description {
+            Some(description) => format!("{error}: {description}"),
+            None => error,
+        };
+        emit_pkce_error(&data.tauri, Some(state), error);
+        return Ok(HttpResponse::new(StatusCode::OK));
+    }
+
+    match params.code {
+        Some(code) => {
+            let params = PkceAuthParams { code, state };
+            println!("PKCE response: {params:?}");
+            data.tauri
+                .emit("oauth2-pkce-auth-response", params)
+                .unwrap();
+            Ok(HttpResponse::new(StatusCode::OK))
+        }
+        None => {
+            emit_pkce_error(
+                &data.tauri,
+                Some(state),
+                "PKCE redirect did not include a code".to_string(),
+            );
+            Ok(HttpResponse::new(StatusCode::BAD_REQUEST))
+        }
     }
 }
 
 #[derive(Default)]
 struct StopHandle {
     inner: parking_lot::Mutex<Option<ServerHandle>>,
+    stopped: parking_lot::Mutex<bool>,
 }
 
 impl StopHandle {
-    /// Sets the server handle to stop.
+    /// Sets the server handle to stop, stopping immediately if already requested
     pub(crate) fn register(&self, handle: ServerHandle) {
+        let stopped = self.stopped.lock();
+        if *stopped {
+            #[allow(clippy::let_underscore_future)]
+            let _ = handle.stop(false);
+        }
         *self.inner.lock() = Some(handle);
     }
 
     /// Sends stop signal through contained server handle.
     pub(crate) fn stop(&self, graceful: bool) {
+        let mut stopped = self.stopped.lock();
+        *stopped = true;
         if let Some(h) = self.inner.lock().as_ref() {
             #[allow(clippy::let_underscore_future)]
             let _ = h.stop(graceful);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_redirect_paths() {
+        assert_eq!(normalize_redirect_path("").unwrap(), "/");
+        assert_eq!(normalize_redirect_path(" / ").unwrap(), "/");
+        assert_eq!(normalize_redirect_path("callback").unwrap(), "/callback");
+        assert_eq!(
+            normalize_redirect_path("/oauth2/callback").unwrap(),
+            "/oauth2/callback"
+        );
+        assert!(normalize_redirect_path("/callback?x=1").is_err());
+    }
+
+    #[test]
+    fn validates_timeouts() {
+        assert!(validate_pkce_timeout(300).is_ok());
+        assert!(validate_pkce_timeout(MIN_PKCE_TIMEOUT).is_ok());
+        assert!(validate_pkce_timeout(MAX_PKCE_TIMEOUT).is_ok());
+        assert!(validate_pkce_timeout(0).is_err());
+        assert!(validate_pkce_timeout(MAX_PKCE_TIMEOUT + 1).is_err());
+    }
+}
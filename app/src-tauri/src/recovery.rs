@@ -0,0 +1,416 @@
+//! Snapshots of workspaces with unsaved changes, written periodically to the settings
+//! directory so that they can be restored if Apicize exits without saving
+
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use apicize_lib::{
+    Authorization, Certificate, IndexedEntities, OpenWorkbookOptions, Parameters, PersistedIndex,
+    Proxy, SaveWorkspaceParameters, Scenario, Workspace, open_data_file, save_data_file,
+};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApicizeAppError,
+    extensions::WorkbookExtensions,
+    settings::ApicizeSettings,
+    workspaces::{DataSetContent, WorkspaceInfo},
+};
+
+const WORKBOOK_FILE_NAME: &str = "workbook.apicize";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+const VAULT_FILE_NAME: &str = "vault.json";
+const VAULT_EXTENSIONS_FILE_NAME: &str = "vault.apicize-ext";
+
+/// Snapshot information stored alongside the snapshot's workbook
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSnapshot {
+    /// Workbook file name, empty if the workspace has never been saved
+    file_name: String,
+    /// Display name
+    display_name: String,
+    /// Time of snapshot (seconds since Unix epoch)
+    saved_at: u64,
+    /// Data set content not yet saved to files
+    #[serde(default)]
+    data_set_content: FxHashMap<String, DataSetContent>,
+    /// True if private parameters were included, false if they were locked
+    #[serde(default)]
+    includes_private: bool,
+    /// True if vault parameters were included, false if they were locked
+    #[serde(default)]
+    includes_vault: bool,
+}
+
+/// Copy of a workspace's unsaved state, taken while the workspaces are locked so that it
+/// can be written after the lock is released
+pub struct WorkspaceSnapshot {
+    workspace: Workspace,
+    extensions: WorkbookExtensions,
+    file_name: String,
+    display_name: String,
+    data_set_content: FxHashMap<String, DataSetContent>,
+}
+
+impl From<&WorkspaceInfo> for WorkspaceSnapshot {
+    fn from(info: &WorkspaceInfo) -> Self {
+        WorkspaceSnapshot {
+            workspace: info.workspace.clone(),
+            extensions: info.extensions.clone(),
+            file_name: info.file_name.clone(),
+            display_name: info.display_name.clone(),
+            data_set_content: info
+                .data_set_content
+                .iter()
+                .filter(|(_, content)| content.dirty)
+                .map(|(id, content)| (id.clone(), content.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Summary of a snapshot available for recovery
+#[derive(Clone)]
+pub struct RecoverySnapshot {
+    /// Snapshot ID (the workspace ID at the time of the snapshot)
+    pub id: String,
+    /// Workbook file name, empty if the workspace has never been saved
+    pub file_name: String,
+    /// Display name
+    pub display_name: String,
+    /// Time of snapshot (seconds since Unix epoch)
+    pub saved_at: u64,
+}
+
+/// Workspace restored from a snapshot
+pub struct RestoredSnapshot {
+    pub workspace: Workspace,
+    pub extensions: WorkbookExtensions,
+    pub file_name: String,
+    pub data_set_content: FxHashMap<String, DataSetContent>,
+}
+
+/// Return the directory snapshots are stored in
+fn get_recovery_directory() -> PathBuf {
+    ApicizeSettings::get_settings_directory().join("recovery")
+}
+
+fn get_snapshot_directory(id: &str) -> PathBuf {
+    get_recovery_directory().join(id)
+}
+
+/// Write a snapshot of the workspace's workbook, extensions and unsaved data set content,
+/// along with private and vault parameters (encrypted with their passwords) if unlocked
+pub fn save_snapshot(
+    workspace_id: &str,
+    snapshot: &WorkspaceSnapshot,
+) -> Result<(), ApicizeAppError> {
+    save_snapshot_to(&get_snapshot_directory(workspace_id), snapshot)
+}
+
+fn save_snapshot_to(directory: &Path, snapshot: &WorkspaceSnapshot) -> Result<(), ApicizeAppError> {
+    fs::create_dir_all(directory)?;
+    let workspace = &snapshot.workspace;
+    let includes_private = !workspace.private_lock_status.is_locked();
+    let includes_vault = !workspace.vault_lock_status.is_locked();
+
+    let workbook_path = directory.join(WORKBOOK_FILE_NAME);
+    workspace.save(&SaveWorkspaceParameters {
+        workbook_path: Some(workbook_path.clone()),
+        include_workbook: true,
+        include_private: includes_private,
+        include_vault: false,
+    })?;
+    snapshot
+        .extensions
+        .save_workbook(&workbook_path, workspace)?;
+
+    // The vault is shared by workbooks, so a copy is kept with the snapshot
+    if includes_vault {
+        Parameters::new(
+            workspace.scenarios.get_vault(),
+            workspace.authorizations.get_vault(),
+            workspace.certificates.get_vault(),
+            workspace.proxies.get_vault(),
+        )
+        .save(
+            &directory.join(VAULT_FILE_NAME),
+            "Vault",
+            &workspace.vault_password,
+        )?;
+        snapshot
+            .extensions
+            .save_vault_to(&directory.join(VAULT_EXTENSIONS_FILE_NAME), workspace)?;
+    }
+
+    save_data_file(
+        &directory.join(SNAPSHOT_FILE_NAME),
+        &StoredSnapshot {
+            file_name: snapshot.file_name.clone(),
+            display_name: snapshot.display_name.clone(),
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            data_set_content: snapshot.data_set_content.clone(),
+            includes_private,
+            includes_vault,
+        },
+    )?;
+    Ok(())
+}
+
+/// Return snapshots available for recovery, most recent first
+pub fn list_snapshots() -> Vec<RecoverySnapshot> {
+    let Ok(entries) = fs::read_dir(get_recovery_directory()) else {
+        return vec![];
+    };
+
+    let mut snapshots = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            match open_data_file::<StoredSnapshot>(&entry.path().join(SNAPSHOT_FILE_NAME)) {
+                Ok(opened) => Some(RecoverySnapshot {
+                    id,
+                    file_name: opened.data.file_name,
+                    display_name: opened.data.display_name,
+                    saved_at: opened.data.saved_at,
+                }),
+                Err(err) => {
+                    eprintln!("Unable to read recovery snapshot {id}: {err}");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort_by_key(|snapshot| Reverse(snapshot.saved_at));
+    snapshots
+}
+
+/// Open the workspace stored in a snapshot, using the original workbook's private
+/// parameters and the vault if they were locked when the snapshot was taken
+pub fn restore_snapshot(
+    id: &str,
+    vault_password: Option<String>,
+) -> Result<RestoredSnapshot, ApicizeAppError> {
+    restore_snapshot_from(&get_snapshot_directory(id), vault_password)
+}
+
+fn restore_snapshot_from(
+    directory: &Path,
+    vault_password: Option<String>,
+) -> Result<RestoredSnapshot, ApicizeAppError> {
+    let stored = open_data_file::<StoredSnapshot>(&directory.join(SNAPSHOT_FILE_NAME))?.data;
+    let workbook_path = directory.join(WORKBOOK_FILE_NAME);
+
+    // Private parameters are loaded from alongside the workbook, so use a temporary copy
+    // of the original's if the snapshot does not include them
+    let original_path = (!stored.file_name.is_empty()).then(|| PathBuf::from(&stored.file_name));
+    let private_copy = match &original_path {
+        Some(original_path) if !stored.includes_private => {
+            let original_private = Parameters::get_workbook_private_filename(original_path);
+            if original_private.is_file() {
+                let private_copy = Parameters::get_workbook_private_filename(&workbook_path);
+                fs::copy(&original_private, &private_copy)?;
+                Some(private_copy)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let data_path = original_path
+        .as_deref()
+        .and_then(Path::parent)
+        .filter(|p| p.is_dir())
+        .unwrap_or(directory)
+        .to_path_buf();
+
+    let result = Workspace::open(
+        Some(&workbook_path),
+        &data_path,
+        OpenWorkbookOptions {
+            vault_password,
+            ..Default::default()
+        },
+    )
+    .map_err(ApicizeAppError::from)
+    .and_then(|mut workspace| {
+        let vault_restored = stored.includes_vault && restore_vault(directory, &mut workspace)?;
+        let vault_extensions = if vault_restored {
+            directory.join(VAULT_EXTENSIONS_FILE_NAME)
+        } else {
+            WorkbookExtensions::get_vault_file_name()
+        };
+        let extensions = WorkbookExtensions::open_with_vault(
+            Some(&workbook_path),
+            &vault_extensions,
+            &mut workspace,
+        )?;
+        Ok((workspace, extensions))
+    });
+
+    if let Some(private_copy) = private_copy {
+        fs::remove_file(private_copy)?;
+    }
+
+    let (workspace, extensions) = result?;
+    Ok(RestoredSnapshot {
+        workspace,
+        extensions,
+        file_name: stored.file_name,
+        data_set_content: stored.data_set_content,
+    })
+}
+
+/// Replace the workspace's vault parameters with those stored in the snapshot, if the
+/// vault is unlocked and the snapshot's copy can be decrypted with its password
+fn restore_vault(directory: &Path, workspace: &mut Workspace) -> Result<bool, ApicizeAppError> {
+    fn replace_vault<T: Clone + apicize_lib::Identifiable>(
+        entities: &IndexedEntities<T>,
+        vault: Option<Vec<T>>,
+    ) -> IndexedEntities<T>
+    where
+        IndexedEntities<T>: PersistedIndex<T>,
+    {
+        <IndexedEntities<T> as PersistedIndex<T>>::new(
+            entities.get_workbook(),
+            entities.get_private(),
+            vault,
+        )
+    }
+
+    if workspace.vault_lock_status.is_locked() {
+        return Ok(false);
+    }
+    let mut vault = Parameters::open(&directory.join(VAULT_FILE_NAME), true)?;
+    if vault
+        .decrypt(workspace.vault_password.as_deref(), None)
+        .0
+        .is_locked()
+    {
+        eprintln!("Unable to decrypt vault parameters in recovery snapshot");
+        return Ok(false);
+    }
+
+    workspace.scenarios = replace_vault::<Scenario>(&workspace.scenarios, vault.scenarios);
+    workspace.authorizations =
+        replace_vault::<Authorization>(&workspace.authorizations, vault.authorizations);
+    workspace.certificates =
+        replace_vault::<Certificate>(&workspace.certificates, vault.certificates);
+    workspace.proxies = replace_vault::<Proxy>(&workspace.proxies, vault.proxies);
+    workspace.perform_all_validations();
+    Ok(true)
+}
+
+/// Rename a snapshot when its workspace has been restored under a new ID
+pub fn rename_snapshot(id: &str, new_id: &str) -> Result<(), ApicizeAppError> {
+    fs::rename(get_snapshot_directory(id), get_snapshot_directory(new_id))?;
+    Ok(())
+}
+
+/// Remove snapshots other than those for the specified workspace IDs
+pub fn retain_snapshots(workspace_ids: &HashSet<&str>) {
+    let Ok(entries) = fs::read_dir(get_recovery_directory()) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir()
+            && !workspace_ids.contains(entry.file_name().to_string_lossy().as_ref())
+            && let Err(err) = fs::remove_dir_all(&path)
+        {
+            eprintln!(
+                "Unable to remove recovery snapshot {}: {err}",
+                path.to_string_lossy()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apicize_lib::{
+        PERSIST_PRIVATE, PERSIST_VAULT, PERSIST_WORKBOOK, ParameterLockStatus, RequestEntry,
+        ScenarioPlain, WorkbookDefaultParameters,
+    };
+
+    use super::*;
+
+    fn scenario(id: &str) -> Scenario {
+        Scenario::Plain(Box::new(ScenarioPlain {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    fn snapshot(lock_status: ParameterLockStatus) -> WorkspaceSnapshot {
+        WorkspaceSnapshot {
+            workspace: Workspace {
+                requests: IndexedEntities::<RequestEntry>::new(&[]),
+                scenarios: <IndexedEntities<Scenario> as PersistedIndex<Scenario>>::new(
+                    Some(vec![scenario("public")]),
+                    Some(vec![scenario("private")]),
+                    Some(vec![scenario("vault")]),
+                ),
+                authorizations: IndexedEntities::default(),
+                certificates: IndexedEntities::default(),
+                proxies: IndexedEntities::default(),
+                data: IndexedEntities::default(),
+                defaults: WorkbookDefaultParameters::default(),
+                private_lock_status: lock_status,
+                vault_lock_status: lock_status,
+                private_password: None,
+                vault_password: None,
+                private_encryption: None,
+                vault_encryption: None,
+            },
+            extensions: WorkbookExtensions::default(),
+            file_name: String::default(),
+            display_name: "Test".to_string(),
+            data_set_content: FxHashMap::default(),
+        }
+    }
+
+    #[test]
+    fn restores_private_and_vault_parameters() {
+        let directory = tempfile::tempdir().unwrap();
+        save_snapshot_to(
+            directory.path(),
+            &snapshot(ParameterLockStatus::UnlockedNoPassword),
+        )
+        .unwrap();
+
+        let restored = restore_snapshot_from(directory.path(), None).unwrap();
+        let ids = |store: &str| restored.workspace.scenarios.child_ids.get(store).cloned();
+        assert_eq!(ids(PERSIST_WORKBOOK).unwrap(), vec!["public"]);
+        assert_eq!(ids(PERSIST_PRIVATE).unwrap(), vec!["private"]);
+        assert_eq!(ids(PERSIST_VAULT).unwrap(), vec!["vault"]);
+    }
+
+    #[test]
+    fn omits_locked_stores() {
+        let directory = tempfile::tempdir().unwrap();
+        save_snapshot_to(directory.path(), &snapshot(ParameterLockStatus::Locked)).unwrap();
+
+        let workbook_path = directory.path().join(WORKBOOK_FILE_NAME);
+        assert!(workbook_path.is_file());
+        assert!(!Parameters::get_workbook_private_filename(&workbook_path).exists());
+        assert!(!directory.path().join(VAULT_FILE_NAME).exists());
+        let stored = open_data_file::<StoredSnapshot>(&directory.path().join(SNAPSHOT_FILE_NAME))
+            .unwrap()
+            .data;
+        assert!(!stored.includes_private && !stored.includes_vault);
+    }
+}
@@ -0,0 +1,769 @@
+//! Full-text search of request, group and scenario fields across a workspace
+
+use apicize_lib::{
+    NameValuePair, Request, RequestBody, RequestEntry, RequestGroup, Scenario, ScenarioPlain,
+    Workspace,
+};
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApicizeAppError, navigation::get_ordered_ids, workspaces::EntityType};
+
+/// Maximum number of hits returned by a search
+const MAX_HITS: usize = 1000;
+
+/// Number of characters included on either side of a match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+
+/// What to search for
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchOptions {
+    /// Text (or regular expression) to search for
+    pub query: String,
+    /// If true, query is a regular expression
+    #[serde(default)]
+    pub regex: bool,
+    /// If true, matches must have the same case as the query
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+impl SearchOptions {
+    /// Return a regular expression matching the query
+    pub fn build_regex(&self) -> Result<Regex, ApicizeAppError> {
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|err| ApicizeAppError::InvalidOperation(format!("Invalid search: {err}")))
+    }
+}
+
+/// Match found in a workspace field
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// ID of the entity containing the match
+    pub entity_id: String,
+    /// Type of entity containing the match
+    pub entity_type: EntityType,
+    /// Name of the entity containing the match
+    pub entity_name: String,
+    /// Field containing the match (ex. "url", "headers[0].value", "variables[2].name")
+    pub field: String,
+    /// Excerpt of the field's value around the match
+    pub snippet: String,
+    /// Byte offset of the start of the match in the field's value
+    pub start: usize,
+    /// Byte offset of the end of the match in the field's value
+    pub end: usize,
+}
+
+/// Matches found in a workspace
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    /// Matches, in navigation order
+    pub hits: Vec<SearchHit>,
+    /// True if there are encrypted scenarios, which cannot be searched until the parameter
+    /// store they belong to is unlocked
+    pub encrypted_scenarios_skipped: bool,
+}
+
+fn add_pair_fields<'a>(
+    fields: &mut Vec<(String, &'a str)>,
+    field: &str,
+    pairs: &'a [NameValuePair],
+) {
+    for (index, pair) in pairs.iter().enumerate() {
+        fields.push((format!("{field}[{index}].name"), &pair.name));
+        fields.push((format!("{field}[{index}].value"), &pair.value));
+    }
+}
+
+/// Return searchable fields of a request
+pub fn get_request_fields(request: &Request) -> Vec<(String, &str)> {
+    let mut fields = vec![("name".to_string(), request.name.as_str())];
+    if let Some(key) = &request.key {
+        fields.push(("key".to_string(), key));
+    }
+    fields.push(("url".to_string(), &request.url));
+    add_pair_fields(
+        &mut fields,
+        "headers",
+        request.headers.as_deref().unwrap_or_default(),
+    );
+    add_pair_fields(
+        &mut fields,
+        "queryStringParams",
+        request.query_string_params.as_deref().unwrap_or_default(),
+    );
+    match &request.body {
+        Some(RequestBody::Text { data })
+        | Some(RequestBody::JSON { data })
+        | Some(RequestBody::XML { data }) => fields.push(("body".to_string(), data)),
+        Some(RequestBody::GraphQL { data }) => {
+            fields.push(("body.query".to_string(), &data.query));
+            if let Some(extensions) = &data.extensions {
+                fields.push(("body.extensions".to_string(), extensions));
+            }
+        }
+        Some(RequestBody::Form { data }) => add_pair_fields(&mut fields, "body", data),
+        Some(RequestBody::Raw { .. }) | None => {}
+    }
+    if let Some(test) = &request.test {
+        fields.push(("test".to_string(), test));
+    }
+    fields
+}
+
+/// Return searchable fields of a group
+pub fn get_group_fields(group: &RequestGroup) -> Vec<(String, &str)> {
+    let mut fields = vec![("name".to_string(), group.name.as_str())];
+    if let Some(key) = &group.key {
+        fields.push(("key".to_string(), key));
+    }
+    if let Some(setup) = &group.setup {
+        fields.push(("setup".to_string(), setup));
+    }
+    fields
+}
+
+/// Return searchable fields of a scenario
+pub fn get_scenario_fields(scenario: &ScenarioPlain) -> Vec<(String, &str)> {
+    let mut fields = vec![("name".to_string(), scenario.name.as_str())];
+    for (index, variable) in scenario.variables.iter().flatten().enumerate() {
+        fields.push((format!("variables[{index}].name"), &variable.name));
+        fields.push((format!("variables[{index}].value"), &variable.value));
+    }
+    fields
+}
+
+// The following return the same fields as above, for replacement
+
+fn add_pair_fields_mut<'a>(
+    fields: &mut Vec<(String, &'a mut String)>,
+    field: &str,
+    pairs: &'a mut [NameValuePair],
+) {
+    for (index, pair) in pairs.iter_mut().enumerate() {
+        fields.push((format!("{field}[{index}].name"), &mut pair.name));
+        fields.push((format!("{field}[{index}].value"), &mut pair.value));
+    }
+}
+
+fn get_request_fields_mut(request: &mut Request) -> Vec<(String, &mut String)> {
+    let mut fields = vec![("name".to_string(), &mut request.name)];
+    if let Some(key) = &mut request.key {
+        fields.push(("key".to_string(), key));
+    }
+    fields.push(("url".to_string(), &mut request.url));
+    add_pair_fields_mut(
+        &mut fields,
+        "headers",
+        request.headers.as_deref_mut().unwrap_or_default(),
+    );
+    add_pair_fields_mut(
+        &mut fields,
+        "queryStringParams",
+        request
+            .query_string_params
+            .as_deref_mut()
+            .unwrap_or_default(),
+    );
+    match &mut request.body {
+        Some(RequestBody::Text { data })
+        | Some(RequestBody::JSON { data })
+        | Some(RequestBody::XML { data }) => fields.push(("body".to_string(), data)),
+        Some(RequestBody::GraphQL { data }) => {
+            fields.push(("body.query".to_string(), &mut data.query));
+            if let Some(extensions) = &mut data.extensions {
+                fields.push(("body.extensions".to_string(), extensions));
+            }
+        }
+        Some(RequestBody::Form { data }) => add_pair_fields_mut(&mut fields, "body", data),
+        Some(RequestBody::Raw { .. }) | None => {}
+    }
+    if let Some(test) = &mut request.test {
+        fields.push(("test".to_string(), test));
+    }
+    fields
+}
+
+fn get_group_fields_mut(group: &mut RequestGroup) -> Vec<(String, &mut String)> {
+    let mut fields = vec![("name".to_string(), &mut group.name)];
+    if let Some(key) = &mut group.key {
+        fields.push(("key".to_string(), key));
+    }
+    if let Some(setup) = &mut group.setup {
+        fields.push(("setup".to_string(), setup));
+    }
+    fields
+}
+
+fn get_scenario_fields_mut(scenario: &mut ScenarioPlain) -> Vec<(String, &mut String)> {
+    let mut fields = vec![("name".to_string(), &mut scenario.name)];
+    for (index, variable) in scenario.variables.iter_mut().flatten().enumerate() {
+        fields.push((format!("variables[{index}].name"), &mut variable.name));
+        fields.push((format!("variables[{index}].value"), &mut variable.value));
+    }
+    fields
+}
+
+/// Return an excerpt of the text around the specified range, on a single line
+fn build_snippet(text: &str, start: usize, end: usize) -> String {
+    let before = &text[..start];
+    let snippet_start = before
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let after = &text[end..];
+    let snippet_end = after
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(i, _)| end + i);
+
+    let mut snippet = String::with_capacity(snippet_end - snippet_start + 2);
+    if snippet_start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(
+        text[snippet_start..snippet_end]
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c }),
+    );
+    if snippet_end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+struct Searcher<'a> {
+    regex: &'a Regex,
+    hits: Vec<SearchHit>,
+}
+
+impl Searcher<'_> {
+    fn is_full(&self) -> bool {
+        self.hits.len() >= MAX_HITS
+    }
+
+    fn search(
+        &mut self,
+        entity_id: &str,
+        entity_type: EntityType,
+        entity_name: &str,
+        fields: Vec<(String, &str)>,
+    ) {
+        for (field, text) in fields {
+            for m in self.regex.find_iter(text) {
+                if self.is_full() {
+                    return;
+                }
+                // Ignore empty matches (ex. "^" or "a*")
+                if m.is_empty() {
+                    continue;
+                }
+                self.hits.push(SearchHit {
+                    entity_id: entity_id.to_string(),
+                    entity_type,
+                    entity_name: entity_name.to_string(),
+                    field: field.clone(),
+                    snippet: build_snippet(text, m.start(), m.end()),
+                    start: m.start(),
+                    end: m.end(),
+                });
+            }
+        }
+    }
+}
+
+/// Search requests, groups and (decrypted) scenarios, in navigation order, returning up
+/// to `MAX_HITS` matches
+pub fn search_workspace(
+    workspace: &Workspace,
+    options: &SearchOptions,
+) -> Result<SearchResults, ApicizeAppError> {
+    if options.query.is_empty() {
+        return Ok(SearchResults::default());
+    }
+    let regex = options.build_regex()?;
+    let mut searcher = Searcher {
+        regex: &regex,
+        hits: Vec::new(),
+    };
+
+    for id in get_ordered_ids(&workspace.requests) {
+        match workspace.requests.entities.get(&id) {
+            Some(RequestEntry::Request(request)) => searcher.search(
+                &request.id,
+                EntityType::Request,
+                &request.name,
+                get_request_fields(request),
+            ),
+            Some(RequestEntry::Group(group)) => searcher.search(
+                &group.id,
+                EntityType::Group,
+                &group.name,
+                get_group_fields(group),
+            ),
+            None => {}
+        }
+    }
+
+    // Encrypted scenarios cannot be searched until they are decrypted
+    let mut encrypted_scenarios_skipped = false;
+    for id in get_ordered_ids(&workspace.scenarios) {
+        match workspace.scenarios.entities.get(&id) {
+            Some(Scenario::Plain(scenario)) => searcher.search(
+                &scenario.id,
+                EntityType::Scenario,
+                &scenario.name,
+                get_scenario_fields(scenario),
+            ),
+            Some(Scenario::Cipher(_)) => encrypted_scenarios_skipped = true,
+            None => {}
+        }
+    }
+
+    Ok(SearchResults {
+        hits: searcher.hits,
+        encrypted_scenarios_skipped,
+    })
+}
+
+/// Replacement of the matches of a search
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceOptions {
+    /// Matches to replace
+    pub search: SearchOptions,
+    /// Text to replace each match with; when searching by regular expression, this may
+    /// refer to captured groups (ex. "$1")
+    pub replacement: String,
+    /// If set, only replace matches in these requests, groups or scenarios
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_ids: Option<Vec<String>>,
+}
+
+/// Change made to a field by a replacement
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldReplacement {
+    /// ID of the entity containing the field
+    pub entity_id: String,
+    /// Type of entity containing the field
+    pub entity_type: EntityType,
+    /// Name of the entity (prior to replacement)
+    pub entity_name: String,
+    /// Field (ex. "url", "headers[0].value", "variables[2].name")
+    pub field: String,
+    /// Value before replacement
+    pub original: String,
+    /// Value after replacement
+    pub replaced: String,
+    /// Number of matches replaced
+    pub count: usize,
+}
+
+/// Field containing matches that a replacement does not change
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedField {
+    /// ID of the entity containing the field
+    pub entity_id: String,
+    /// Type of entity containing the field
+    pub entity_type: EntityType,
+    /// Name of the entity
+    pub entity_name: String,
+    /// Field (ex. "body")
+    pub field: String,
+}
+
+/// Changes made (or that would be made) by a replacement
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceResults {
+    /// Changes to each field
+    pub fields: Vec<FieldReplacement>,
+    /// Fields with matches that are not replaced, i.e. raw (binary) request bodies
+    pub skipped: Vec<SkippedField>,
+}
+
+/// Entities changed by a replacement, as they would be after the replacement
+#[derive(Default)]
+pub struct PlannedReplacement {
+    /// Changes to each field
+    pub fields: Vec<FieldReplacement>,
+    /// Fields with matches that are not replaced
+    pub skipped: Vec<SkippedField>,
+    pub requests: Vec<Request>,
+    pub groups: Vec<RequestGroup>,
+    pub scenarios: Vec<ScenarioPlain>,
+}
+
+struct Replacer<'a> {
+    regex: Regex,
+    options: &'a ReplaceOptions,
+    fields: Vec<FieldReplacement>,
+}
+
+impl Replacer<'_> {
+    /// Replace matches in the fields, returning true if any were changed
+    fn replace(
+        &mut self,
+        entity_id: &str,
+        entity_type: EntityType,
+        entity_name: &str,
+        fields: Vec<(String, &mut String)>,
+    ) -> bool {
+        let mut changed = false;
+        for (field, text) in fields {
+            let mut count = 0;
+            let replaced = self.regex.replace_all(text, |captures: &Captures| {
+                let matched = &captures[0];
+                // Empty matches are not reported by search, so do not replace them either
+                if matched.is_empty() {
+                    return String::default();
+                }
+                count += 1;
+                if self.options.search.regex {
+                    let mut expanded = String::new();
+                    captures.expand(&self.options.replacement, &mut expanded);
+                    expanded
+                } else {
+                    self.options.replacement.clone()
+                }
+            });
+            if count == 0 || replaced == text.as_str() {
+                continue;
+            }
+            let replaced = replaced.into_owned();
+            self.fields.push(FieldReplacement {
+                entity_id: entity_id.to_string(),
+                entity_type,
+                entity_name: entity_name.to_string(),
+                field,
+                original: std::mem::replace(text, replaced.clone()),
+                replaced,
+                count,
+            });
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Determine the changes that replacing matches of a search would make to requests, groups
+/// and (decrypted) scenarios, in navigation order.  Raw request bodies are binary data, so
+/// matches in them are reported as skipped rather than replaced
+pub fn plan_replacement(
+    workspace: &Workspace,
+    options: &ReplaceOptions,
+) -> Result<PlannedReplacement, ApicizeAppError> {
+    let mut planned = PlannedReplacement::default();
+    if options.search.query.is_empty() {
+        return Ok(planned);
+    }
+    let mut replacer = Replacer {
+        regex: options.search.build_regex()?,
+        options,
+        fields: Vec::new(),
+    };
+    let in_scope = |id: &str| {
+        options
+            .entity_ids
+            .as_ref()
+            .is_none_or(|ids| ids.iter().any(|i| i == id))
+    };
+
+    for id in get_ordered_ids(&workspace.requests) {
+        if !in_scope(&id) {
+            continue;
+        }
+        match workspace.requests.entities.get(&id) {
+            Some(RequestEntry::Request(request)) => {
+                if let Some(RequestBody::Raw { data }) = &request.body
+                    && replacer.regex.is_match(&String::from_utf8_lossy(data))
+                {
+                    planned.skipped.push(SkippedField {
+                        entity_id: id.clone(),
+                        entity_type: EntityType::Request,
+                        entity_name: request.name.clone(),
+                        field: "body".to_string(),
+                    });
+                }
+                let mut request = request.clone();
+                let name = request.name.clone();
+                if replacer.replace(
+                    &id,
+                    EntityType::Request,
+                    &name,
+                    get_request_fields_mut(&mut request),
+                ) {
+                    planned.requests.push(request);
+                }
+            }
+            Some(RequestEntry::Group(group)) => {
+                let mut group = group.clone();
+                let name = group.name.clone();
+                if replacer.replace(
+                    &id,
+                    EntityType::Group,
+                    &name,
+                    get_group_fields_mut(&mut group),
+                ) {
+                    planned.groups.push(group);
+                }
+            }
+            None => {}
+        }
+    }
+
+    for id in get_ordered_ids(&workspace.scenarios) {
+        if !in_scope(&id) {
+            continue;
+        }
+        let Some(Scenario::Plain(scenario)) = workspace.scenarios.entities.get(&id) else {
+            continue;
+        };
+        let mut scenario = scenario.as_ref().clone();
+        let name = scenario.name.clone();
+        if replacer.replace(
+            &id,
+            EntityType::Scenario,
+            &name,
+            get_scenario_fields_mut(&mut scenario),
+        ) {
+            planned.scenarios.push(scenario);
+        }
+    }
+
+    planned.fields = replacer.fields;
+    Ok(planned)
+}
+
+#[cfg(test)]
+mod tests {
+    use apicize_lib::{
+        IndexedEntities, ParameterLockStatus, PersistedIndex, WorkbookDefaultParameters,
+        parameters::ParameterCipher,
+    };
+
+    use super::*;
+
+    fn options(query: &str, regex: bool, case_sensitive: bool) -> SearchOptions {
+        SearchOptions {
+            query: query.to_string(),
+            regex,
+            case_sensitive,
+        }
+    }
+
+    #[test]
+    fn matches_literal_and_regex_queries() {
+        let request = Request {
+            id: "r".to_string(),
+            name: "Get Customer".to_string(),
+            url: "https://api.example.com/customers/{{id}}".to_string(),
+            headers: Some(vec![NameValuePair {
+                name: "X-Api-Version".to_string(),
+                value: "2".to_string(),
+                disabled: None,
+            }]),
+            ..Default::default()
+        };
+        let search = |options: SearchOptions| {
+            let regex = options.build_regex().unwrap();
+            let mut searcher = Searcher {
+                regex: &regex,
+                hits: Vec::new(),
+            };
+            searcher.search("r", EntityType::Request, "r", get_request_fields(&request));
+            searcher
+                .hits
+                .into_iter()
+                .map(|h| h.field)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search(options("customer", false, false)),
+            vec!["name", "url"]
+        );
+        assert_eq!(search(options("customer", false, true)), vec!["url"]);
+        assert_eq!(search(options("{{id}}", false, false)), vec!["url"]);
+        assert_eq!(
+            search(options(r"x-api-\w+", true, false)),
+            vec!["headers[0].name"]
+        );
+        assert!(options("(", true, false).build_regex().is_err());
+    }
+
+    fn workspace(requests: Vec<RequestEntry>, private_scenarios: Vec<Scenario>) -> Workspace {
+        Workspace {
+            requests: IndexedEntities::<RequestEntry>::new(&requests),
+            scenarios: <IndexedEntities<Scenario> as PersistedIndex<Scenario>>::new(
+                Some(vec![Scenario::Plain(Box::new(ScenarioPlain {
+                    id: "public".to_string(),
+                    name: "Public Test".to_string(),
+                    ..Default::default()
+                }))]),
+                Some(private_scenarios),
+                None,
+            ),
+            authorizations: IndexedEntities::default(),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters::default(),
+            private_lock_status: ParameterLockStatus::Locked,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        }
+    }
+
+    #[test]
+    fn reports_skipped_encrypted_scenarios() {
+        let workspace = workspace(
+            vec![],
+            vec![Scenario::Cipher(ParameterCipher {
+                id: "private".to_string(),
+                name: "Private Test".to_string(),
+                data: String::default(),
+            })],
+        );
+
+        let results = search_workspace(&workspace, &options("test", false, false)).unwrap();
+        assert_eq!(
+            results
+                .hits
+                .iter()
+                .map(|h| h.entity_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["public"]
+        );
+        assert!(results.encrypted_scenarios_skipped);
+    }
+
+    #[test]
+    fn reports_matches_in_raw_bodies_as_skipped() {
+        let workspace = workspace(
+            vec![RequestEntry::Request(Request {
+                id: "r".to_string(),
+                name: "Upload".to_string(),
+                url: "https://localhost/test".to_string(),
+                body: Some(RequestBody::Raw {
+                    data: b"test data".to_vec(),
+                }),
+                ..Default::default()
+            })],
+            vec![],
+        );
+
+        let planned = plan_replacement(
+            &workspace,
+            &ReplaceOptions {
+                search: options("test", false, false),
+                replacement: "prod".to_string(),
+                entity_ids: Some(vec!["r".to_string()]),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            planned
+                .fields
+                .iter()
+                .map(|f| f.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["url"]
+        );
+        assert_eq!(
+            planned
+                .skipped
+                .iter()
+                .map(|f| (f.entity_id.as_str(), f.field.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("r", "body")]
+        );
+        assert!(matches!(
+            &planned.requests[0].body,
+            Some(RequestBody::Raw { data }) if data == b"test data"
+        ));
+    }
+
+    #[test]
+    fn builds_snippets_around_matches() {
+        let text = format!("{}needle\n{}", "a".repeat(50), "b".repeat(50));
+        let start = text.find("needle").unwrap();
+        let snippet = build_snippet(&text, start, start + 6);
+        assert_eq!(
+            snippet,
+            format!("…{}needle {}…", "a".repeat(40), "b".repeat(39))
+        );
+        assert_eq!(build_snippet("short needle", 6, 12), "short needle");
+    }
+
+    #[test]
+    fn replaces_matches_in_fields() {
+        let mut request = Request {
+            id: "r".to_string(),
+            name: "Get Customer".to_string(),
+            url: "https://old.example.com/customers/old".to_string(),
+            headers: Some(vec![NameValuePair {
+                name: "X-Api-Version".to_string(),
+                value: "v1".to_string(),
+                disabled: None,
+            }]),
+            ..Default::default()
+        };
+        let replace = |request: &mut Request, options: ReplaceOptions| {
+            let mut replacer = Replacer {
+                regex: options.search.build_regex().unwrap(),
+                options: &options,
+                fields: Vec::new(),
+            };
+            replacer.replace(
+                "r",
+                EntityType::Request,
+                "r",
+                get_request_fields_mut(request),
+            );
+            replacer.fields
+        };
+
+        let fields = replace(
+            &mut request,
+            ReplaceOptions {
+                search: options("OLD", false, false),
+                replacement: "$new".to_string(),
+                entity_ids: None,
+            },
+        );
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, "url");
+        assert_eq!(fields[0].count, 2);
+        assert_eq!(request.url, "https://$new.example.com/customers/$new");
+
+        let fields = replace(
+            &mut request,
+            ReplaceOptions {
+                search: options(r"^v(\d+)$", true, true),
+                replacement: "version-$1".to_string(),
+                entity_ids: None,
+            },
+        );
+        assert_eq!(fields[0].field, "headers[0].value");
+        assert_eq!(fields[0].original, "v1");
+        assert_eq!(request.headers.unwrap()[0].value, "version-1");
+    }
+}
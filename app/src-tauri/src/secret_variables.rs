@@ -0,0 +1,349 @@
+//! Scenario variables whose values are resolved when requests are executed, from an
+//! environment variable, a file or a command's output, so that secrets are never
+//! stored in workbooks
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use apicize_lib::{Scenario, Variable, VariableSourceType, Workspace};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Maximum time a secret variable's command may run before it is killed
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Source of a secret variable's value
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum SecretSourceType {
+    /// Environment variable
+    #[serde(rename = "ENV")]
+    EnvVar,
+    /// Contents of a file, relative to the workbook's directory
+    #[serde(rename = "FILE-SECRET")]
+    File,
+    /// Standard output of a shell command
+    #[serde(rename = "COMMAND")]
+    Command,
+}
+
+/// Reference to a secret, stored in workbook extensions instead of the variable's value
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretVariable {
+    #[serde(rename = "type")]
+    pub source_type: SecretSourceType,
+    /// Environment variable name, file name or command
+    pub reference: String,
+}
+
+/// Secret variables for a scenario, indexed by variable name
+pub type ScenarioSecretVariables = BTreeMap<String, SecretVariable>;
+
+impl SecretVariable {
+    /// Return the secret's value, with a single trailing line break removed
+    pub fn resolve(&self, directory: Option<&Path>) -> Result<String, String> {
+        let reference = self.reference.trim();
+        if reference.is_empty() {
+            return Err("Secret reference is not set".to_string());
+        }
+        let value = match self.source_type {
+            SecretSourceType::EnvVar => env::var(reference)
+                .map_err(|_| format!("Environment variable \"{reference}\" is not defined"))?,
+            SecretSourceType::File => {
+                let file_name = match directory {
+                    Some(directory) => directory.join(reference),
+                    None => PathBuf::from(reference),
+                };
+                fs::read_to_string(&file_name).map_err(|err| {
+                    format!("Unable to read secret file {}: {err}", file_name.display())
+                })?
+            }
+            SecretSourceType::Command => run_command(reference, directory, COMMAND_TIMEOUT)?,
+        };
+        let value = value.strip_suffix('\n').unwrap_or(&value);
+        Ok(value.strip_suffix('\r').unwrap_or(value).to_string())
+    }
+}
+
+/// Run a shell command and return its standard output, killing it if it does not
+/// complete within the timeout
+fn run_command(
+    reference: &str,
+    directory: Option<&Path>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", reference]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", reference]);
+        command
+    };
+    if let Some(directory) = directory {
+        command.current_dir(directory);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Unable to run \"{reference}\": {err}"))?;
+
+    // Read output on separate threads so that a command filling a pipe is not blocked
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "\"{reference}\" did not complete within {} seconds",
+                    timeout.as_secs()
+                ));
+            }
+            Err(err) => return Err(format!("Unable to run \"{reference}\": {err}")),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!(
+            "\"{reference}\" failed ({status}): {}",
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+    String::from_utf8(stdout).map_err(|_| format!("\"{reference}\" did not output text"))
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut data);
+        }
+        data
+    })
+}
+
+/// Return the commands run by secret variables
+pub fn get_secret_commands(
+    secrets: &BTreeMap<String, ScenarioSecretVariables>,
+) -> BTreeSet<String> {
+    secrets
+        .values()
+        .flat_map(|variables| variables.values())
+        .filter(|secret| secret.source_type == SecretSourceType::Command)
+        .map(|secret| secret.reference.trim().to_string())
+        .collect()
+}
+
+/// Return a fingerprint of secret variable commands, so that trusting a workbook's
+/// commands does not extend to commands added or changed afterwards
+pub fn get_commands_fingerprint(commands: &BTreeSet<String>) -> String {
+    let mut hasher = Sha256::new();
+    for command in commands {
+        hasher.update(command.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Resolve secret variables for the specified scenarios, indexed by scenario ID
+pub fn resolve_secret_variables(
+    secrets: &BTreeMap<String, ScenarioSecretVariables>,
+    directory: Option<&Path>,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, String> {
+    secrets
+        .iter()
+        .map(|(scenario_id, variables)| {
+            variables
+                .iter()
+                .map(|(name, secret)| {
+                    secret
+                        .resolve(directory)
+                        .map(|value| (name.clone(), value))
+                        .map_err(|err| format!("Variable \"{name}\": {err}"))
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()
+                .map(|values| (scenario_id.clone(), values))
+        })
+        .collect()
+}
+
+/// Set resolved secret values in a workspace cloned for execution
+pub fn apply_secret_values(
+    workspace: &mut Workspace,
+    values: BTreeMap<String, BTreeMap<String, String>>,
+) {
+    for (scenario_id, values) in values {
+        let Some(Scenario::Plain(scenario)) = workspace.scenarios.entities.get_mut(&scenario_id)
+        else {
+            continue;
+        };
+        let variables = scenario.variables.get_or_insert_with(Vec::new);
+        for (name, value) in values {
+            match variables.iter_mut().find(|v| v.name == name) {
+                Some(variable) => {
+                    if variable.disabled != Some(true) {
+                        variable.source_type = VariableSourceType::Text;
+                        variable.value = value;
+                    }
+                }
+                None => variables.push(Variable {
+                    name,
+                    source_type: VariableSourceType::Text,
+                    value,
+                    disabled: None,
+                }),
+            }
+        }
+    }
+}
+
+/// Make sure each secret has a text variable placeholder without a value, and drop
+/// secrets whose variables have been removed
+pub fn update_placeholders(
+    variables: &mut Option<Vec<Variable>>,
+    secrets: &mut ScenarioSecretVariables,
+    variables_updated: bool,
+) {
+    let list = variables.get_or_insert_with(Vec::new);
+    if variables_updated {
+        secrets.retain(|name, _| list.iter().any(|v| &v.name == name));
+    }
+    for name in secrets.keys() {
+        match list.iter_mut().find(|v| &v.name == name) {
+            Some(variable) => {
+                variable.source_type = VariableSourceType::Text;
+                variable.value = String::default();
+            }
+            None => list.push(Variable {
+                name: name.clone(),
+                source_type: VariableSourceType::Text,
+                value: String::default(),
+                disabled: None,
+            }),
+        }
+    }
+    if list.is_empty() {
+        *variables = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_secrets() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(directory.path().join("token"), "file-secret\n").unwrap();
+
+        let file = SecretVariable {
+            source_type: SecretSourceType::File,
+            reference: "token".to_string(),
+        };
+        assert_eq!(file.resolve(Some(directory.path())).unwrap(), "file-secret");
+
+        let missing = SecretVariable {
+            source_type: SecretSourceType::EnvVar,
+            reference: "APICIZE_TEST_UNDEFINED_SECRET".to_string(),
+        };
+        assert!(missing.resolve(None).is_err());
+
+        #[cfg(unix)]
+        {
+            let command = SecretVariable {
+                source_type: SecretSourceType::Command,
+                reference: "echo command-secret".to_string(),
+            };
+            assert_eq!(command.resolve(None).unwrap(), "command-secret");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_commands_that_time_out() {
+        let started = Instant::now();
+        let result = run_command("sleep 10", None, Duration::from_millis(200));
+        assert!(result.unwrap_err().contains("did not complete"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn fingerprints_commands() {
+        let secret = |source_type, reference: &str| SecretVariable {
+            source_type,
+            reference: reference.to_string(),
+        };
+        let mut secrets = BTreeMap::from([(
+            "scenario".to_string(),
+            ScenarioSecretVariables::from([
+                (
+                    "token".to_string(),
+                    secret(SecretSourceType::Command, " pass show api "),
+                ),
+                ("key".to_string(), secret(SecretSourceType::EnvVar, "KEY")),
+            ]),
+        )]);
+        let commands = get_secret_commands(&secrets);
+        assert_eq!(commands, BTreeSet::from(["pass show api".to_string()]));
+        let fingerprint = get_commands_fingerprint(&commands);
+
+        secrets.get_mut("scenario").unwrap().insert(
+            "other".to_string(),
+            secret(SecretSourceType::Command, "curl example.com"),
+        );
+        assert_ne!(
+            get_commands_fingerprint(&get_secret_commands(&secrets)),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn maintains_placeholders() {
+        let mut secrets = ScenarioSecretVariables::from([
+            (
+                "token".to_string(),
+                SecretVariable {
+                    source_type: SecretSourceType::EnvVar,
+                    reference: "TOKEN".to_string(),
+                },
+            ),
+            (
+                "removed".to_string(),
+                SecretVariable {
+                    source_type: SecretSourceType::EnvVar,
+                    reference: "REMOVED".to_string(),
+                },
+            ),
+        ]);
+        let mut variables = Some(vec![Variable {
+            name: "token".to_string(),
+            source_type: VariableSourceType::JSON,
+            value: "leaked".to_string(),
+            disabled: None,
+        }]);
+        update_placeholders(&mut variables, &mut secrets, true);
+        assert_eq!(secrets.keys().collect::<Vec<_>>(), vec!["token"]);
+        let variables = variables.unwrap();
+        assert_eq!(variables.len(), 1);
+        assert!(variables[0].value.is_empty());
+        assert!(variables[0].source_type == VariableSourceType::Text);
+    }
+}
@@ -0,0 +1,152 @@
+//! Resolution of parameters selected by requests and groups, which may be inherited from
+//! parent groups or the workbook's defaults
+
+use std::collections::HashSet;
+
+use apicize_lib::{SelectedParameters, Selection, Workspace};
+
+/// Return the parameter selected for a request or group, resolving default selections
+/// from parents and workbook defaults
+pub fn get_effective_selection<'a>(
+    workspace: &'a Workspace,
+    request_or_group_id: &str,
+    select: fn(&dyn SelectedParameters) -> &Selection,
+) -> Option<&'a Selection> {
+    let mut current_id = request_or_group_id;
+    loop {
+        let selection = select(workspace.requests.entities.get(current_id)?);
+        if !selection.is_default() {
+            return (!selection.is_none()).then_some(selection);
+        }
+        match workspace.requests.parent_ids.get(current_id) {
+            Some(parent_id) => current_id = parent_id,
+            None => {
+                let default = select(&workspace.defaults);
+                return (!default.is_default_or_none()).then_some(default);
+            }
+        }
+    }
+}
+
+/// Append IDs of parameters selected by the request or group and its descendants
+pub fn append_selected_ids(
+    workspace: &Workspace,
+    request_or_group_id: &str,
+    select: fn(&dyn SelectedParameters) -> &Selection,
+    results: &mut HashSet<String>,
+) {
+    if let Some(selection) = get_effective_selection(workspace, request_or_group_id, select) {
+        results.insert(selection.id.to_string());
+    }
+    if let Some(child_ids) = workspace.requests.child_ids.get(request_or_group_id) {
+        for child_id in child_ids {
+            append_selected_ids(workspace, child_id, select, results);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apicize_lib::{
+        IndexedEntities, ParameterLockStatus, Request, RequestEntry, RequestGroup,
+        WorkbookDefaultParameters, editing::indexed_entities::IndexedEntityPosition,
+    };
+
+    fn selection(id: &str) -> Selection {
+        Selection {
+            id: id.to_string(),
+            name: String::default(),
+        }
+    }
+
+    fn request(id: &str, selected_authorization: Selection) -> RequestEntry {
+        RequestEntry::Request(Request {
+            id: id.to_string(),
+            name: id.to_string(),
+            selected_authorization,
+            ..Default::default()
+        })
+    }
+
+    /// Group "g" selects "group-auth" with children inheriting it ("g1"), overriding it
+    /// ("g2") and selecting nothing ("g3"); "r" inherits the workbook default
+    fn workspace() -> Workspace {
+        let mut requests = IndexedEntities::<RequestEntry>::default();
+        requests
+            .add_entity(
+                RequestEntry::Group(RequestGroup {
+                    id: "g".to_string(),
+                    name: "g".to_string(),
+                    selected_authorization: selection("group-auth"),
+                    ..Default::default()
+                }),
+                None,
+                None,
+            )
+            .unwrap();
+        for (id, selected) in [
+            ("g1", Selection::default()),
+            ("g2", selection("request-auth")),
+            ("g3", Selection::new_none()),
+        ] {
+            requests
+                .add_entity(
+                    request(id, selected),
+                    Some("g"),
+                    Some(IndexedEntityPosition::Under),
+                )
+                .unwrap();
+        }
+        requests
+            .add_entity(request("r", Selection::default()), None, None)
+            .unwrap();
+
+        Workspace {
+            requests,
+            scenarios: IndexedEntities::default(),
+            authorizations: IndexedEntities::default(),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters {
+                selected_authorization: selection("default-auth"),
+                ..Default::default()
+            },
+            private_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        }
+    }
+
+    #[test]
+    fn resolves_inherited_selections() {
+        let workspace = workspace();
+        let effective =
+            |id| get_effective_selection(&workspace, id, |p| p.selected_authorization());
+        assert_eq!(effective("g1").unwrap().id, "group-auth");
+        assert_eq!(effective("g2").unwrap().id, "request-auth");
+        assert!(effective("g3").is_none());
+        assert_eq!(effective("r").unwrap().id, "default-auth");
+        assert!(effective("missing").is_none());
+    }
+
+    #[test]
+    fn collects_selections_of_descendants_only() {
+        let workspace = workspace();
+        let mut ids = HashSet::<String>::new();
+        append_selected_ids(&workspace, "g", |p| p.selected_authorization(), &mut ids);
+        assert_eq!(
+            ids,
+            HashSet::from(["group-auth".to_string(), "request-auth".to_string()])
+        );
+
+        // Inheriting from a parent does not include the parent's other descendants
+        ids.clear();
+        append_selected_ids(&workspace, "g1", |p| p.selected_authorization(), &mut ids);
+        assert_eq!(ids, HashSet::from(["group-auth".to_string()]));
+    }
+}
@@ -127,6 +127,7 @@ impl Session {
                     | EntityType::Certificate
                     | EntityType::Proxy
                     | EntityType::DataSet
+                    | EntityType::TrustedRoots
             ),
             None => false,
         };
@@ -246,6 +247,10 @@ pub struct SessionSaveState {
     pub display_name: String,
     pub dirty: bool,
     pub editor_count: usize,
+    /// True if the workspace has edits that can be undone
+    pub can_undo: bool,
+    /// True if the workspace has undone edits that can be redone
+    pub can_redo: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
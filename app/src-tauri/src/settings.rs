@@ -3,6 +3,7 @@
 //! This submodule defines models used to store application settings
 
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs::create_dir_all,
     path::{self, Path},
 };
@@ -28,6 +29,14 @@ fn default_pkce_listener_port() -> u16 {
     8080
 }
 
+fn default_recovery_interval() -> u64 {
+    30
+}
+
+fn default_pkce_timeout() -> u64 {
+    300
+}
+
 fn default_editor_indent_size() -> u8 {
     4
 }
@@ -88,6 +97,18 @@ pub struct ApicizeSettings {
     #[serde(default = "default_pkce_listener_port")]
     pub pkce_listener_port: u16,
 
+    /// Path of the PKCE redirect URI (e.g. "/callback"), blank for none
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pkce_redirect_path: String,
+
+    /// Start a listener on a free port for each PKCE flow instead of using the listener port
+    #[serde(default)]
+    pub pkce_ephemeral_port: bool,
+
+    /// Number of seconds to wait for a PKCE flow to complete
+    #[serde(default = "default_pkce_timeout")]
+    pub pkce_timeout: u64,
+
     /// Always hide navigation tree
     #[serde(default)]
     pub always_hide_nav_tree: bool,
@@ -107,6 +128,24 @@ pub struct ApicizeSettings {
     /// Tab indent
     #[serde(default = "default_true")]
     pub editor_check_js_syntax: bool,
+
+    /// IDs of authorizations whose OAuth2 tokens are persisted between sessions, encrypted
+    /// with the vault password
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub persisted_token_authorization_ids: BTreeSet<String>,
+
+    /// Fingerprints of secret variable commands the user has trusted, indexed by workbook
+    /// file name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted_secret_commands: BTreeMap<String, String>,
+
+    /// Remember parameter store passwords in the operating system keyring
+    #[serde(default)]
+    pub remember_passwords_in_keyring: bool,
+
+    /// Number of seconds between recovery snapshots of unsaved workspaces (0 to disable)
+    #[serde(default = "default_recovery_interval")]
+    pub recovery_interval: u64,
 }
 
 impl ApicizeSettings {
@@ -190,12 +229,19 @@ impl ApicizeSettings {
                 editor_panels: String::from(""),
                 recent_workbook_file_names: None,
                 pkce_listener_port: 8080,
+                pkce_redirect_path: String::default(),
+                pkce_ephemeral_port: false,
+                pkce_timeout: default_pkce_timeout(),
                 always_hide_nav_tree: false,
                 show_diagnostic_info: false,
                 report_format: ExecutionReportFormat::JSON,
                 editor_indent_size: 3,
                 editor_check_js_syntax: true,
                 editor_detect_existing_indent: true,
+                persisted_token_authorization_ids: BTreeSet::new(),
+                trusted_secret_commands: BTreeMap::new(),
+                remember_passwords_in_keyring: false,
+                recovery_interval: default_recovery_interval(),
             };
             Ok(SerializationOpenSuccess {
                 file_name: String::from(""),
@@ -0,0 +1,51 @@
+//! Substitution of `{{name}}` handlebars in values used outside of apicize_lib's runner,
+//! following the same rules as the runner so values resolve identically
+
+use std::collections::HashMap;
+
+/// Return the text with each `{{name}}` found in the substitutions replaced by its value;
+/// handlebars without a substitution are left as they are
+pub fn clone_and_sub(text: &str, subs: &HashMap<String, String>) -> String {
+    if subs.is_empty() || !text.contains("{{") {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut remaining = text;
+
+    while let Some(start) = remaining.find("{{") {
+        result.push_str(&remaining[..start]);
+        if let Some(end) = remaining[start + 2..].find("}}") {
+            let key = &remaining[start..start + 2 + end + 2];
+            match subs.get(key) {
+                Some(value) => result.push_str(value),
+                None => result.push_str(key),
+            }
+            remaining = &remaining[start + 2 + end + 2..];
+        } else {
+            // No closing braces, copy the rest as is
+            result.push_str(&remaining[start..]);
+            return result;
+        }
+    }
+    result.push_str(remaining);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_known_handlebars() {
+        let subs = HashMap::from([
+            ("{{host}}".to_string(), "example.com".to_string()),
+            ("{{id}}".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(
+            clone_and_sub("https://{{host}}/{{id}}/{{other}}", &subs),
+            "https://example.com/1/{{other}}"
+        );
+        assert_eq!(clone_and_sub("{{host", &subs), "{{host");
+    }
+}
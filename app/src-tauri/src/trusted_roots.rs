@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use apicize_lib::{
+    Identifiable, Validated, ValidationState, add_validation_error, generate_uuid,
+    identifiable::CloneIdentifiable, remove_validation_error,
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+
+const PEM_CERTIFICATE_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
+/// Bundle of root certificate authorities to trust when validating server certificates
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedRoots {
+    /// Uniquely identify trusted root bundle
+    #[serde(default = "generate_uuid")]
+    pub id: String,
+    /// Name of trusted root bundle
+    pub name: String,
+    /// One or more PEM encoded CA certificates
+    #[serde(default)]
+    pub pem: String,
+    /// If true, roots are trusted in addition to the operating system's store,
+    /// otherwise only these roots are trusted
+    #[serde(default = "default_include_system_roots")]
+    pub include_system_roots: bool,
+    /// Validation state
+    #[serde(default, skip_serializing_if = "ValidationState::is_empty")]
+    pub validation_state: ValidationState,
+    /// Warnings for invalid values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_warnings: Option<Vec<String>>,
+    /// Validation errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_errors: Option<HashMap<String, String>>,
+}
+
+fn default_include_system_roots() -> bool {
+    true
+}
+
+/// Return the DER encoding of each PEM encoded certificate in `pem`
+pub fn parse_pem_certificates(pem: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut certificates: Vec<Vec<u8>> = vec![];
+    let mut block: Option<String> = None;
+
+    for line in pem.lines().map(|l| l.trim()) {
+        match &mut block {
+            None => {
+                if line == PEM_CERTIFICATE_BEGIN {
+                    block = Some(String::new());
+                }
+            }
+            Some(encoded) => {
+                if line == PEM_CERTIFICATE_END {
+                    let der = BASE64.decode(encoded.as_bytes()).map_err(|err| {
+                        format!("Certificate {} is not valid: {err}", certificates.len() + 1)
+                    })?;
+                    // Certificates are DER encoded ASN.1 sequences
+                    if der.first() != Some(&0x30) {
+                        return Err(format!(
+                            "Certificate {} is not valid",
+                            certificates.len() + 1
+                        ));
+                    }
+                    certificates.push(der);
+                    block = None;
+                } else if line.starts_with("-----") {
+                    return Err(format!(
+                        "Certificate {} is missing \"{PEM_CERTIFICATE_END}\"",
+                        certificates.len() + 1
+                    ));
+                } else {
+                    encoded.push_str(line);
+                }
+            }
+        }
+    }
+
+    if block.is_some() {
+        return Err(format!(
+            "Certificate {} is missing \"{PEM_CERTIFICATE_END}\"",
+            certificates.len() + 1
+        ));
+    }
+
+    if certificates.is_empty() {
+        return Err("At least one PEM encoded certificate is required".to_string());
+    }
+
+    Ok(certificates)
+}
+
+impl Default for TrustedRoots {
+    fn default() -> Self {
+        Self {
+            id: generate_uuid(),
+            name: Default::default(),
+            pem: Default::default(),
+            include_system_roots: true,
+            validation_state: Default::default(),
+            validation_warnings: None,
+            validation_errors: None,
+        }
+    }
+}
+
+impl TrustedRoots {
+    /// Return the DER encoding of each certificate in the bundle
+    pub fn get_certificates(&self) -> Result<Vec<Vec<u8>>, String> {
+        parse_pem_certificates(&self.pem)
+    }
+
+    pub fn perform_validation(&mut self) {
+        self.validate_name();
+        self.validate_pem();
+    }
+
+    pub fn validate_name(&mut self) {
+        let name_ok = !self.name.trim().is_empty();
+        if name_ok {
+            remove_validation_error(&mut self.validation_errors, "name");
+        } else {
+            add_validation_error(&mut self.validation_errors, "name", "Name is required");
+        }
+        self.validation_state
+            .set(ValidationState::ERROR, self.validation_errors.is_some());
+    }
+
+    pub fn validate_pem(&mut self) {
+        match self.get_certificates() {
+            Ok(_) => remove_validation_error(&mut self.validation_errors, "pem"),
+            Err(err) => add_validation_error(&mut self.validation_errors, "pem", &err),
+        }
+        self.validation_state
+            .set(ValidationState::ERROR, self.validation_errors.is_some());
+    }
+}
+
+impl Identifiable for TrustedRoots {
+    fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_title(&self) -> String {
+        if self.name.is_empty() {
+            "(Unnamed)".to_string()
+        } else {
+            self.name.to_string()
+        }
+    }
+}
+
+impl CloneIdentifiable for TrustedRoots {
+    fn clone_as_new(&self, new_name: String) -> Self {
+        let mut cloned = self.clone();
+        cloned.id = generate_uuid();
+        cloned.name = new_name;
+        cloned
+    }
+}
+
+impl Validated for TrustedRoots {
+    fn get_validation_state(&self) -> ValidationState {
+        self.validation_state
+    }
+
+    fn get_validation_warnings(&self) -> &Option<Vec<String>> {
+        &self.validation_warnings
+    }
+
+    fn set_validation_warnings(&mut self, warnings: Option<Vec<String>>) {
+        self.validation_warnings = warnings;
+        self.validation_state
+            .set(ValidationState::WARNING, self.validation_warnings.is_some());
+    }
+
+    fn get_validation_errors(&self) -> &Option<HashMap<String, String>> {
+        &self.validation_errors
+    }
+
+    fn set_validation_errors(&mut self, errors: Option<HashMap<String, String>>) {
+        self.validation_errors = errors;
+        self.validation_state
+            .set(ValidationState::ERROR, self.validation_errors.is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CERTIFICATE: &str =
+        "MIIBszCCAVmgAwIBAgIUBYFnQkSDqLlFNHQvX3bjG3iDtOIwCgYIKoZIzj0EAwIw";
+
+    fn bundle(pem: &str) -> TrustedRoots {
+        TrustedRoots {
+            pem: pem.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_multiple_certificates() {
+        let pem = format!(
+            "{PEM_CERTIFICATE_BEGIN}\n{TEST_CERTIFICATE}\n{PEM_CERTIFICATE_END}\n\n{PEM_CERTIFICATE_BEGIN}\r\n{TEST_CERTIFICATE}\r\n{PEM_CERTIFICATE_END}\r\n"
+        );
+        let certificates = bundle(&pem).get_certificates().unwrap();
+        assert_eq!(certificates.len(), 2);
+        assert_eq!(certificates[0], certificates[1]);
+    }
+
+    #[test]
+    fn rejects_empty_and_unterminated_bundles() {
+        assert!(bundle("").get_certificates().is_err());
+        assert!(
+            bundle(&format!("{PEM_CERTIFICATE_BEGIN}\n{TEST_CERTIFICATE}\n"))
+                .get_certificates()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_content() {
+        let pem = format!("{PEM_CERTIFICATE_BEGIN}\nnot base64!\n{PEM_CERTIFICATE_END}");
+        assert!(bundle(&pem).get_certificates().is_err());
+
+        let mut trusted_roots = bundle(&pem);
+        trusted_roots.name = "Internal CA".to_string();
+        trusted_roots.perform_validation();
+        assert!(
+            trusted_roots
+                .validation_errors
+                .is_some_and(|errors| errors.contains_key("pem") && !errors.contains_key("name"))
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Per-workspace history of edits, recorded as the operations that revert them so that
+//! undoing an edit produces the operation to redo it (and vice versa)
+
+use std::time::{Duration, Instant};
+
+use apicize_lib::{
+    ApicizeError, Authorization, Certificate, DataSet, Identifiable, IndexedEntities, Proxy,
+    RequestBody, RequestEntry, Scenario, editing::indexed_entities::IndexedEntityPosition,
+};
+
+use crate::{
+    authorization_settings::AuthorizationSettings, proxy_settings::ProxySettings,
+    secret_variables::ScenarioSecretVariables, trusted_roots::TrustedRoots, updates::EntityUpdate,
+    workspaces::DataSetContent, workspaces::EntityType,
+};
+
+/// Maximum number of steps retained for undo and for redo
+const MAX_STEPS: usize = 100;
+
+/// Consecutive updates to the same entity within this interval (i.e. typing) are undone
+/// as a single step
+const COALESCE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Operation that reverts (or re-applies) an edit
+#[allow(clippy::large_enum_variant)]
+pub enum UndoOperation {
+    /// Apply an entity update
+    Update(EntityUpdate),
+    /// Set a request's body (which entity updates do not include)
+    UpdateBody {
+        request_id: String,
+        body: Option<RequestBody>,
+    },
+    /// Delete an entity along with any descendants
+    Delete {
+        entity_type: EntityType,
+        entity_id: String,
+    },
+    /// Re-insert a deleted entity
+    Restore(Box<DeletedEntity>),
+    /// Move an entity relative to another entity
+    Move {
+        entity_type: EntityType,
+        entity_id: String,
+        relative_to_id: String,
+        relative_position: IndexedEntityPosition,
+    },
+}
+
+/// Entity removed from a workspace, along with what is required to restore it
+pub struct DeletedEntity {
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub entities: DeletedEntities,
+    /// Selections referring to the entity, which are reset when it is deleted
+    pub selections: Vec<EntityUpdate>,
+}
+
+/// Deleted entities and any settings stored for them in workbook extensions
+pub enum DeletedEntities {
+    Requests(RemovedEntities<RequestEntry>),
+    Scenario(RemovedEntities<Scenario>, Option<ScenarioSecretVariables>),
+    Authorization(
+        RemovedEntities<Authorization>,
+        Option<AuthorizationSettings>,
+    ),
+    Certificate(RemovedEntities<Certificate>),
+    Proxy(RemovedEntities<Proxy>, Option<ProxySettings>),
+    DataSet(RemovedEntities<DataSet>, Option<DataSetContent>),
+    TrustedRoots(RemovedEntities<TrustedRoots>),
+}
+
+/// Indexed entity and its descendants, along with the position to restore them to
+pub struct RemovedEntities<T> {
+    /// Entity (and position) to restore the removed entity relative to
+    anchor: Option<(String, IndexedEntityPosition)>,
+    /// Removed entity followed by its descendants, each with its parent ID
+    entities: Vec<(T, Option<String>)>,
+}
+
+impl<T: Identifiable + Clone> RemovedEntities<T> {
+    /// Capture an entity and its descendants prior to removal
+    pub fn capture(index: &IndexedEntities<T>, entity_id: &str) -> Option<Self> {
+        let entity = index.entities.get(entity_id)?;
+        let mut entities = vec![(entity.clone(), None)];
+        let mut next = 0;
+        while next < entities.len() {
+            let parent_id = entities[next].0.get_id().to_string();
+            if let Some(child_ids) = index.child_ids.get(&parent_id) {
+                for child_id in child_ids {
+                    if let Some(child) = index.entities.get(child_id) {
+                        entities.push((child.clone(), Some(parent_id.clone())));
+                    }
+                }
+            }
+            next += 1;
+        }
+        Some(RemovedEntities {
+            anchor: find_anchor(index, entity_id),
+            entities,
+        })
+    }
+
+    /// Re-insert the removed entity and its descendants
+    pub fn restore(self, index: &mut IndexedEntities<T>) -> Result<(), ApicizeError> {
+        // Fall back to appending if whatever the entity was positioned against is gone
+        let (relative_to_id, relative_position) = match self.anchor {
+            Some((id, position))
+                if index.entities.contains_key(&id) || index.child_ids.contains_key(&id) =>
+            {
+                (Some(id), Some(position))
+            }
+            _ => (None, None),
+        };
+        let mut entities = self.entities.into_iter();
+        if let Some((entity, _)) = entities.next() {
+            index.add_entity(entity, relative_to_id.as_deref(), relative_position)?;
+        }
+        for (entity, parent_id) in entities {
+            index.add_entity(
+                entity,
+                parent_id.as_deref(),
+                Some(IndexedEntityPosition::Under),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Return a sibling or parent that an entity can be positioned relative to in order to
+/// return it to its current position, or None if it is the only top level entity
+pub fn find_anchor<T>(
+    index: &IndexedEntities<T>,
+    entity_id: &str,
+) -> Option<(String, IndexedEntityPosition)> {
+    let parent_id = index
+        .parent_ids
+        .get(entity_id)
+        .map(|id| id.as_str())
+        .or_else(|| {
+            index
+                .child_ids
+                .iter()
+                .find(|(_, child_ids)| child_ids.iter().any(|id| id == entity_id))
+                .map(|(parent_id, _)| parent_id.as_str())
+        });
+    let siblings = match parent_id {
+        Some(parent_id) => index.child_ids.get(parent_id)?,
+        None => &index.top_level_ids,
+    };
+    let position = siblings.iter().position(|id| id == entity_id)?;
+    if position > 0 {
+        Some((siblings[position - 1].clone(), IndexedEntityPosition::After))
+    } else if let Some(next) = siblings.get(position + 1) {
+        Some((next.clone(), IndexedEntityPosition::Before))
+    } else {
+        parent_id.map(|parent_id| (parent_id.to_string(), IndexedEntityPosition::Under))
+    }
+}
+
+/// Operations undone or redone together
+struct UndoStep {
+    operations: Vec<UndoOperation>,
+    /// Entity updated by the step, if the step can be combined with later updates
+    coalesce_key: Option<String>,
+    recorded_at: Instant,
+}
+
+impl UndoStep {
+    fn new(operations: Vec<UndoOperation>) -> Self {
+        UndoStep {
+            operations,
+            coalesce_key: None,
+            recorded_at: Instant::now(),
+        }
+    }
+}
+
+/// Undo and redo stacks for a workspace, shared by all of its sessions
+#[derive(Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoStep>,
+    redo: Vec<UndoStep>,
+}
+
+impl UndoHistory {
+    /// Record the operations that revert an edit, clearing anything available to redo
+    pub fn record(&mut self, operations: Vec<UndoOperation>) {
+        if operations.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        Self::push(&mut self.undo, UndoStep::new(operations));
+    }
+
+    /// Record the update that reverts an entity update; if the previous step updated the
+    /// same entity moments ago, it already reverts this update as well
+    pub fn record_update(&mut self, inverse: EntityUpdate) {
+        let key = Self::get_update_key(&inverse);
+        self.record_coalesced(key, UndoOperation::Update(inverse));
+    }
+
+    /// Record the body a request had before its body was set; consecutive edits of the
+    /// same body moments apart are undone together
+    pub fn record_body_update(&mut self, request_id: &str, previous_body: Option<RequestBody>) {
+        self.record_coalesced(
+            format!("{request_id}:body"),
+            UndoOperation::UpdateBody {
+                request_id: request_id.to_string(),
+                body: previous_body,
+            },
+        );
+    }
+
+    fn record_coalesced(&mut self, key: String, operation: UndoOperation) {
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut()
+            && last.coalesce_key.as_ref() == Some(&key)
+            && last.recorded_at.elapsed() < COALESCE_INTERVAL
+        {
+            last.recorded_at = Instant::now();
+            return;
+        }
+        let mut step = UndoStep::new(vec![operation]);
+        step.coalesce_key = Some(key);
+        Self::push(&mut self.undo, step);
+    }
+
+    /// Remove and return the operations to undo the last edit
+    pub fn take_undo(&mut self) -> Option<Vec<UndoOperation>> {
+        self.undo.pop().map(|step| step.operations)
+    }
+
+    /// Remove and return the operations to redo the last undone edit
+    pub fn take_redo(&mut self) -> Option<Vec<UndoOperation>> {
+        self.redo.pop().map(|step| step.operations)
+    }
+
+    /// Store the operations that revert an undo
+    pub fn push_redo(&mut self, operations: Vec<UndoOperation>) {
+        if !operations.is_empty() {
+            Self::push(&mut self.redo, UndoStep::new(operations));
+        }
+    }
+
+    /// Store the operations that revert a redo, without clearing what remains to redo
+    pub fn push_undo(&mut self, operations: Vec<UndoOperation>) {
+        if !operations.is_empty() {
+            Self::push(&mut self.undo, UndoStep::new(operations));
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn push(steps: &mut Vec<UndoStep>, step: UndoStep) {
+        steps.push(step);
+        if steps.len() > MAX_STEPS {
+            steps.remove(0);
+        }
+    }
+
+    fn get_update_key(update: &EntityUpdate) -> String {
+        match update {
+            EntityUpdate::Request(update) => update.id.clone(),
+            EntityUpdate::RequestGroup(update) => update.id.clone(),
+            EntityUpdate::Scenario(update) => update.id.clone(),
+            EntityUpdate::Authorization(update) => update.id.clone(),
+            EntityUpdate::Certificate(update) => update.id.clone(),
+            EntityUpdate::Proxy(update) => update.id.clone(),
+            EntityUpdate::DataSet(update) => update.id.clone(),
+            EntityUpdate::Defaults(..) => EntityType::Defaults.to_string(),
+            EntityUpdate::TrustedRoots(update) => update.id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apicize_lib::{Request, RequestGroup};
+
+    fn request(id: &str) -> RequestEntry {
+        RequestEntry::Request(Request {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn group(id: &str) -> RequestEntry {
+        RequestEntry::Group(RequestGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn restores_removed_group_with_descendants_in_place() {
+        let mut index = IndexedEntities::<RequestEntry>::default();
+        index.add_entity(request("a"), None, None).unwrap();
+        index.add_entity(group("g"), None, None).unwrap();
+        index
+            .add_entity(request("g1"), Some("g"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        index
+            .add_entity(request("g2"), Some("g"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        index.add_entity(request("b"), None, None).unwrap();
+        let original = index.clone();
+
+        let removed = RemovedEntities::capture(&index, "g").unwrap();
+        index.remove_entity("g").unwrap();
+        assert_eq!(index.top_level_ids, vec!["a", "b"]);
+        assert!(!index.entities.contains_key("g1"));
+
+        removed.restore(&mut index).unwrap();
+        assert_eq!(index.top_level_ids, original.top_level_ids);
+        assert_eq!(index.child_ids.get("g"), original.child_ids.get("g"));
+        assert_eq!(index.parent_ids.get("g2").map(|s| s.as_str()), Some("g"));
+    }
+
+    #[test]
+    fn anchors_to_sibling_or_parent() {
+        let mut index = IndexedEntities::<RequestEntry>::default();
+        index.add_entity(group("g"), None, None).unwrap();
+        index
+            .add_entity(request("g1"), Some("g"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        assert!(matches!(
+            find_anchor(&index, "g1"),
+            Some((id, IndexedEntityPosition::Under)) if id == "g"
+        ));
+        index
+            .add_entity(
+                request("g0"),
+                Some("g1"),
+                Some(IndexedEntityPosition::Before),
+            )
+            .unwrap();
+        assert!(matches!(
+            find_anchor(&index, "g0"),
+            Some((id, IndexedEntityPosition::Before)) if id == "g1"
+        ));
+        assert!(matches!(
+            find_anchor(&index, "g1"),
+            Some((id, IndexedEntityPosition::After)) if id == "g0"
+        ));
+        assert!(find_anchor(&index, "g").is_none());
+    }
+
+    #[test]
+    fn coalesces_consecutive_body_edits() {
+        let body = |data: &str| {
+            Some(RequestBody::Text {
+                data: data.to_string(),
+            })
+        };
+        let mut history = UndoHistory::default();
+        history.record_body_update("r", body("original"));
+        history.record_body_update("r", body("edited"));
+        history.record_body_update("other", None);
+
+        assert!(matches!(
+            history.take_undo().as_deref(),
+            Some([UndoOperation::UpdateBody { request_id, body: None }]) if request_id == "other"
+        ));
+        assert!(matches!(
+            history.take_undo().as_deref(),
+            Some([UndoOperation::UpdateBody { body: Some(RequestBody::Text { data }), .. }])
+                if data == "original"
+        ));
+        assert!(!history.can_undo());
+    }
+}
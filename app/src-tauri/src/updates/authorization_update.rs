@@ -1,7 +1,10 @@
 use apicize_lib::{Selection, authorization::AuthorizationPlain};
 use serde::{Deserialize, Serialize};
 
-use crate::workspaces::EntityType;
+use crate::{
+    authorization_settings::ExtendedAuthorization, oauth2_grants::JwtSigningAlgorithm,
+    workspaces::EntityType,
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub enum AuthorizationUpdateType {
@@ -9,6 +12,11 @@ pub enum AuthorizationUpdateType {
     OAuth2Client,
     OAuth2Pkce,
     ApiKey,
+    AwsSigV4,
+    Digest,
+    OAuth2Device,
+    OAuth2Password,
+    OAuth2JwtBearer,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
@@ -31,6 +39,8 @@ pub struct AuthorizationUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_authorization_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authorize_url: Option<String>,
@@ -49,25 +59,45 @@ pub struct AuthorizationUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub send_credentials_in_body: Option<Option<bool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_certificate: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<JwtSigningAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assertion_audience: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assertion_lifetime: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub validation_warnings: Option<Vec<String>>,
 }
 
 impl AuthorizationUpdate {
-    pub fn from_selections(
-        id: &str,
-        selected_certificate: &Selection,
-        selected_proxy: &Selection,
-    ) -> Self {
+    /// Return an update for the specified authorization that does not change any values
+    pub fn new(id: &str) -> Self {
         AuthorizationUpdate {
             id: id.to_string(),
             entity_type: EntityType::Authorization,
-            encrypted: Some(false),
+            encrypted: None,
             name: None,
             auth_type: None,
             username: None,
             password: None,
             header: None,
             value: None,
+            device_authorization_url: None,
             access_token_url: None,
             authorize_url: None,
             client_id: None,
@@ -75,10 +105,95 @@ impl AuthorizationUpdate {
             audience: None,
             scope: None,
             send_credentials_in_body: None,
+            access_key_id: None,
+            secret_access_key: None,
+            session_token: None,
+            region: None,
+            service: None,
+            signing_certificate: None,
+            algorithm: None,
+            issuer: None,
+            subject: None,
+            assertion_audience: None,
+            assertion_lifetime: None,
+            selected_certificate: None,
+            selected_proxy: None,
+            validation_warnings: None,
+        }
+    }
+
+    pub fn from_selections(
+        id: &str,
+        selected_certificate: &Selection,
+        selected_proxy: &Selection,
+    ) -> Self {
+        AuthorizationUpdate {
+            encrypted: Some(false),
             selected_certificate: Some(selected_certificate.clone()),
             selected_proxy: Some(selected_proxy.clone()),
-            validation_warnings: None,
+            ..Self::new(id)
+        }
+    }
+
+    /// Replace placeholder values with those of an extended authorization
+    pub fn with_settings(mut self, settings: &ExtendedAuthorization) -> Self {
+        self.auth_type = Some(settings.get_update_type());
+        self.header = None;
+        self.value = None;
+        match settings {
+            ExtendedAuthorization::AwsSigV4(parameters) => {
+                self.access_key_id = Some(parameters.access_key_id.clone());
+                self.secret_access_key = Some(parameters.secret_access_key.clone());
+                self.session_token = Some(parameters.session_token.clone());
+                self.region = Some(parameters.region.clone());
+                self.service = Some(parameters.service.clone());
+            }
+            ExtendedAuthorization::Digest(parameters) => {
+                self.username = Some(parameters.username.clone());
+                self.password = Some(parameters.password.clone());
+            }
+            ExtendedAuthorization::OAuth2Device(parameters) => {
+                self.authorize_url = None;
+                self.send_credentials_in_body = None;
+                self.device_authorization_url = Some(parameters.device_authorization_url.clone());
+                self.access_token_url = Some(parameters.access_token_url.clone());
+                self.client_id = Some(parameters.client_id.clone());
+                self.client_secret = Some(parameters.client_secret.clone());
+                self.scope = Some(parameters.scope.clone());
+                self.audience = Some(parameters.audience.clone());
+                self.selected_certificate = Some(parameters.selected_certificate.clone());
+                self.selected_proxy = Some(parameters.selected_proxy.clone());
+            }
+            ExtendedAuthorization::OAuth2Password(parameters) => {
+                self.authorize_url = None;
+                self.access_token_url = Some(parameters.access_token_url.clone());
+                self.client_id = Some(parameters.client_id.clone());
+                self.client_secret = Some(parameters.client_secret.clone());
+                self.username = Some(parameters.username.clone());
+                self.password = Some(parameters.password.clone());
+                self.scope = Some(parameters.scope.clone());
+                self.audience = Some(parameters.audience.clone());
+                self.send_credentials_in_body = Some(parameters.send_credentials_in_body);
+                self.selected_certificate = Some(parameters.selected_certificate.clone());
+                self.selected_proxy = Some(parameters.selected_proxy.clone());
+            }
+            ExtendedAuthorization::OAuth2JwtBearer(parameters) => {
+                self.authorize_url = None;
+                self.send_credentials_in_body = None;
+                self.access_token_url = Some(parameters.access_token_url.clone());
+                self.client_id = Some(parameters.client_id.clone());
+                self.scope = Some(parameters.scope.clone());
+                self.signing_certificate = Some(parameters.signing_certificate.clone());
+                self.algorithm = Some(parameters.algorithm);
+                self.issuer = Some(parameters.issuer.clone());
+                self.subject = Some(parameters.subject.clone());
+                self.assertion_audience = Some(parameters.assertion_audience.clone());
+                self.assertion_lifetime = Some(parameters.assertion_lifetime);
+                self.selected_certificate = Some(parameters.selected_certificate.clone());
+                self.selected_proxy = Some(parameters.selected_proxy.clone());
+            }
         }
+        self
     }
 }
 
@@ -102,6 +217,7 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 password: Some(password),
                 header: None,
                 value: None,
+                device_authorization_url: None,
                 access_token_url: None,
                 authorize_url: None,
                 client_id: None,
@@ -111,6 +227,17 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 selected_certificate: None,
                 selected_proxy: None,
                 send_credentials_in_body: None,
+                access_key_id: None,
+                secret_access_key: None,
+                session_token: None,
+                region: None,
+                service: None,
+                signing_certificate: None,
+                algorithm: None,
+                issuer: None,
+                subject: None,
+                assertion_audience: None,
+                assertion_lifetime: None,
                 validation_warnings: Some(validation_warnings.unwrap_or_default()),
             },
             AuthorizationPlain::OAuth2Client {
@@ -136,6 +263,7 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 password: None,
                 header: None,
                 value: None,
+                device_authorization_url: None,
                 access_token_url: Some(access_token_url),
                 authorize_url: None,
                 client_id: Some(client_id),
@@ -145,6 +273,17 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 selected_certificate: Some(selected_certificate),
                 selected_proxy: Some(selected_proxy),
                 send_credentials_in_body: Some(send_credentials_in_body),
+                access_key_id: None,
+                secret_access_key: None,
+                session_token: None,
+                region: None,
+                service: None,
+                signing_certificate: None,
+                algorithm: None,
+                issuer: None,
+                subject: None,
+                assertion_audience: None,
+                assertion_lifetime: None,
                 validation_warnings: Some(validation_warnings.unwrap_or_default()),
             },
             AuthorizationPlain::OAuth2Pkce {
@@ -167,6 +306,7 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 password: None,
                 header: None,
                 value: None,
+                device_authorization_url: None,
                 access_token_url: Some(access_token_url),
                 authorize_url: Some(authorize_url),
                 client_id: Some(client_id),
@@ -176,6 +316,17 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 selected_certificate: None,
                 selected_proxy: None,
                 send_credentials_in_body: Some(send_credentials_in_body),
+                access_key_id: None,
+                secret_access_key: None,
+                session_token: None,
+                region: None,
+                service: None,
+                signing_certificate: None,
+                algorithm: None,
+                issuer: None,
+                subject: None,
+                assertion_audience: None,
+                assertion_lifetime: None,
                 validation_warnings: Some(validation_warnings.unwrap_or_default()),
             },
             AuthorizationPlain::ApiKey {
@@ -195,6 +346,7 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 password: None,
                 header: Some(header),
                 value: Some(value),
+                device_authorization_url: None,
                 access_token_url: None,
                 authorize_url: None,
                 client_id: None,
@@ -204,6 +356,17 @@ impl From<AuthorizationPlain> for AuthorizationUpdate {
                 selected_certificate: None,
                 selected_proxy: None,
                 send_credentials_in_body: None,
+                access_key_id: None,
+                secret_access_key: None,
+                session_token: None,
+                region: None,
+                service: None,
+                signing_certificate: None,
+                algorithm: None,
+                issuer: None,
+                subject: None,
+                assertion_audience: None,
+                assertion_lifetime: None,
                 validation_warnings: Some(validation_warnings.unwrap_or_default()),
             },
         }
@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+
+use apicize_lib::{
+    ExecutionConcurrency, IndexedEntities, NameValuePair, Request, RequestEntry, Selection,
+};
+use serde::{Deserialize, Serialize};
+
+use super::RequestUpdate;
+use crate::{error::ApicizeAppError, workspaces::EntityType};
+
+/// Values to apply to several requests at once
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkRequestUpdate {
+    /// Values set on each request
+    #[serde(default)]
+    pub values: BulkRequestValues,
+    /// Requests to update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_ids: Option<Vec<String>>,
+    /// Group whose descendant requests are updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// Headers to add to each request, replacing any header with the same name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_headers: Option<Vec<NameValuePair>>,
+    /// Names of headers to remove from each request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove_headers: Option<Vec<String>>,
+}
+
+/// Request values that can be set by a bulk update; values not specified are left as they
+/// are on each request
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkRequestValues {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runs: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_run_execution: Option<ExecutionConcurrency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_invalid_certs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_redirects: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_scenario: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_authorization: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_certificate: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_proxy: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_data: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_trusted_roots: Option<Selection>,
+}
+
+impl BulkRequestUpdate {
+    /// Return the IDs of the requests to update, without duplicates, including requests
+    /// within the group and its descendant groups
+    pub fn get_request_ids(
+        &self,
+        requests: &IndexedEntities<RequestEntry>,
+    ) -> Result<Vec<String>, ApicizeAppError> {
+        let mut request_ids = Vec::<String>::new();
+        let mut included = HashSet::<String>::new();
+        for id in self.request_ids.iter().flatten() {
+            if included.insert(id.clone()) {
+                request_ids.push(id.clone());
+            }
+        }
+
+        if let Some(group_id) = &self.group_id {
+            if !matches!(
+                requests.entities.get(group_id),
+                Some(RequestEntry::Group(..))
+            ) {
+                return Err(ApicizeAppError::InvalidGroup(group_id.to_string()));
+            }
+            let mut to_process = vec![group_id.to_string()];
+            while let Some(id) = to_process.pop() {
+                match requests.child_ids.get(&id) {
+                    Some(child_ids) => to_process.extend(child_ids.iter().rev().cloned()),
+                    None => {
+                        if let Some(RequestEntry::Request(..)) = requests.entities.get(&id)
+                            && included.insert(id.clone())
+                        {
+                            request_ids.push(id);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(request_ids)
+    }
+
+    /// Return the update to apply to the specified request
+    pub fn for_request(&self, request: &Request) -> RequestUpdate {
+        let values = self.values.clone();
+        let headers = if self.add_headers.is_some() || self.remove_headers.is_some() {
+            let mut headers = request.headers.clone().unwrap_or_default();
+            let added = self.add_headers.as_deref().unwrap_or_default();
+            headers.retain(|header| {
+                !self
+                    .remove_headers
+                    .iter()
+                    .flatten()
+                    .chain(added.iter().map(|added| &added.name))
+                    .any(|name| name.eq_ignore_ascii_case(&header.name))
+            });
+            headers.extend(added.iter().cloned());
+            Some(headers)
+        } else {
+            None
+        };
+
+        RequestUpdate {
+            id: request.id.clone(),
+            entity_type: EntityType::Request,
+            name: None,
+            disabled: values.disabled,
+            key: None,
+            url: None,
+            method: values.method,
+            runs: values.runs,
+            multi_run_execution: values.multi_run_execution,
+            timeout: values.timeout,
+            keep_alive: values.keep_alive,
+            accept_invalid_certs: values.accept_invalid_certs,
+            number_of_redirects: values.number_of_redirects,
+            query_string_params: None,
+            headers,
+            test: None,
+            body: None,
+            body_mime_type: None,
+            body_length: None,
+            selected_scenario: values.selected_scenario,
+            selected_authorization: values.selected_authorization,
+            selected_certificate: values.selected_certificate,
+            selected_proxy: values.selected_proxy,
+            selected_data: values.selected_data,
+            selected_trusted_roots: values.selected_trusted_roots,
+            validation_warnings: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apicize_lib::{
+        ParameterLockStatus, RequestGroup, WorkbookDefaultParameters, Workspace,
+        editing::indexed_entities::IndexedEntityPosition,
+    };
+
+    use super::*;
+    use crate::{extensions::WorkbookExtensions, workspaces::Workspaces};
+
+    fn header(name: &str, value: &str) -> NameValuePair {
+        NameValuePair {
+            name: name.to_string(),
+            value: value.to_string(),
+            disabled: None,
+        }
+    }
+
+    fn request(id: &str) -> RequestEntry {
+        RequestEntry::Request(Request {
+            id: id.to_string(),
+            name: id.to_string(),
+            url: format!("https://localhost/{id}"),
+            timeout: Some(1000),
+            headers: Some(vec![header("Accept", "text/plain")]),
+            ..Default::default()
+        })
+    }
+
+    fn group(id: &str) -> RequestEntry {
+        RequestEntry::Group(RequestGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Requests "a", "b" and "c", where "b" is in group "g" and "c" is in group "g2"
+    /// within "g"
+    fn requests() -> IndexedEntities<RequestEntry> {
+        let mut requests = IndexedEntities::<RequestEntry>::default();
+        requests.add_entity(request("a"), None, None).unwrap();
+        requests.add_entity(group("g"), None, None).unwrap();
+        requests
+            .add_entity(request("b"), Some("g"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        requests
+            .add_entity(group("g2"), Some("g"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        requests
+            .add_entity(request("c"), Some("g2"), Some(IndexedEntityPosition::Under))
+            .unwrap();
+        requests
+    }
+
+    fn bulk_update(request_ids: &[&str], group_id: Option<&str>) -> BulkRequestUpdate {
+        BulkRequestUpdate {
+            values: BulkRequestValues {
+                timeout: Some(5000),
+                ..Default::default()
+            },
+            request_ids: Some(request_ids.iter().map(|id| id.to_string()).collect()),
+            group_id: group_id.map(|id| id.to_string()),
+            add_headers: None,
+            remove_headers: None,
+        }
+    }
+
+    #[test]
+    fn adds_and_removes_headers() {
+        let RequestEntry::Request(request) = request("a") else {
+            unreachable!()
+        };
+        let mut request = request;
+        request.headers = Some(vec![
+            header("Accept", "text/plain"),
+            header("X-Trace", "1"),
+            header("X-Remove", "1"),
+        ]);
+
+        let update = BulkRequestUpdate {
+            add_headers: Some(vec![header("accept", "application/json")]),
+            remove_headers: Some(vec!["x-remove".to_string()]),
+            ..bulk_update(&["a"], None)
+        }
+        .for_request(&request);
+
+        let headers = update.headers.unwrap();
+        assert_eq!(
+            headers
+                .iter()
+                .map(|h| (h.name.as_str(), h.value.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("X-Trace", "1"), ("accept", "application/json")]
+        );
+        assert_eq!(update.timeout, Some(5000));
+        assert!(update.url.is_none() && update.body.is_none());
+
+        let update = bulk_update(&["a"], None).for_request(&request);
+        assert!(update.headers.is_none(), "headers are left unchanged");
+    }
+
+    #[test]
+    fn collects_nested_descendants_without_duplicates() {
+        let requests = requests();
+        assert_eq!(
+            bulk_update(&["c", "a", "c"], Some("g"))
+                .get_request_ids(&requests)
+                .unwrap(),
+            vec!["c", "a", "b"]
+        );
+        assert!(
+            bulk_update(&[], Some("a"))
+                .get_request_ids(&requests)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn undoes_bulk_update_as_one_step() {
+        let mut workspaces = Workspaces::default();
+        let workspace = Workspace {
+            requests: requests(),
+            scenarios: IndexedEntities::default(),
+            authorizations: IndexedEntities::default(),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters::default(),
+            private_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        };
+        let workspace_id = workspaces
+            .add_workspace(workspace, WorkbookExtensions::default(), "", false)
+            .workspace_id;
+        let get_timeouts = |workspaces: &Workspaces| {
+            ["a", "b", "c"]
+                .iter()
+                .map(|id| {
+                    match &workspaces
+                        .get_workspace(&workspace_id)
+                        .unwrap()
+                        .requests
+                        .entities[*id]
+                    {
+                        RequestEntry::Request(request) => request.timeout,
+                        RequestEntry::Group(_) => None,
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let results = workspaces
+            .bulk_update_requests(&workspace_id, &bulk_update(&["a"], Some("g")))
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(get_timeouts(&workspaces), vec![Some(5000); 3]);
+
+        assert!(workspaces.undo(&workspace_id).unwrap().is_some());
+        assert_eq!(get_timeouts(&workspaces), vec![Some(1000); 3]);
+        assert!(workspaces.undo(&workspace_id).unwrap().is_none());
+    }
+}
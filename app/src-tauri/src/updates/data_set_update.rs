@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
-use apicize_lib::DataSourceType;
+use apicize_lib::{DataSet, DataSourceType};
 use serde::{Deserialize, Serialize};
 
-use crate::workspaces::EntityType;
+use crate::workspaces::{DataSetContent, EntityType};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -23,3 +23,22 @@ pub struct DataSetUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub csv_rows: Option<Vec<HashMap<String, String>>>,
 }
+
+impl DataSetUpdate {
+    /// Return an update that sets the data set's source and any loaded content
+    pub fn from_data_set(data_set: &DataSet, content: Option<&DataSetContent>) -> Self {
+        let is_file = data_set.source_type != DataSourceType::JSON;
+        DataSetUpdate {
+            id: data_set.id.clone(),
+            entity_type: EntityType::DataSet,
+            name: Some(data_set.name.clone()),
+            source_type: Some(data_set.source_type.clone()),
+            source_file_name: is_file.then(|| data_set.source.clone()),
+            source_text: content
+                .and_then(|c| c.source_text.clone())
+                .or_else(|| (!is_file).then(|| data_set.source.clone())),
+            csv_columns: content.and_then(|c| c.csv_columns.clone()),
+            csv_rows: content.and_then(|c| c.csv_rows.clone()),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use apicize_lib::Selection;
+use apicize_lib::{Selection, WorkbookDefaultParameters};
 use serde::{Deserialize, Serialize};
 
 use crate::workspaces::EntityType;
@@ -18,5 +18,26 @@ pub struct DefaultsUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub selected_data: Option<Selection>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_trusted_roots: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub validation_warnings: Option<Vec<String>>,
 }
+
+impl DefaultsUpdate {
+    /// Return an update that sets each of the default selections
+    pub fn from_defaults(
+        defaults: &WorkbookDefaultParameters,
+        selected_trusted_roots: Selection,
+    ) -> Self {
+        DefaultsUpdate {
+            entity_type: EntityType::Defaults,
+            selected_scenario: Some(defaults.selected_scenario.clone()),
+            selected_authorization: Some(defaults.selected_authorization.clone()),
+            selected_certificate: Some(defaults.selected_certificate.clone()),
+            selected_proxy: Some(defaults.selected_proxy.clone()),
+            selected_data: Some(defaults.selected_data.clone()),
+            selected_trusted_roots: Some(selected_trusted_roots),
+            validation_warnings: Some(defaults.validation_warnings.clone().unwrap_or_default()),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 pub mod authorization_update;
+pub mod bulk_request_update;
 pub mod certificate_update;
 pub mod data_set_update;
 pub mod defaults_update;
@@ -8,8 +9,10 @@ pub mod proxy_update;
 pub mod request_group_update;
 pub mod request_update;
 pub mod scenario_update;
+pub mod trusted_roots_update;
 
 pub use authorization_update::*;
+pub use bulk_request_update::*;
 pub use certificate_update::*;
 pub use data_set_update::*;
 pub use defaults_update::*;
@@ -17,7 +20,9 @@ pub use proxy_update::*;
 pub use request_group_update::*;
 pub use request_update::*;
 pub use scenario_update::*;
+pub use trusted_roots_update::*;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 #[serde(tag = "type")]
 pub enum EntityUpdate {
@@ -29,4 +34,5 @@ pub enum EntityUpdate {
     Proxy(ProxyUpdate),
     DataSet(DataSetUpdate),
     Defaults(DefaultsUpdate),
+    TrustedRoots(TrustedRootsUpdate),
 }
@@ -1,7 +1,10 @@
 use apicize_lib::proxy::ProxyPlain;
 use serde::{Deserialize, Serialize};
 
-use crate::workspaces::EntityType;
+use crate::{
+    proxy_settings::{ProxyCredentials, ProxyMode, ProxyScheme, ProxySettings},
+    workspaces::EntityType,
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -14,6 +17,20 @@ pub struct ProxyUpdate {
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ProxyMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pac_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<ProxyScheme>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_dns: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
 }
 
 impl From<ProxyPlain> for ProxyUpdate {
@@ -24,6 +41,37 @@ impl From<ProxyPlain> for ProxyUpdate {
             encrypted: Some(false),
             name: Some(value.name),
             url: Some(value.url),
+            mode: None,
+            pac_file: None,
+            scheme: None,
+            remote_dns: None,
+            no_proxy: None,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+impl ProxyUpdate {
+    /// Include settings stored in workbook extensions, credentials are only included
+    /// if they are not encrypted
+    pub fn with_settings(mut self, settings: &ProxySettings) -> Self {
+        self.mode = Some(settings.mode);
+        self.pac_file = Some(settings.pac_file.clone().unwrap_or_default());
+        self.scheme = Some(settings.scheme);
+        self.remote_dns = Some(settings.remote_dns);
+        self.no_proxy = Some(settings.no_proxy.clone());
+        match &settings.credentials {
+            Some(ProxyCredentials::Plain { username, password }) => {
+                self.username = Some(username.clone());
+                self.password = Some(password.clone());
+            }
+            Some(ProxyCredentials::Cipher { .. }) => {}
+            None => {
+                self.username = Some(String::default());
+                self.password = Some(String::default());
+            }
         }
+        self
     }
 }
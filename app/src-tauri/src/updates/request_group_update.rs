@@ -33,10 +33,34 @@ pub struct RequestGroupUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub selected_data: Option<Selection>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_trusted_roots: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub validation_warnings: Option<Vec<String>>,
 }
 
 impl RequestGroupUpdate {
+    /// Return an update that sets every editable value of the group
+    pub fn from_group(group: &RequestGroup, selected_trusted_roots: Selection) -> Self {
+        RequestGroupUpdate {
+            id: group.id.to_string(),
+            entity_type: EntityType::Group,
+            name: Some(group.name.clone()),
+            disabled: Some(group.disabled),
+            key: Some(group.key.clone().unwrap_or_default()),
+            runs: Some(group.runs),
+            multi_run_execution: Some(group.multi_run_execution.clone()),
+            execution: Some(group.execution.clone()),
+            setup: Some(group.setup.clone().unwrap_or_default()),
+            selected_scenario: Some(group.selected_scenario.clone()),
+            selected_authorization: Some(group.selected_authorization.clone()),
+            selected_certificate: Some(group.selected_certificate.clone()),
+            selected_proxy: Some(group.selected_proxy.clone()),
+            selected_data: Some(group.selected_data.clone()),
+            selected_trusted_roots: Some(selected_trusted_roots),
+            validation_warnings: Some(group.validation_warnings.clone().unwrap_or_default()),
+        }
+    }
+
     pub fn from_selections(group: &RequestGroup) -> Self {
         RequestGroupUpdate {
             id: group.id.to_string(),
@@ -53,6 +77,7 @@ impl RequestGroupUpdate {
             selected_certificate: Some(group.selected_certificate.clone()),
             selected_proxy: Some(group.selected_proxy.clone()),
             selected_data: Some(group.selected_data.clone()),
+            selected_trusted_roots: None,
             validation_warnings: None,
         }
     }
@@ -53,6 +53,8 @@ pub struct RequestUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub selected_data: Option<Selection>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_trusted_roots: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub validation_warnings: Option<Vec<String>>,
 }
 
@@ -83,10 +85,44 @@ impl RequestUpdate {
             selected_certificate: None,
             selected_proxy: None,
             selected_data: None,
+            selected_trusted_roots: None,
             validation_warnings: None,
         }
     }
 
+    /// Return an update that sets every editable value of the request, other than its body
+    /// (which is updated separately)
+    pub fn from_request(request: &Request, selected_trusted_roots: Selection) -> Self {
+        RequestUpdate {
+            id: request.id.to_string(),
+            entity_type: EntityType::Request,
+            name: Some(request.name.clone()),
+            disabled: Some(request.disabled),
+            key: Some(request.key.clone().unwrap_or_default()),
+            url: Some(request.url.clone()),
+            method: request.method.clone(),
+            runs: Some(request.runs),
+            multi_run_execution: Some(request.multi_run_execution.clone()),
+            timeout: Some(request.timeout.unwrap_or_default()),
+            keep_alive: Some(request.keep_alive),
+            accept_invalid_certs: Some(request.accept_invalid_certs),
+            number_of_redirects: Some(request.number_of_redirects),
+            query_string_params: Some(request.query_string_params.clone().unwrap_or_default()),
+            headers: Some(request.headers.clone().unwrap_or_default()),
+            test: Some(request.test.clone().unwrap_or_default()),
+            body: None,
+            body_mime_type: None,
+            body_length: None,
+            selected_scenario: Some(request.selected_scenario.clone()),
+            selected_authorization: Some(request.selected_authorization.clone()),
+            selected_certificate: Some(request.selected_certificate.clone()),
+            selected_proxy: Some(request.selected_proxy.clone()),
+            selected_data: Some(request.selected_data.clone()),
+            selected_trusted_roots: Some(selected_trusted_roots),
+            validation_warnings: Some(request.validation_warnings.clone().unwrap_or_default()),
+        }
+    }
+
     pub fn from_selections(request: &Request) -> Self {
         RequestUpdate {
             id: request.id.to_string(),
@@ -113,6 +149,7 @@ impl RequestUpdate {
             selected_certificate: Some(request.selected_certificate.clone()),
             selected_proxy: Some(request.selected_proxy.clone()),
             selected_data: Some(request.selected_data.clone()),
+            selected_trusted_roots: None,
             validation_warnings: None,
         }
     }
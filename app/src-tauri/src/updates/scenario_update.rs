@@ -1,7 +1,7 @@
 use apicize_lib::{ScenarioPlain, Variable};
 use serde::{Deserialize, Serialize};
 
-use crate::workspaces::EntityType;
+use crate::{secret_variables::ScenarioSecretVariables, workspaces::EntityType};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -14,6 +14,9 @@ pub struct ScenarioUpdate {
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variables: Option<Vec<Variable>>,
+    /// Variables resolved at execution, indexed by variable name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_variables: Option<ScenarioSecretVariables>,
 }
 
 impl From<ScenarioPlain> for ScenarioUpdate {
@@ -24,6 +27,15 @@ impl From<ScenarioPlain> for ScenarioUpdate {
             encrypted: Some(false),
             name: Some(value.name),
             variables: Some(value.variables.unwrap_or_default()),
+            secret_variables: None,
         }
     }
 }
+
+impl ScenarioUpdate {
+    /// Include secret variable references
+    pub fn with_secret_variables(mut self, secrets: Option<&ScenarioSecretVariables>) -> Self {
+        self.secret_variables = Some(secrets.cloned().unwrap_or_default());
+        self
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{trusted_roots::TrustedRoots, workspaces::EntityType};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedRootsUpdate {
+    pub id: String,
+    pub entity_type: EntityType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pem: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_system_roots: Option<bool>,
+}
+
+impl From<TrustedRoots> for TrustedRootsUpdate {
+    fn from(value: TrustedRoots) -> Self {
+        TrustedRootsUpdate {
+            id: value.id,
+            entity_type: EntityType::TrustedRoots,
+            name: Some(value.name),
+            pem: Some(value.pem),
+            include_system_roots: Some(value.include_system_roots),
+        }
+    }
+}
@@ -0,0 +1,742 @@
+//! Storage of a workbook as a directory of files, so that changes to different requests
+//! and parameters merge cleanly in source control.
+//!
+//! The workbook file remains the entry point, storing defaults and the order of entries,
+//! while requests, groups, scenarios, authorizations, certificates, proxies and data sets
+//! are each stored in their own file in a directory alongside it.  Requests are stored in
+//! a folder tree mirroring the navigation hierarchy, where each group is a folder
+//! containing the group's settings (`_group.json`) and its children.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use apicize_lib::{
+    ApicizeError, DataSet, Identifiable, IndexedEntities, PersistedIndex, RequestEntry, Scenario,
+    StoredRequestEntry, Validated, WorkbookDefaultParameters, Workspace, open_data_file,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned, de::IgnoredAny};
+use serde_json::ser::PrettyFormatter;
+
+const REQUESTS_FOLDER: &str = "requests";
+const SCENARIOS_FOLDER: &str = "scenarios";
+const AUTHORIZATIONS_FOLDER: &str = "authorizations";
+const CERTIFICATES_FOLDER: &str = "certificates";
+const PROXIES_FOLDER: &str = "proxies";
+const DATA_FOLDER: &str = "data";
+const GROUP_FILE_NAME: &str = "_group.json";
+
+/// Format a workbook is saved in
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkbookFormat {
+    /// All requests and workbook parameters in the workbook file
+    #[default]
+    File,
+    /// Requests and workbook parameters in their own files, in a directory alongside the
+    /// workbook file
+    Directory,
+}
+
+impl WorkbookFormat {
+    /// Return the format of the workbook saved at the specified path
+    pub fn detect(workbook_path: &Path) -> Result<WorkbookFormat, ApicizeError> {
+        let probe = open_data_file::<FormatProbe>(&workbook_path.to_path_buf())?.data;
+        Ok(if probe.directory.is_some() {
+            WorkbookFormat::Directory
+        } else {
+            WorkbookFormat::File
+        })
+    }
+}
+
+/// Used to check whether a workbook file is the index of a directory workbook
+#[derive(Deserialize)]
+struct FormatProbe {
+    #[serde(default)]
+    directory: Option<IgnoredAny>,
+}
+
+/// Used to read the layout of a directory workbook before saving over it
+#[derive(Deserialize)]
+struct LayoutProbe {
+    #[serde(default)]
+    directory: Option<DirectoryLayout>,
+}
+
+/// Workbook file of a workbook saved as a directory, which remains a valid (empty)
+/// workbook with defaults
+#[derive(Serialize, Deserialize)]
+struct WorkbookIndex {
+    /// Version of workbook format (should not be changed manually)
+    version: f32,
+    /// Always empty, requests are stored in the directory
+    requests: Vec<StoredRequestEntry>,
+    /// Workbook defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    defaults: Option<WorkbookDefaultParameters>,
+    /// Order of the files stored in the directory
+    directory: DirectoryLayout,
+}
+
+/// Order of files in a directory workbook, relative to the folder of each type of entry;
+/// files not listed (i.e. added when merging) are ordered after listed ones by name
+#[derive(Serialize, Deserialize, Default)]
+struct DirectoryLayout {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requests: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scenarios: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    authorizations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    certificates: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    proxies: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    data: Vec<String>,
+}
+
+/// Return the name of the directory storing entries of the specified workbook
+pub fn get_directory_name(workbook_path: &Path) -> PathBuf {
+    let mut directory = PathBuf::from(workbook_path);
+    directory.set_extension("apicize-dir");
+    directory
+}
+
+/// Replace the workbook entries of a workspace opened from the workbook file at the
+/// specified path with those stored in its directory
+pub fn open_directory(workspace: &mut Workspace, workbook_path: &Path) -> Result<(), ApicizeError> {
+    let index = open_data_file::<WorkbookIndex>(&workbook_path.to_path_buf())?.data;
+    let directory = get_directory_name(workbook_path);
+    let layout = index.directory;
+
+    let requests = read_requests(
+        &directory.join(REQUESTS_FOLDER),
+        "",
+        &get_positions(&layout.requests),
+    )?
+    .into_iter()
+    .map(RequestEntry::from)
+    .collect::<Vec<RequestEntry>>();
+    workspace.requests = IndexedEntities::<RequestEntry>::new(&requests);
+
+    workspace.scenarios = replace_workbook(
+        &workspace.scenarios,
+        read_entities(&directory.join(SCENARIOS_FOLDER), &layout.scenarios)?,
+    );
+    workspace.authorizations = replace_workbook(
+        &workspace.authorizations,
+        read_entities(
+            &directory.join(AUTHORIZATIONS_FOLDER),
+            &layout.authorizations,
+        )?,
+    );
+    workspace.certificates = replace_workbook(
+        &workspace.certificates,
+        read_entities(&directory.join(CERTIFICATES_FOLDER), &layout.certificates)?,
+    );
+    workspace.proxies = replace_workbook(
+        &workspace.proxies,
+        read_entities(&directory.join(PROXIES_FOLDER), &layout.proxies)?,
+    );
+    let data = read_entities::<DataSet>(&directory.join(DATA_FOLDER), &layout.data)?;
+    workspace.data = IndexedEntities::<DataSet>::new(Some(data).filter(|d| !d.is_empty()));
+
+    // Selections were validated before entries were loaded, so restore them
+    workspace.defaults = index.defaults.unwrap_or_default();
+    workspace.perform_all_validations();
+    Ok(())
+}
+
+/// Save the workbook entries of a workspace to the directory of the workbook file at the
+/// specified path, along with the workbook file itself.  Only files whose content changed
+/// are written, and files listed by the previous save that were not written again are
+/// removed; other files in the directory are left as they are
+pub fn save_directory(workspace: &Workspace, workbook_path: &Path) -> Result<(), ApicizeError> {
+    // A workbook file that cannot be read as a directory workbook lists no files to remove
+    let previous_layout = if workbook_path.is_file() {
+        open_data_file::<LayoutProbe>(&workbook_path.to_path_buf())
+            .ok()
+            .and_then(|probe| probe.data.directory)
+            .unwrap_or_default()
+    } else {
+        DirectoryLayout::default()
+    };
+
+    let mut writer = DirectoryWriter {
+        directory: get_directory_name(workbook_path),
+        written: HashSet::new(),
+    };
+
+    let requests = workspace
+        .requests
+        .to_entities()
+        .into_iter()
+        .map(StoredRequestEntry::from)
+        .collect::<Vec<StoredRequestEntry>>();
+    let mut layout = DirectoryLayout::default();
+    writer.write_requests("", requests, &mut layout.requests)?;
+
+    let scenarios = workspace
+        .scenarios
+        .get_workbook()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|scenario| match scenario {
+            Scenario::Plain(mut plain) => {
+                clear_validation(plain.as_mut());
+                Scenario::Plain(plain)
+            }
+            cipher => cipher,
+        })
+        .collect::<Vec<Scenario>>();
+    layout.scenarios = writer.write_entities(SCENARIOS_FOLDER, &scenarios, |s| s.get_name())?;
+    layout.authorizations = writer.write_entities(
+        AUTHORIZATIONS_FOLDER,
+        &get_stored(workspace.authorizations.get_workbook()),
+        |a| a.get_name(),
+    )?;
+    layout.certificates = writer.write_entities(
+        CERTIFICATES_FOLDER,
+        &get_stored(workspace.certificates.get_workbook()),
+        |c| c.get_name(),
+    )?;
+    layout.proxies = writer.write_entities(
+        PROXIES_FOLDER,
+        &get_stored(workspace.proxies.get_workbook()),
+        |p| p.get_name(),
+    )?;
+
+    let data = workspace
+        .data
+        .top_level_ids
+        .iter()
+        .filter_map(|id| workspace.data.entities.get(id))
+        .map(|data_set| {
+            let mut data_set = data_set.clone();
+            data_set.source_error = None;
+            data_set.validation_warnings = None;
+            data_set.validation_errors = None;
+            data_set
+        })
+        .collect::<Vec<DataSet>>();
+    layout.data = writer.write_entities(DATA_FOLDER, &data, |d| &d.name)?;
+
+    writer.remove_stale_files(&previous_layout)?;
+
+    let defaults = if workspace.defaults.any_values_set() {
+        let mut defaults = workspace.defaults.clone();
+        clear_validation(&mut defaults);
+        Some(defaults)
+    } else {
+        None
+    };
+    write_file(
+        workbook_path,
+        &WorkbookIndex {
+            version: 1.0,
+            requests: Vec::default(),
+            defaults,
+            directory: layout,
+        },
+    )
+}
+
+/// Tracks files written when saving a directory workbook
+struct DirectoryWriter {
+    directory: PathBuf,
+    written: HashSet<PathBuf>,
+}
+
+impl DirectoryWriter {
+    /// Write requests to the specified folder (relative to the requests folder), storing
+    /// groups as folders, and appending the files written to `order`
+    fn write_requests(
+        &mut self,
+        folder: &str,
+        entries: Vec<StoredRequestEntry>,
+        order: &mut Vec<String>,
+    ) -> Result<(), ApicizeError> {
+        let mut names = UniqueNames::default();
+        for entry in entries {
+            match entry {
+                StoredRequestEntry::Request(request) => {
+                    let file = join(folder, &format!("{}.json", names.get(&request.name)));
+                    self.write(
+                        REQUESTS_FOLDER,
+                        &file,
+                        &StoredRequestEntry::Request(request),
+                    )?;
+                    order.push(file);
+                }
+                StoredRequestEntry::Group(mut group) => {
+                    let group_folder = join(folder, &names.get(&group.name));
+                    // Children are stored in the group's folder; an empty list is kept so
+                    // that the file is read back as a group
+                    let children = group.children.replace(Vec::default()).unwrap_or_default();
+                    let file = join(&group_folder, GROUP_FILE_NAME);
+                    self.write(REQUESTS_FOLDER, &file, &StoredRequestEntry::Group(group))?;
+                    order.push(file);
+                    self.write_requests(&group_folder, children, order)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write entities to their own files in the specified folder, returning the files
+    /// written in order
+    fn write_entities<T: Serialize>(
+        &mut self,
+        folder: &str,
+        entities: &[T],
+        get_name: impl Fn(&T) -> &str,
+    ) -> Result<Vec<String>, ApicizeError> {
+        let mut names = UniqueNames::default();
+        let mut order = Vec::with_capacity(entities.len());
+        for entity in entities {
+            let file = format!("{}.json", names.get(get_name(entity)));
+            self.write(folder, &file, entity)?;
+            order.push(file);
+        }
+        Ok(order)
+    }
+
+    fn write<T: Serialize>(
+        &mut self,
+        folder: &str,
+        file: &str,
+        data: &T,
+    ) -> Result<(), ApicizeError> {
+        let path = self.directory.join(folder).join(file);
+        write_file(&path, data)?;
+        self.written.insert(path);
+        Ok(())
+    }
+
+    /// Remove files listed in the previous layout that were not written, along with any
+    /// group folders left empty
+    fn remove_stale_files(&self, previous: &DirectoryLayout) -> Result<(), ApicizeError> {
+        for (folder, files) in [
+            (REQUESTS_FOLDER, &previous.requests),
+            (SCENARIOS_FOLDER, &previous.scenarios),
+            (AUTHORIZATIONS_FOLDER, &previous.authorizations),
+            (CERTIFICATES_FOLDER, &previous.certificates),
+            (PROXIES_FOLDER, &previous.proxies),
+            (DATA_FOLDER, &previous.data),
+        ] {
+            let folder = self.directory.join(folder);
+            for file in files {
+                // Ignore entries that would point outside of the folder
+                if !Path::new(file)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+                {
+                    continue;
+                }
+                let path = folder.join(file);
+                if self.written.contains(&path) || !path.is_file() {
+                    continue;
+                }
+                fs::remove_file(&path).map_err(|err| from_io(err, &path))?;
+
+                let mut parent = path.parent();
+                while let Some(directory) = parent
+                    && directory != folder
+                    && fs::read_dir(directory)
+                        .map_err(|err| from_io(err, directory))?
+                        .next()
+                        .is_none()
+                {
+                    fs::remove_dir(directory).map_err(|err| from_io(err, directory))?;
+                    parent = directory.parent();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generates file names from entity names, unique within a folder regardless of case
+#[derive(Default)]
+struct UniqueNames {
+    used: HashSet<String>,
+}
+
+impl UniqueNames {
+    fn get(&mut self, name: &str) -> String {
+        let mut base = name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .take(64)
+            .collect::<String>()
+            .trim_matches(['.', ' '])
+            .to_string();
+        if base.is_empty() {
+            base = "Unnamed".to_string();
+        }
+
+        let mut unique = base.clone();
+        let mut counter = 1;
+        loop {
+            let key = unique.to_lowercase();
+            if format!("{key}.json") != GROUP_FILE_NAME && self.used.insert(key) {
+                return unique;
+            }
+            counter += 1;
+            unique = format!("{base} ({counter})");
+        }
+    }
+}
+
+/// Read requests and groups stored in the specified folder (relative to `root`)
+fn read_requests(
+    root: &Path,
+    folder: &str,
+    positions: &HashMap<&str, usize>,
+) -> Result<Vec<StoredRequestEntry>, ApicizeError> {
+    let directory = root.join(folder);
+    if !directory.is_dir() {
+        return Ok(Vec::default());
+    }
+
+    let mut entries = Vec::<(String, StoredRequestEntry)>::new();
+    for item in fs::read_dir(&directory).map_err(|err| from_io(err, &directory))? {
+        let path = item.map_err(|err| from_io(err, &directory))?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            let group_folder = join(folder, &name);
+            let file = join(&group_folder, GROUP_FILE_NAME);
+            let group_path = root.join(&file);
+            if !group_path.is_file() {
+                continue;
+            }
+            match open_data_file::<StoredRequestEntry>(&group_path)?.data {
+                StoredRequestEntry::Group(mut group) => {
+                    group.children = Some(read_requests(root, &group_folder, positions)?);
+                    entries.push((file, StoredRequestEntry::Group(group)));
+                }
+                StoredRequestEntry::Request(_) => {
+                    return Err(ApicizeError::Error {
+                        description: format!("{} is not a group", group_path.to_string_lossy()),
+                    });
+                }
+            }
+        } else if is_json_file(&path) && name != GROUP_FILE_NAME {
+            let entry = open_data_file::<StoredRequestEntry>(&path)?.data;
+            entries.push((join(folder, &name), entry));
+        }
+    }
+
+    sort_by_position(&mut entries, positions);
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Read entities stored in their own files in the specified folder
+fn read_entities<T: DeserializeOwned>(
+    folder: &Path,
+    order: &[String],
+) -> Result<Vec<T>, ApicizeError> {
+    if !folder.is_dir() {
+        return Ok(Vec::default());
+    }
+
+    let mut entities = Vec::<(String, T)>::new();
+    for item in fs::read_dir(folder).map_err(|err| from_io(err, folder))? {
+        let path = item.map_err(|err| from_io(err, folder))?.path();
+        if path.is_file() && is_json_file(&path) {
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            entities.push((name, open_data_file::<T>(&path)?.data));
+        }
+    }
+
+    sort_by_position(&mut entities, &get_positions(order));
+    Ok(entities.into_iter().map(|(_, entity)| entity).collect())
+}
+
+/// Replace workbook entities, keeping private and vault entities
+fn replace_workbook<T: Clone + Identifiable>(
+    entities: &IndexedEntities<T>,
+    workbook: Vec<T>,
+) -> IndexedEntities<T>
+where
+    IndexedEntities<T>: PersistedIndex<T>,
+{
+    <IndexedEntities<T> as PersistedIndex<T>>::new(
+        Some(workbook).filter(|w| !w.is_empty()),
+        entities.get_private(),
+        entities.get_vault(),
+    )
+}
+
+/// Return entities as they are stored, without validation state
+fn get_stored<T: Validated>(entities: Option<Vec<T>>) -> Vec<T> {
+    let mut entities = entities.unwrap_or_default();
+    entities.iter_mut().for_each(clear_validation);
+    entities
+}
+
+fn clear_validation<T: Validated>(entity: &mut T) {
+    entity.set_validation_errors(None);
+    entity.set_validation_warnings(None);
+}
+
+fn get_positions(order: &[String]) -> HashMap<&str, usize> {
+    order
+        .iter()
+        .enumerate()
+        .map(|(position, file)| (file.as_str(), position))
+        .collect()
+}
+
+fn sort_by_position<T>(entries: &mut [(String, T)], positions: &HashMap<&str, usize>) {
+    entries.sort_by(|(a, _), (b, _)| {
+        let position_a = positions.get(a.as_str()).unwrap_or(&usize::MAX);
+        let position_b = positions.get(b.as_str()).unwrap_or(&usize::MAX);
+        position_a.cmp(position_b).then_with(|| a.cmp(b))
+    });
+}
+
+fn join(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        name.to_string()
+    } else {
+        format!("{folder}/{name}")
+    }
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+fn from_io(err: std::io::Error, path: &Path) -> ApicizeError {
+    ApicizeError::from_io(err, Some(path.to_string_lossy().to_string()))
+}
+
+/// Write data to the specified file, unless it already has the same content
+fn write_file<T: Serialize>(path: &Path, data: &T) -> Result<(), ApicizeError> {
+    let file_name = path.to_string_lossy().to_string();
+    let mut content = Vec::new();
+    let mut serializer =
+        serde_json::Serializer::with_formatter(&mut content, PrettyFormatter::with_indent(b"    "));
+    data.serialize(&mut serializer)
+        .map_err(|err| ApicizeError::from_serde(err, file_name))?;
+
+    if fs::read(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| from_io(err, parent))?;
+    }
+    fs::write(path, content).map_err(|err| from_io(err, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use apicize_lib::{
+        OpenWorkbookOptions, ParameterLockStatus, Request, RequestGroup, ScenarioPlain, Selection,
+    };
+
+    use super::*;
+
+    fn request(id: &str, name: &str) -> RequestEntry {
+        RequestEntry::Request(Request {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: format!("https://localhost/{id}"),
+            ..Default::default()
+        })
+    }
+
+    fn group(id: &str, name: &str, children: Vec<RequestEntry>) -> RequestEntry {
+        RequestEntry::Group(RequestGroup {
+            id: id.to_string(),
+            name: name.to_string(),
+            children: Some(children),
+            ..Default::default()
+        })
+    }
+
+    fn scenario(id: &str) -> Scenario {
+        Scenario::Plain(Box::new(ScenarioPlain {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    fn workspace(requests: Vec<RequestEntry>) -> Workspace {
+        Workspace {
+            requests: IndexedEntities::<RequestEntry>::new(&requests),
+            scenarios: <IndexedEntities<Scenario> as PersistedIndex<Scenario>>::new(
+                Some(vec![scenario("dev"), scenario("prod")]),
+                Some(vec![scenario("private")]),
+                None,
+            ),
+            authorizations: IndexedEntities::default(),
+            certificates: IndexedEntities::default(),
+            proxies: IndexedEntities::default(),
+            data: IndexedEntities::default(),
+            defaults: WorkbookDefaultParameters {
+                selected_scenario: Selection {
+                    id: "prod".to_string(),
+                    name: "prod".to_string(),
+                },
+                ..Default::default()
+            },
+            private_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            vault_lock_status: ParameterLockStatus::UnlockedNoPassword,
+            private_password: None,
+            vault_password: None,
+            private_encryption: None,
+            vault_encryption: None,
+        }
+    }
+
+    fn open(workbook_path: &Path) -> Workspace {
+        let mut opened = Workspace::open(
+            Some(&workbook_path.to_path_buf()),
+            workbook_path.parent().unwrap(),
+            OpenWorkbookOptions {
+                override_default_scenario: None,
+                override_default_authorization: None,
+                override_default_certificate: None,
+                override_default_proxy: None,
+                override_data_seed: None,
+                private_password: None,
+                vault_password: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            WorkbookFormat::detect(workbook_path).unwrap(),
+            WorkbookFormat::Directory
+        );
+        open_directory(&mut opened, workbook_path).unwrap();
+        opened
+    }
+
+    fn get_names(workspace: &Workspace, ids: &[String]) -> Vec<String> {
+        ids.iter()
+            .map(|id| workspace.requests.entities[id].get_name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn saves_and_opens_requests_in_folders() {
+        let directory = tempfile::tempdir().unwrap();
+        let workbook_path = directory.path().join("test.apicize");
+        let saved = workspace(vec![
+            request("r1", "Login"),
+            group(
+                "g1",
+                "Users",
+                vec![
+                    request("r2", "List"),
+                    group("g2", "Admin", vec![request("r3", "Create")]),
+                    request("r4", "list"),
+                ],
+            ),
+        ]);
+
+        save_directory(&saved, &workbook_path).unwrap();
+
+        let folder = get_directory_name(&workbook_path);
+        for file in [
+            "requests/Login.json",
+            "requests/Users/_group.json",
+            "requests/Users/List.json",
+            "requests/Users/list (2).json",
+            "requests/Users/Admin/_group.json",
+            "requests/Users/Admin/Create.json",
+            "scenarios/dev.json",
+            "scenarios/prod.json",
+        ] {
+            assert!(folder.join(file).is_file(), "{file} not saved");
+        }
+
+        let opened = open(&workbook_path);
+        assert_eq!(opened.requests.top_level_ids, vec!["r1", "g1"]);
+        assert_eq!(opened.requests.child_ids["g1"], vec!["r2", "g2", "r4"]);
+        assert_eq!(opened.requests.child_ids["g2"], vec!["r3"]);
+        assert_eq!(
+            opened
+                .scenarios
+                .get_workbook()
+                .unwrap()
+                .iter()
+                .map(|s| s.get_id().to_string())
+                .collect::<Vec<String>>(),
+            vec!["dev", "prod"]
+        );
+        assert_eq!(opened.defaults.selected_scenario.id, "prod");
+    }
+
+    #[test]
+    fn removes_stale_files_and_keeps_added_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let workbook_path = directory.path().join("test.apicize");
+        save_directory(
+            &workspace(vec![group("g1", "Users", vec![request("r1", "List")])]),
+            &workbook_path,
+        )
+        .unwrap();
+
+        // Renaming the group moves its folder
+        save_directory(
+            &workspace(vec![group("g1", "People", vec![request("r1", "List")])]),
+            &workbook_path,
+        )
+        .unwrap();
+        let folder = get_directory_name(&workbook_path);
+        assert!(!folder.join("requests/Users").exists());
+        assert!(folder.join("requests/People/List.json").is_file());
+
+        // Files added outside of Apicize (i.e. by merging) are ordered after listed ones
+        write_file(
+            &folder.join("requests/People/Added.json"),
+            &StoredRequestEntry::from(request("r2", "Added")),
+        )
+        .unwrap();
+        write_file(
+            &folder.join("requests/First.json"),
+            &StoredRequestEntry::from(request("r3", "First")),
+        )
+        .unwrap();
+
+        let opened = open(&workbook_path);
+        assert_eq!(
+            get_names(&opened, &opened.requests.top_level_ids),
+            vec!["People", "First"]
+        );
+        assert_eq!(
+            get_names(&opened, &opened.requests.child_ids["g1"]),
+            vec!["List", "Added"]
+        );
+
+        // Files not listed by the previous save (i.e. added since the workbook was opened)
+        // are left in place
+        fs::write(folder.join("requests/People/notes.txt"), "Notes").unwrap();
+        save_directory(&workspace(vec![request("r4", "Other")]), &workbook_path).unwrap();
+        assert!(!folder.join("requests/People/List.json").exists());
+        assert!(!folder.join("requests/People/_group.json").exists());
+        assert!(folder.join("requests/People/Added.json").is_file());
+        assert!(folder.join("requests/People/notes.txt").is_file());
+        assert!(folder.join("requests/First.json").is_file());
+        assert!(folder.join("requests/Other.json").is_file());
+    }
+}
@@ -1,43 +1,71 @@
 use apicize_lib::{
     ApicizeError, Authorization, Certificate, DataSet, DataSourceType, ExecutionReportFormat,
     ExecutionResultBuilder, ExecutionResultDetail, ExecutionResultSuccess, ExecutionResultSummary,
-    ExecutionState, Identifiable, IndexedEntities, PERSIST_PRIVATE, PERSIST_VAULT,
-    ParameterLockStatus, ParameterStore, Parameters, Proxy, Request, RequestBody, RequestEntry,
-    RequestGroup, SaveWorkspaceParameters, Scenario, SelectedParameters, Selection,
-    StoredRequestEntry, Validated, ValidationState, WorkbookDefaultParameters, Workspace,
-    authorization::AuthorizationPlain, certificate::CertificatePlain,
-    editing::indexed_entities::IndexedEntityPosition, identifiable::CloneIdentifiable,
+    ExecutionState, Identifiable, IndexedEntities, OpenWorkbookOptions, PERSIST_PRIVATE,
+    PERSIST_VAULT, PERSIST_WORKBOOK, ParameterLockStatus, ParameterStore, Parameters, Proxy,
+    Request, RequestBody, RequestEntry, RequestGroup, SaveWorkspaceParameters, Scenario,
+    SelectedParameters, Selection, StoredRequestEntry, Validated, ValidationState,
+    WorkbookDefaultParameters, Workspace,
+    authorization::AuthorizationPlain,
+    build_absolute_file_name,
+    certificate::CertificatePlain,
+    editing::indexed_entities::IndexedEntityPosition,
+    identifiable::CloneIdentifiable,
+    parameters::{EncryptableParameter, ParameterEncryption},
+    proxy::ProxyPlain,
+    variable_cache::VariableCache,
     workspace::InvalidSelections,
 };
 use file_type::FileType;
 use indexmap::IndexMap;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
-use serde_json::ser::PrettyFormatter;
+use serde_json::{Value, ser::PrettyFormatter};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     env,
     fmt::Display,
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
 use uuid::Uuid;
 
 use crate::{
+    authorization_settings::{AuthorizationSettings, ExtendedAuthorization},
     clipboard::{ClipboardData, ClipboardDataType},
+    digest_auth::NonceCounts,
     error::ApicizeAppError,
+    extensions::WorkbookExtensions,
+    file_watching::{
+        ExternalChanges, MergeConflict, WatchedFile, merge_extensions, merge_workspace,
+    },
     navigation::{
         Navigation, NavigationRequestEntry, UpdateWithNavigationResponse, UpdatedNavigationEntry,
     },
-    sessions::{Session, SessionSaveState},
+    oauth2_grants::build_client,
+    proxy_settings::{ProxyCredentials, ProxySettings},
+    search::{ReplaceOptions, ReplaceResults, plan_replacement},
+    secret_scanning::SecretFinding,
+    secret_variables::{
+        ScenarioSecretVariables, get_commands_fingerprint, get_secret_commands, update_placeholders,
+    },
+    selections::{append_selected_ids, get_effective_selection},
+    sessions::{Session, SessionEntity, SessionSaveState},
     settings::ApicizeSettings,
+    substitution::clone_and_sub,
+    token_cache::{TokenRenewal, TokenSource},
+    trusted_roots::TrustedRoots,
+    undo_history::{
+        DeletedEntities, DeletedEntity, RemovedEntities, UndoHistory, UndoOperation, find_anchor,
+    },
     updates::{
-        AuthorizationUpdate, AuthorizationUpdateType, CertificateUpdate, CertificateUpdateType,
-        DataSetUpdate, DefaultsUpdate, EntityUpdate, ProxyUpdate, RequestGroupUpdate,
-        RequestUpdate, ScenarioUpdate,
+        AuthorizationUpdate, AuthorizationUpdateType, BulkRequestUpdate, CertificateUpdate,
+        CertificateUpdateType, DataSetUpdate, DefaultsUpdate, EntityUpdate, ProxyUpdate,
+        RequestGroupUpdate, RequestUpdate, ScenarioUpdate, TrustedRootsUpdate,
     },
+    workbook_directory::{WorkbookFormat, open_directory, save_directory},
 };
 
 pub const DEFAULT_SELECTION_ID: &str = "\tDEFAULT\t";
@@ -75,6 +103,16 @@ pub struct BodyMimeInfo {
     pub body_length: Option<usize>,
 }
 
+/// Outcome of a save that was not completed and requires confirmation to retry
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveWorkspaceResponse {
+    /// Values that look like secrets which would be saved to the public workbook
+    pub secret_findings: Vec<SecretFinding>,
+    /// True if the workbook was changed outside of Apicize and would be overwritten
+    pub changed_on_disk: bool,
+}
+
 pub struct WorkspaceInfo {
     /// True if workspace has been modified since last open/save
     pub dirty: bool,
@@ -88,6 +126,8 @@ pub struct WorkspaceInfo {
     pub display_name: String,
     /// Actual workspace
     pub workspace: Workspace,
+    /// Settings persisted alongside the workbook
+    pub extensions: WorkbookExtensions,
     /// Activation tree
     pub navigation: Navigation,
     /// Execution results
@@ -98,6 +138,34 @@ pub struct WorkspaceInfo {
     pub data_set_content: FxHashMap<String, DataSetContent>,
     /// Execution counters for tracking active requests
     pub execution_counters: ExecutionCounters,
+    /// Edits that can be undone or redone, shared by the workspace's sessions
+    pub undo_history: UndoHistory,
+    /// Changes made to the workbook and data set files outside of Apicize
+    pub external_changes: ExternalChanges,
+    /// Digest authentication nonce counts, shared with executions
+    pub nonce_counts: Arc<NonceCounts>,
+    /// Fingerprint of secret variable commands trusted while the workbook is open
+    pub trusted_commands_fingerprint: Option<String>,
+}
+
+/// Changes made when undoing or redoing an edit, to be sent to the workspace's sessions
+#[derive(Default)]
+pub struct AppliedUndo {
+    /// Updates applied to entities, including reset or restored selections
+    pub updates: Vec<EntityUpdate>,
+    /// Deleted entities
+    pub deleted: Vec<(EntityType, String)>,
+}
+
+/// Changes brought into a workspace from files changed outside of Apicize
+#[derive(Default)]
+pub struct AppliedExternalChanges {
+    /// True if the workbook (or private parameters) were reloaded or merged
+    pub workbook_changed: bool,
+    /// Data sets whose external files were reloaded
+    pub data_set_updates: Vec<EntityUpdate>,
+    /// Entities changed both locally and on disk, which were left as they are locally
+    pub conflicts: Vec<MergeConflict>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -138,6 +206,23 @@ impl Default for Workspaces {
 }
 
 impl Workspaces {
+    /// Return the vault password, if set or defined in the environment
+    pub fn get_vault_password(&self) -> Option<String> {
+        self.vault_password.clone().or_else(|| {
+            env::var("APICIZE_VAULT_PWD")
+                .ok()
+                .filter(|password| !password.is_empty())
+        })
+    }
+
+    /// Return the encryption method configured for the vault
+    pub fn get_vault_encryption(&self) -> ParameterEncryption {
+        self.workspaces
+            .values()
+            .find_map(|info| info.workspace.vault_encryption)
+            .unwrap_or_default()
+    }
+
     pub fn trace_all_workspaces(&self) {
         println!("   Workspaces:");
         for (id, info) in &self.workspaces {
@@ -188,12 +273,13 @@ impl Workspaces {
     pub fn add_workspace(
         &mut self,
         workspace: Workspace,
+        extensions: WorkbookExtensions,
         file_name: &str,
         is_new: bool,
     ) -> OpenWorkspaceResult {
         let workspace_id = Uuid::new_v4().to_string();
 
-        let navigation = Navigation::new(&workspace, &FxHashMap::default());
+        let navigation = Navigation::new(&workspace, &extensions, &FxHashMap::default());
 
         let directory: String;
         let display_name: String;
@@ -225,6 +311,7 @@ impl Workspaces {
                 dirty: false,
                 warn_on_workspace_creds: !(any_public_auths || any_public_certs),
                 workspace,
+                extensions,
                 navigation,
                 execution_results: ExecutionResultBuilder::default(),
                 executions: FxHashMap::default(),
@@ -233,10 +320,21 @@ impl Workspaces {
                 display_name: display_name.to_string(),
                 data_set_content: FxHashMap::default(),
                 execution_counters: Arc::new(Mutex::new(HashMap::new())),
+                undo_history: UndoHistory::default(),
+                external_changes: ExternalChanges::default(),
+                nonce_counts: Arc::new(NonceCounts::default()),
+                trusted_commands_fingerprint: None,
                 // request_body_mime_types: HashMap::default(),
             },
         );
 
+        if !(is_new || file_name.is_empty()) {
+            let info = self.workspaces.get_mut(&workspace_id).unwrap();
+            info.external_changes.track(file_name, &info.workspace);
+            info.external_changes
+                .set_base(Some((info.workspace.clone(), info.extensions.clone())));
+        }
+
         if is_new {
             let request = Request {
                 name: "New Request".to_string(),
@@ -352,7 +450,8 @@ impl Workspaces {
         lock_type: PasswordLockType,
     ) -> Result<(), ApicizeAppError> {
         let updated_password = {
-            let workspace = self.get_workspace_mut(workspace_id)?;
+            let info = self.get_workspace_info_mut(workspace_id)?;
+            let workspace = &mut info.workspace;
             let workspace_is_saved = workbook_path.is_some();
 
             // Return the current value of the specified env var (if set)
@@ -438,7 +537,13 @@ impl Workspaces {
             match result {
                 Ok(_) => match parameter_store {
                     ParameterStore::Vault => Ok(workspace.vault_password.clone()),
-                    ParameterStore::Private => Ok(None),
+                    ParameterStore::Private => {
+                        // Private parameters were re-encrypted by us, not changed externally
+                        if workspace_is_saved {
+                            info.external_changes.acknowledge(&WatchedFile::Private);
+                        }
+                        Ok(None)
+                    }
                 },
                 Err(err) => {
                     match parameter_store {
@@ -464,6 +569,162 @@ impl Workspaces {
         Ok(())
     }
 
+    /// Change the password of an unlocked parameter store after verifying its current password,
+    /// re-encrypting the store and any extended settings encrypted with it.  Previous files are
+    /// backed up and restored if saving fails; backups are removed once the save succeeds
+    pub fn change_parameters_password(
+        &mut self,
+        workspace_id: &str,
+        workbook_path: Option<PathBuf>,
+        parameter_store: ParameterStore,
+        current_password: &str,
+        lock_type: PasswordLockType,
+    ) -> Result<(), ApicizeAppError> {
+        let (old_password, old_status) = {
+            let workspace = self.get_workspace(workspace_id)?;
+            match parameter_store {
+                ParameterStore::Vault => (
+                    workspace.vault_password.clone(),
+                    workspace.vault_lock_status,
+                ),
+                ParameterStore::Private => (
+                    workspace.private_password.clone(),
+                    workspace.private_lock_status,
+                ),
+            }
+        };
+        if old_status.is_locked() {
+            return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Parameters must be decrypted before changing their password"
+                    .to_string(),
+            }));
+        }
+        if old_password.as_deref().unwrap_or_default() != current_password {
+            return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Current password is incorrect".to_string(),
+            }));
+        }
+
+        let old_shared_vault_password = self.vault_password.clone();
+
+        // Extended settings of vault parameters are stored alongside the vault rather than
+        // in workbooks, so that changing the vault password re-keys them for all workbooks
+        let file_names = match parameter_store {
+            ParameterStore::Vault => vec![
+                Parameters::get_globals_filename(),
+                WorkbookExtensions::get_vault_file_name(),
+            ],
+            ParameterStore::Private => match &workbook_path {
+                Some(workbook_path) => vec![
+                    Parameters::get_workbook_private_filename(workbook_path),
+                    WorkbookExtensions::get_file_name(workbook_path),
+                ],
+                None => vec![],
+            },
+        };
+        let mut backups = Vec::<(PathBuf, PathBuf)>::with_capacity(file_names.len());
+        let mut created = Vec::<PathBuf>::new();
+        for file_name in file_names {
+            if file_name.is_file() {
+                let mut backup_name = file_name.clone().into_os_string();
+                backup_name.push(".bak");
+                let backup_name = PathBuf::from(backup_name);
+                fs::copy(&file_name, &backup_name)?;
+                backups.push((file_name, backup_name));
+            } else {
+                created.push(file_name);
+            }
+        }
+
+        let result = self
+            .set_parameters_password(
+                workspace_id,
+                workbook_path.clone(),
+                parameter_store,
+                lock_type,
+            )
+            .and_then(|_| {
+                let info = self.get_workspace_info_mut(workspace_id)?;
+                match (parameter_store, &workbook_path) {
+                    (ParameterStore::Vault, _) => info.extensions.save_vault(&info.workspace)?,
+                    (ParameterStore::Private, Some(workbook_path)) => {
+                        info.extensions
+                            .save_private_settings(workbook_path, &info.workspace)?;
+                        info.external_changes.acknowledge(&WatchedFile::Extensions);
+                    }
+                    (ParameterStore::Private, None) => {}
+                }
+                Ok(())
+            });
+
+        match result {
+            Ok(()) => {
+                for (_, backup_name) in backups {
+                    fs::remove_file(backup_name)?;
+                }
+                // Other workbooks with an unlocked vault hold it decrypted, and will encrypt
+                // it with the new password when saved
+                if parameter_store == ParameterStore::Vault {
+                    let (password, status) = {
+                        let workspace = self.get_workspace(workspace_id)?;
+                        (
+                            workspace.vault_password.clone(),
+                            workspace.vault_lock_status,
+                        )
+                    };
+                    for (id, info) in self.workspaces.iter_mut() {
+                        if id != workspace_id && !info.workspace.vault_lock_status.is_locked() {
+                            info.workspace.vault_password = password.clone();
+                            info.workspace.vault_lock_status = status;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Err(err) => {
+                // Restore previous files and password, leaving backups in place if that fails
+                for (file_name, backup_name) in backups {
+                    if let Err(restore_err) = fs::rename(&backup_name, &file_name) {
+                        eprintln!(
+                            "Unable to restore {} from {}: {restore_err}",
+                            file_name.display(),
+                            backup_name.display()
+                        );
+                    }
+                }
+                for file_name in created {
+                    if file_name.is_file()
+                        && let Err(remove_err) = fs::remove_file(&file_name)
+                    {
+                        eprintln!("Unable to remove {}: {remove_err}", file_name.display());
+                    }
+                }
+                if parameter_store == ParameterStore::Vault {
+                    self.vault_password = old_shared_vault_password;
+                }
+                let info = self.get_workspace_info_mut(workspace_id)?;
+                // Files written before the failure were acknowledged, so acknowledge their
+                // restored versions as well
+                if parameter_store == ParameterStore::Private && workbook_path.is_some() {
+                    info.external_changes.acknowledge(&WatchedFile::Private);
+                    info.external_changes.acknowledge(&WatchedFile::Extensions);
+                }
+                let workspace = &mut info.workspace;
+                match parameter_store {
+                    ParameterStore::Vault => {
+                        workspace.vault_password = old_password;
+                        workspace.vault_lock_status = old_status;
+                    }
+                    ParameterStore::Private => {
+                        workspace.private_password = old_password;
+                        workspace.private_lock_status = old_status;
+                    }
+                }
+                Err(err)
+            }
+        }
+    }
+
     pub fn decrypt_parameters(
         &mut self,
         workspace_id: &str,
@@ -885,6 +1146,11 @@ impl Workspaces {
             request.selected_data = selected_data_set.clone();
         }
 
+        if let Some(selected_trusted_roots) = &update.selected_trusted_roots {
+            info.extensions
+                .set_selected_trusted_roots(&id, selected_trusted_roots);
+        }
+
         if let Some(validation_warnings) = &update.validation_warnings {
             request.set_validation_warnings(if validation_warnings.is_empty() {
                 None
@@ -993,6 +1259,11 @@ impl Workspaces {
             group.selected_data = selected_data_set.clone();
         }
 
+        if let Some(selected_trusted_roots) = &update.selected_trusted_roots {
+            info.extensions
+                .set_selected_trusted_roots(&id, selected_trusted_roots);
+        }
+
         if let Some(validation_warnings) = &update.validation_warnings {
             group.set_validation_warnings(if validation_warnings.is_empty() {
                 None
@@ -1121,7 +1392,10 @@ impl Workspaces {
 
         match request_or_group {
             RequestEntry::Request(request) => {
-                let notification = EntityUpdate::Request(RequestUpdate::from_selections(request));
+                let mut update = RequestUpdate::from_selections(request);
+                update.selected_trusted_roots =
+                    Some(info.extensions.get_selected_trusted_roots(&request.id));
+                let notification = EntityUpdate::Request(update);
                 let navigation = UpdatedNavigationEntry {
                     id: request.id.to_string(),
                     name: request.get_title(),
@@ -1136,8 +1410,10 @@ impl Workspaces {
                 Ok((notification, navigation))
             }
             RequestEntry::Group(group) => {
-                let notification =
-                    EntityUpdate::RequestGroup(RequestGroupUpdate::from_selections(group));
+                let mut update = RequestGroupUpdate::from_selections(group);
+                update.selected_trusted_roots =
+                    Some(info.extensions.get_selected_trusted_roots(&group.id));
+                let notification = EntityUpdate::RequestGroup(update);
                 let navigation = UpdatedNavigationEntry {
                     id: group.id.to_string(),
                     name: group.get_title(),
@@ -1275,6 +1551,14 @@ impl Workspaces {
             Ok(Some(EntityType::Certificate))
         } else if workspace.proxies.entities.contains_key(entity_id) {
             Ok(Some(EntityType::Proxy))
+        } else if self
+            .get_workspace_info(workspace_id)?
+            .extensions
+            .trusted_roots
+            .entities
+            .contains_key(entity_id)
+        {
+            Ok(Some(EntityType::TrustedRoots))
         } else {
             Ok(None)
         }
@@ -1287,7 +1571,8 @@ impl Workspaces {
         entity_type: EntityType,
         entity_id: &str,
     ) -> Result<Vec<String>, ApicizeAppError> {
-        let workspace = self.get_workspace(workspace_id)?;
+        let info = self.get_workspace_info(workspace_id)?;
+        let workspace = &info.workspace;
 
         let child_id_assigments = match entity_type {
             EntityType::RequestEntry => &workspace.requests.child_ids,
@@ -1298,6 +1583,7 @@ impl Workspaces {
             EntityType::Certificate => &workspace.certificates.child_ids,
             EntityType::Proxy => &workspace.proxies.child_ids,
             EntityType::DataSet => &workspace.data.child_ids,
+            EntityType::TrustedRoots => &info.extensions.trusted_roots.child_ids,
             _ => {
                 return Err(ApicizeAppError::InvalidOperation(format!(
                     "Invalid list type {entity_type}",
@@ -1323,6 +1609,93 @@ impl Workspaces {
         Ok(results)
     }
 
+    /// Move a scenario, authorization, certificate or proxy to the workbook (None), private
+    /// or vault store, keeping its ID so that selections referencing it remain valid.
+    /// Parameters are encrypted when the destination store is saved with a password
+    pub fn move_parameter_to_store(
+        &mut self,
+        workspace_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+        parameter_store: Option<ParameterStore>,
+    ) -> Result<bool, ApicizeAppError> {
+        fn move_to_store<T: Identifiable + EncryptableParameter>(
+            entities: &mut IndexedEntities<T>,
+            entity_id: &str,
+            store_id: &str,
+        ) -> Result<bool, ApicizeAppError> {
+            let Some(entity) = entities.entities.get(entity_id) else {
+                return Err(ApicizeAppError::InvalidOperation(format!(
+                    "Invalid parameter ID {entity_id}"
+                )));
+            };
+            if entity.is_encrypted() {
+                return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                    description: format!(
+                        "\"{}\" must be unlocked before it can be moved",
+                        entity.get_title()
+                    ),
+                }));
+            }
+            if entities
+                .child_ids
+                .get(store_id)
+                .is_some_and(|ids| ids.iter().any(|id| id == entity_id))
+            {
+                return Ok(false);
+            }
+            Ok(entities.move_entity(entity_id, store_id, IndexedEntityPosition::Under)?)
+        }
+
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        let workspace = &mut info.workspace;
+
+        let store_id = match parameter_store {
+            Some(ParameterStore::Vault) => {
+                if workspace.vault_lock_status.is_locked() {
+                    return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                        description: "Vault must be unlocked before parameters can be moved to it"
+                            .to_string(),
+                    }));
+                }
+                PERSIST_VAULT
+            }
+            Some(ParameterStore::Private) => {
+                if workspace.private_lock_status.is_locked() {
+                    return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                        description: "Private parameter store must be unlocked before parameters can be moved to it"
+                            .to_string(),
+                    }));
+                }
+                PERSIST_PRIVATE
+            }
+            None => PERSIST_WORKBOOK,
+        };
+
+        let moved = match entity_type {
+            EntityType::Scenario => move_to_store(&mut workspace.scenarios, entity_id, store_id),
+            EntityType::Authorization => {
+                move_to_store(&mut workspace.authorizations, entity_id, store_id)
+            }
+            EntityType::Certificate => {
+                move_to_store(&mut workspace.certificates, entity_id, store_id)
+            }
+            EntityType::Proxy => move_to_store(&mut workspace.proxies, entity_id, store_id),
+            _ => Err(ApicizeAppError::InvalidTypeForOperation(entity_type)),
+        }?;
+
+        if moved {
+            info.dirty = true;
+            info.navigation.move_navigation_entity(
+                entity_id,
+                store_id,
+                &IndexedEntityPosition::Under,
+                entity_type,
+            );
+        }
+        Ok(moved)
+    }
+
     /// Return a list of all group and descendant group IDs
     pub fn list_request_and_group_with_children(
         &self,
@@ -1413,6 +1786,13 @@ impl Workspaces {
             },
             None => Scenario::default(),
         };
+        if let Some(other_id) = clone_from_id
+            && let Some(secrets) = info.extensions.secret_variables.get(other_id).cloned()
+        {
+            info.extensions
+                .secret_variables
+                .insert(scenario.get_id().to_string(), secrets);
+        }
         Self::perform_add_scenario(info, relative_to, relative_position, scenario)
     }
 
@@ -1454,6 +1834,7 @@ impl Workspaces {
         let info = self.get_workspace_info_mut(workspace_id)?;
         info.dirty = true;
         info.workspace.scenarios.remove_entity(scenario_id)?;
+        info.extensions.secret_variables.remove(scenario_id);
         info.navigation
             .delete_navigation_entity(scenario_id, EntityType::Scenario);
         Ok(info.workspace.validate_selections())
@@ -1505,7 +1886,26 @@ impl Workspaces {
             } else {
                 Some(variables.clone())
             };
+        }
+
+        // Secret variables have placeholders so that they are listed with other variables
+        let mut secrets = update
+            .secret_variables
+            .clone()
+            .or_else(|| info.extensions.secret_variables.get(&id).cloned())
+            .unwrap_or_default();
+        if update.variables.is_some() || update.secret_variables.is_some() {
+            update_placeholders(
+                &mut scenario.variables,
+                &mut secrets,
+                update.variables.is_some(),
+            );
             scenario.validate_variables();
+            if secrets.is_empty() {
+                info.extensions.secret_variables.remove(&id);
+            } else {
+                info.extensions.secret_variables.insert(id.clone(), secrets);
+            }
         }
 
         let updated_name = scenario.get_title();
@@ -1555,6 +1955,46 @@ impl Workspaces {
         }
     }
 
+    pub fn get_authorization_settings(
+        &self,
+        workspace_id: &str,
+        authorization_id: &str,
+    ) -> Result<Option<AuthorizationSettings>, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        if !info
+            .workspace
+            .authorizations
+            .entities
+            .contains_key(authorization_id)
+        {
+            return Err(ApicizeAppError::InvalidAuthorization(
+                authorization_id.to_string(),
+            ));
+        }
+        Ok(info
+            .extensions
+            .authorization_settings
+            .get(authorization_id)
+            .cloned())
+    }
+
+    pub fn get_secret_variables(
+        &self,
+        workspace_id: &str,
+        scenario_id: &str,
+    ) -> Result<ScenarioSecretVariables, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        if !info.workspace.scenarios.entities.contains_key(scenario_id) {
+            return Err(ApicizeAppError::InvalidScenario(scenario_id.to_string()));
+        }
+        Ok(info
+            .extensions
+            .secret_variables
+            .get(scenario_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
     pub fn get_authorization_title(
         &self,
         workspace_id: &str,
@@ -1582,6 +2022,17 @@ impl Workspaces {
             },
             None => Authorization::default(),
         };
+        if let Some(other_id) = clone_from_id
+            && let Some(settings) = info
+                .extensions
+                .authorization_settings
+                .get(other_id)
+                .cloned()
+        {
+            info.extensions
+                .authorization_settings
+                .insert(authorization.get_id().to_string(), settings);
+        }
         Self::perform_add_authorization(info, relative_to, relative_position, authorization)
     }
 
@@ -1626,6 +2077,9 @@ impl Workspaces {
         info.workspace
             .authorizations
             .remove_entity(authorization_id)?;
+        info.extensions
+            .authorization_settings
+            .remove(authorization_id);
         info.navigation
             .delete_navigation_entity(authorization_id, EntityType::Authorization);
         Ok(info.workspace.validate_selections())
@@ -1666,6 +2120,36 @@ impl Workspaces {
             }
         };
 
+        // Authorizations implemented by Apicize are stored as a placeholder, with
+        // their settings stored in workbook extensions
+        let extended = info.extensions.authorization_settings.get(&update.id);
+        if let Some(AuthorizationSettings::Cipher { .. }) = extended {
+            return Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                description: "Unable to edit encrypted authorization".to_string(),
+            }));
+        }
+        let extended_type = match extended {
+            Some(AuthorizationSettings::Plain(settings)) => Some(settings.get_update_type()),
+            _ => None,
+        };
+        let was_extended = extended_type.is_some();
+        match update
+            .auth_type
+            .as_ref()
+            .and_then(ExtendedAuthorization::from_update_type)
+        {
+            Some(settings) if extended_type.as_ref() != update.auth_type.as_ref() => {
+                *auth = settings.create_placeholder(&update.id, auth.get_name());
+                info.extensions
+                    .authorization_settings
+                    .insert(update.id.clone(), AuthorizationSettings::Plain(settings));
+            }
+            None if was_extended && update.auth_type.is_some() => {
+                info.extensions.authorization_settings.remove(&update.id);
+            }
+            _ => {}
+        }
+
         // Make sure that we haven't switched authorization types
         match update.auth_type {
             Some(AuthorizationUpdateType::Basic) => {
@@ -1705,7 +2189,7 @@ impl Workspaces {
                 }
             },
             Some(AuthorizationUpdateType::OAuth2Pkce) => match auth {
-                AuthorizationPlain::OAuth2Pkce { .. } => {}
+                AuthorizationPlain::OAuth2Pkce { .. } if !was_extended => {}
                 _ => {
                     *auth = AuthorizationPlain::OAuth2Pkce {
                         id: update.id.to_string(),
@@ -1725,7 +2209,7 @@ impl Workspaces {
                 }
             },
             Some(AuthorizationUpdateType::ApiKey) => match auth {
-                AuthorizationPlain::ApiKey { .. } => {}
+                AuthorizationPlain::ApiKey { .. } if !was_extended => {}
                 _ => {
                     *auth = AuthorizationPlain::ApiKey {
                         id: update.id.to_string(),
@@ -1738,7 +2222,14 @@ impl Workspaces {
                     };
                 }
             },
-            None => {}
+            Some(
+                AuthorizationUpdateType::AwsSigV4
+                | AuthorizationUpdateType::Digest
+                | AuthorizationUpdateType::OAuth2Device
+                | AuthorizationUpdateType::OAuth2Password
+                | AuthorizationUpdateType::OAuth2JwtBearer,
+            )
+            | None => {}
         }
 
         if let Some(updated_name) = &update.name {
@@ -1874,6 +2365,119 @@ impl Workspaces {
             }
         }
 
+        let settings = info.extensions.authorization_settings.get_mut(&update.id);
+        match settings {
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::AwsSigV4(parameters))) => {
+                for (value, updated) in [
+                    (&mut parameters.access_key_id, &update.access_key_id),
+                    (&mut parameters.secret_access_key, &update.secret_access_key),
+                    (&mut parameters.session_token, &update.session_token),
+                    (&mut parameters.region, &update.region),
+                    (&mut parameters.service, &update.service),
+                ] {
+                    if let Some(updated) = updated {
+                        *value = updated.trim().to_string();
+                    }
+                }
+            }
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::Digest(parameters))) => {
+                if let Some(username) = &update.username {
+                    parameters.username = username.to_string();
+                }
+                if let Some(password) = &update.password {
+                    parameters.password = password.to_string();
+                }
+            }
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::OAuth2Device(parameters))) => {
+                for (value, updated) in [
+                    (
+                        &mut parameters.device_authorization_url,
+                        &update.device_authorization_url,
+                    ),
+                    (&mut parameters.access_token_url, &update.access_token_url),
+                    (&mut parameters.client_id, &update.client_id),
+                    (&mut parameters.client_secret, &update.client_secret),
+                    (&mut parameters.scope, &update.scope),
+                    (&mut parameters.audience, &update.audience),
+                ] {
+                    if let Some(updated) = updated {
+                        *value = updated.to_string();
+                    }
+                }
+                if let Some(selected_certificate) = &update.selected_certificate {
+                    parameters.selected_certificate = selected_certificate.clone();
+                }
+                if let Some(selected_proxy) = &update.selected_proxy {
+                    parameters.selected_proxy = selected_proxy.clone();
+                }
+            }
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::OAuth2Password(
+                parameters,
+            ))) => {
+                for (value, updated) in [
+                    (&mut parameters.access_token_url, &update.access_token_url),
+                    (&mut parameters.client_id, &update.client_id),
+                    (&mut parameters.client_secret, &update.client_secret),
+                    (&mut parameters.username, &update.username),
+                    (&mut parameters.password, &update.password),
+                    (&mut parameters.scope, &update.scope),
+                    (&mut parameters.audience, &update.audience),
+                ] {
+                    if let Some(updated) = updated {
+                        *value = updated.to_string();
+                    }
+                }
+                if let Some(send_credentials_in_body) = update.send_credentials_in_body {
+                    parameters.send_credentials_in_body = send_credentials_in_body;
+                }
+                if let Some(selected_certificate) = &update.selected_certificate {
+                    parameters.selected_certificate = selected_certificate.clone();
+                }
+                if let Some(selected_proxy) = &update.selected_proxy {
+                    parameters.selected_proxy = selected_proxy.clone();
+                }
+            }
+            Some(AuthorizationSettings::Plain(ExtendedAuthorization::OAuth2JwtBearer(
+                parameters,
+            ))) => {
+                for (value, updated) in [
+                    (&mut parameters.access_token_url, &update.access_token_url),
+                    (&mut parameters.client_id, &update.client_id),
+                    (&mut parameters.scope, &update.scope),
+                    (&mut parameters.issuer, &update.issuer),
+                    (&mut parameters.subject, &update.subject),
+                    (
+                        &mut parameters.assertion_audience,
+                        &update.assertion_audience,
+                    ),
+                ] {
+                    if let Some(updated) = updated {
+                        *value = updated.to_string();
+                    }
+                }
+                if let Some(algorithm) = update.algorithm {
+                    parameters.algorithm = algorithm;
+                }
+                if let Some(assertion_lifetime) = update.assertion_lifetime {
+                    parameters.assertion_lifetime = assertion_lifetime;
+                }
+                if let Some(signing_certificate) = &update.signing_certificate {
+                    parameters.signing_certificate = signing_certificate.clone();
+                }
+                if let Some(selected_certificate) = &update.selected_certificate {
+                    parameters.selected_certificate = selected_certificate.clone();
+                }
+                if let Some(selected_proxy) = &update.selected_proxy {
+                    parameters.selected_proxy = selected_proxy.clone();
+                }
+            }
+            _ => {}
+        }
+        AuthorizationSettings::validate_authorization(
+            info.extensions.authorization_settings.get(&update.id),
+            auth,
+        );
+
         if let Some(validation_warnings) = &update.validation_warnings {
             auth.set_validation_warnings(if validation_warnings.is_empty() {
                 None
@@ -1996,6 +2600,17 @@ impl Workspaces {
         )
     }
 
+    pub fn add_generated_certificate(
+        &mut self,
+        workspace_id: &str,
+        relative_to: Option<&str>,
+        relative_position: Option<IndexedEntityPosition>,
+        certificate: Certificate,
+    ) -> Result<String, ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        Self::perform_add_certificate(info, relative_to, relative_position, certificate)
+    }
+
     fn perform_add_certificate(
         info: &mut WorkspaceInfo,
         relative_to: Option<&str>,
@@ -2217,6 +2832,13 @@ impl Workspaces {
             },
             None => Proxy::default(),
         };
+        if let Some(other_id) = clone_from_id
+            && let Some(settings) = info.extensions.proxy_settings.get(other_id).cloned()
+        {
+            info.extensions
+                .proxy_settings
+                .insert(proxy.get_id().to_string(), settings);
+        }
         Self::perform_add_proxy(info, relative_to, relative_position, proxy)
     }
 
@@ -2250,6 +2872,39 @@ impl Workspaces {
         Ok(id)
     }
 
+    pub fn get_proxy_settings(
+        &self,
+        workspace_id: &str,
+        proxy_id: &str,
+    ) -> Result<ProxySettings, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        if !info.workspace.proxies.entities.contains_key(proxy_id) {
+            return Err(ApicizeAppError::InvalidProxy(proxy_id.to_string()));
+        }
+        Ok(info.extensions.get_proxy_settings(proxy_id))
+    }
+
+    /// Return the proxy and its settings, for resolving proxies outside of the workspace lock
+    pub fn get_proxy_for_resolution(
+        &self,
+        workspace_id: &str,
+        proxy_id: &str,
+    ) -> Result<(ProxyPlain, ProxySettings), ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        match info.workspace.proxies.entities.get(proxy_id) {
+            Some(Proxy::Plain(proxy)) => Ok((
+                proxy.as_ref().clone(),
+                info.extensions.get_proxy_settings(proxy_id),
+            )),
+            Some(Proxy::Cipher(_)) => {
+                Err(ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                    description: "Unable to resolve encrypted proxy".to_string(),
+                }))
+            }
+            None => Err(ApicizeAppError::InvalidProxy(proxy_id.to_string())),
+        }
+    }
+
     pub fn delete_proxy(
         &mut self,
         workspace_id: &str,
@@ -2258,6 +2913,7 @@ impl Workspaces {
         let info = self.get_workspace_info_mut(workspace_id)?;
         info.dirty = true;
         info.workspace.proxies.remove_entity(proxy_id)?;
+        info.extensions.proxy_settings.remove(proxy_id);
         info.navigation
             .delete_navigation_entity(proxy_id, EntityType::Proxy);
         Ok(info.workspace.validate_selections())
@@ -2307,6 +2963,66 @@ impl Workspaces {
             proxy.validate_url();
         }
 
+        let settings = info
+            .extensions
+            .proxy_settings
+            .entry(update.id.clone())
+            .or_default();
+
+        if let Some(mode) = update.mode {
+            settings.mode = mode;
+        }
+
+        if let Some(pac_file) = &update.pac_file {
+            let pac_file = pac_file.trim();
+            settings.pac_file = if pac_file.is_empty() {
+                None
+            } else {
+                Some(pac_file.to_string())
+            };
+        }
+
+        if let Some(scheme) = update.scheme {
+            settings.scheme = scheme;
+        }
+
+        if let Some(remote_dns) = update.remote_dns {
+            settings.remote_dns = remote_dns;
+        }
+
+        if let Some(no_proxy) = &update.no_proxy {
+            settings.no_proxy = no_proxy
+                .iter()
+                .map(|entry| entry.trim().to_string())
+                .filter(|entry| !entry.is_empty())
+                .collect();
+        }
+
+        match (&settings.credentials, &update.username, &update.password) {
+            // Encrypted credentials are only replaced when both values are updated
+            (Some(ProxyCredentials::Cipher { .. }), Some(username), Some(password)) => {
+                settings.set_credentials(username, password);
+            }
+            (Some(ProxyCredentials::Cipher { .. }), _, _) | (_, None, None) => {}
+            (credentials, username, password) => {
+                let (existing_username, existing_password) = match credentials {
+                    Some(ProxyCredentials::Plain { username, password }) => {
+                        (username.clone(), password.clone())
+                    }
+                    _ => (String::default(), String::default()),
+                };
+                settings.set_credentials(
+                    username.as_ref().unwrap_or(&existing_username),
+                    password.as_ref().unwrap_or(&existing_password),
+                );
+            }
+        }
+
+        settings.validate_proxy(proxy);
+        if settings.is_empty() {
+            info.extensions.proxy_settings.remove(&update.id);
+        }
+
         let updated_name = proxy.get_title();
         let updated_validation_state = proxy.validation_state;
         let updated_validation_warnings = proxy.validation_warnings.clone();
@@ -2376,6 +3092,11 @@ impl Workspaces {
             info.workspace.defaults.selected_data = selected_data_set.clone();
         }
 
+        if let Some(selected_trusted_roots) = &update.selected_trusted_roots {
+            info.extensions
+                .set_selected_trusted_roots(Navigation::DEFAULTS_ID, selected_trusted_roots);
+        }
+
         let old_validation_state = info.workspace.defaults.validation_state;
         if let Some(validation_warnings) = &update.validation_warnings {
             info.workspace
@@ -2774,31 +3495,259 @@ impl Workspaces {
         }
     }
 
-    /// Build a list of parameters, optionally including the default selections
-    /// for a specified request
-    pub fn list_parameters(
+    pub fn get_trusted_roots(
         &self,
         workspace_id: &str,
-        active_request_id: Option<&str>,
-    ) -> Result<WorkspaceParameters, ApicizeAppError> {
+        trusted_roots_id: &str,
+    ) -> Result<&TrustedRoots, ApicizeAppError> {
         let info = self.get_workspace_info(workspace_id)?;
+        match info.extensions.trusted_roots.get(trusted_roots_id) {
+            Some(t) => Ok(t),
+            None => Err(ApicizeAppError::InvalidTrustedRoots(
+                trusted_roots_id.into(),
+            )),
+        }
+    }
 
-        let mut scenarios = info.navigation.scenarios.generate_selection_list();
-        let mut authorizations = info.navigation.authorizations.generate_selection_list();
-        let mut certificates = info.navigation.certificates.generate_selection_list();
-        let mut proxies = info.navigation.proxies.generate_selection_list();
-        let mut data: Vec<Selection> = vec![Selection::new_none()];
-        data.extend(info.navigation.data_sets.iter().map(|d| Selection {
-            id: d.id.clone(),
-            name: d.name.clone(),
-        }));
-
-        if let Some(request_id) = active_request_id {
-            let mut default_scenario: Option<Selection> = None;
-            let mut default_authorization: Option<Selection> = None;
-            let mut default_certificate: Option<Selection> = None;
-            let mut default_proxy: Option<Selection> = None;
-            let mut default_data: Option<Selection> = None;
+    pub fn get_trusted_roots_title(
+        &self,
+        workspace_id: &str,
+        trusted_roots_id: &str,
+    ) -> Result<String, ApicizeAppError> {
+        Ok(self
+            .get_trusted_roots(workspace_id, trusted_roots_id)?
+            .get_title())
+    }
+
+    pub fn add_trusted_roots(
+        &mut self,
+        workspace_id: &str,
+        relative_to: Option<&str>,
+        relative_position: Option<IndexedEntityPosition>,
+        clone_from_id: Option<&str>,
+    ) -> Result<String, ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        info.dirty = true;
+        let mut trusted_roots = match clone_from_id {
+            Some(other_id) => match info.extensions.trusted_roots.get(other_id) {
+                Some(other) => other.clone_as_new(Self::create_copy_name(&other.name)),
+                None => return Err(ApicizeAppError::InvalidTrustedRoots(other_id.to_owned())),
+            },
+            None => TrustedRoots::default(),
+        };
+        trusted_roots.perform_validation();
+        let id = trusted_roots.id.clone();
+        info.extensions
+            .trusted_roots
+            .add_entity(trusted_roots, relative_to, relative_position)?;
+        Ok(id)
+    }
+
+    pub fn delete_trusted_roots(
+        &mut self,
+        workspace_id: &str,
+        trusted_roots_id: &str,
+    ) -> Result<Option<InvalidSelections>, ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        info.dirty = true;
+        info.extensions
+            .trusted_roots
+            .remove_entity(trusted_roots_id)?;
+        info.navigation
+            .delete_navigation_entity(trusted_roots_id, EntityType::TrustedRoots);
+
+        // Requests, groups and defaults referring to the deleted bundle revert to default
+        let invalid_ids = info
+            .extensions
+            .selected_trusted_roots
+            .iter()
+            .filter_map(|(id, selection)| {
+                if selection.id == trusted_roots_id {
+                    Some(id.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<String>>();
+
+        if invalid_ids.is_empty() {
+            return Ok(None);
+        }
+
+        for id in &invalid_ids {
+            info.extensions.selected_trusted_roots.remove(id);
+        }
+
+        let defaults = invalid_ids.iter().any(|id| id == Navigation::DEFAULTS_ID);
+        Ok(Some(InvalidSelections {
+            request_or_group_ids: invalid_ids
+                .into_iter()
+                .filter(|id| id != Navigation::DEFAULTS_ID)
+                .collect(),
+            authorization_ids: vec![],
+            defaults,
+        }))
+    }
+
+    pub fn move_trusted_roots(
+        &mut self,
+        workspace_id: &str,
+        trusted_roots_id: &str,
+        relative_to: &str,
+        relative_position: IndexedEntityPosition,
+    ) -> Result<bool, ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        Ok(info.extensions.trusted_roots.move_entity(
+            trusted_roots_id,
+            relative_to,
+            relative_position,
+        )?)
+    }
+
+    pub fn update_trusted_roots(
+        &mut self,
+        workspace_id: &str,
+        update: &TrustedRootsUpdate,
+    ) -> Result<UpdateWithNavigationResponse, ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        info.dirty = true;
+
+        let trusted_roots = match info.extensions.trusted_roots.entities.get_mut(&update.id) {
+            Some(trusted_roots) => trusted_roots,
+            None => {
+                return Err(ApicizeAppError::InvalidTrustedRoots(update.id.to_string()));
+            }
+        };
+
+        if let Some(name) = &update.name {
+            trusted_roots.name = name.to_string();
+            trusted_roots.validate_name();
+        }
+
+        if let Some(pem) = &update.pem {
+            trusted_roots.pem = pem.to_string();
+            trusted_roots.validate_pem();
+        }
+
+        if let Some(include_system_roots) = update.include_system_roots {
+            trusted_roots.include_system_roots = include_system_roots;
+        }
+
+        let updated_name = trusted_roots.get_title();
+        let updated_validation_state = trusted_roots.validation_state;
+        let updated_validation_warnings = trusted_roots.validation_warnings.clone();
+        let updated_validation_errors = trusted_roots.validation_errors.clone();
+
+        let response = UpdateWithNavigationResponse {
+            navigation: info.check_parameter_navigation_update(
+                &update.id,
+                &updated_name,
+                updated_validation_state,
+                EntityType::TrustedRoots,
+            ),
+            validation_warnings: updated_validation_warnings,
+            validation_errors: updated_validation_errors,
+        };
+
+        // Update request entry and default selections
+        for selection in info.extensions.selected_trusted_roots.values_mut() {
+            if selection.id == update.id {
+                selection.name = updated_name.clone();
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Return the trusted root bundle selection in effect for the specified request or group,
+    /// checking parents and then workbook defaults if not explicitly set
+    fn find_trusted_roots_selection(
+        info: &WorkspaceInfo,
+        request_id: &str,
+        include_self: bool,
+    ) -> Option<Selection> {
+        let mut id = Some(request_id.to_string());
+        let mut is_self = true;
+
+        while let Some(check_id) = id {
+            if include_self || !is_self {
+                let selection = info.extensions.get_selected_trusted_roots(&check_id);
+                if !selection.is_default() {
+                    return Some(selection);
+                }
+            }
+            is_self = false;
+            id = Self::get_request_parent_id(&check_id, &info.workspace);
+        }
+
+        let selection = info
+            .extensions
+            .get_selected_trusted_roots(Navigation::DEFAULTS_ID);
+        if selection.is_default() {
+            None
+        } else {
+            Some(selection)
+        }
+    }
+
+    /// Return the trusted root bundle in effect for the specified request, if any
+    pub fn get_request_active_trusted_roots(
+        &self,
+        workspace_id: &str,
+        request_id: &str,
+    ) -> Result<Option<TrustedRoots>, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        match Self::find_trusted_roots_selection(info, request_id, true) {
+            Some(selection) if selection.id != Selection::NO_SELECTION_ID => {
+                match info.extensions.trusted_roots.get(&selection.id) {
+                    Some(trusted_roots) => Ok(Some(trusted_roots.clone())),
+                    None => Err(ApicizeAppError::InvalidTrustedRoots(selection.id)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the trusted root bundle selection for a request, group or the workbook defaults
+    pub fn get_selected_trusted_roots(
+        &self,
+        workspace_id: &str,
+        entity_id: &str,
+    ) -> Result<Selection, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        Ok(info.extensions.get_selected_trusted_roots(entity_id))
+    }
+
+    /// Build a list of parameters, optionally including the default selections
+    /// for a specified request
+    pub fn list_parameters(
+        &self,
+        workspace_id: &str,
+        active_request_id: Option<&str>,
+    ) -> Result<WorkspaceParameters, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+
+        let mut scenarios = info.navigation.scenarios.generate_selection_list();
+        let mut authorizations = info.navigation.authorizations.generate_selection_list();
+        let mut certificates = info.navigation.certificates.generate_selection_list();
+        let mut proxies = info.navigation.proxies.generate_selection_list();
+        let mut data: Vec<Selection> = vec![Selection::new_none()];
+        data.extend(info.navigation.data_sets.iter().map(|d| Selection {
+            id: d.id.clone(),
+            name: d.name.clone(),
+        }));
+        let mut trusted_roots: Vec<Selection> = vec![Selection::new_none()];
+        trusted_roots.extend(info.navigation.trusted_roots.iter().map(|t| Selection {
+            id: t.id.clone(),
+            name: t.name.clone(),
+        }));
+
+        if let Some(request_id) = active_request_id {
+            let mut default_scenario: Option<Selection> = None;
+            let mut default_authorization: Option<Selection> = None;
+            let mut default_certificate: Option<Selection> = None;
+            let mut default_proxy: Option<Selection> = None;
+            let mut default_data: Option<Selection> = None;
 
             let mut id = request_id.to_string();
             let mut is_self = true;
@@ -2942,6 +3891,15 @@ impl Workspaces {
             Self::insert_default_selection(&default_certificate, &mut certificates);
             Self::insert_default_selection(&default_proxy, &mut proxies);
             Self::insert_default_selection(&default_data, &mut data);
+
+            let default_trusted_roots = Self::find_trusted_roots_selection(info, request_id, false)
+                .map(
+                    |selection| match trusted_roots.iter().find(|s| s.id == selection.id) {
+                        Some(m) => m.clone(),
+                        None => selection,
+                    },
+                );
+            Self::insert_default_selection(&default_trusted_roots, &mut trusted_roots);
         }
 
         Ok(WorkspaceParameters {
@@ -2950,11 +3908,974 @@ impl Workspaces {
             certificates,
             proxies,
             data,
+            trusted_roots,
         })
     }
+    /// Apply an update to the specified entity
+    pub fn update_entity(
+        &mut self,
+        workspace_id: &str,
+        entity_update: &EntityUpdate,
+    ) -> Result<UpdateWithNavigationResponse, ApicizeAppError> {
+        match entity_update {
+            EntityUpdate::Request(request) => self.update_request(workspace_id, request),
+            EntityUpdate::RequestGroup(group) => self.update_group(workspace_id, group),
+            EntityUpdate::Scenario(scenario) => self.update_scenario(workspace_id, scenario),
+            EntityUpdate::Authorization(authorization) => {
+                self.update_authorization(workspace_id, authorization)
+            }
+            EntityUpdate::Certificate(certificate) => {
+                self.update_certificate(workspace_id, certificate)
+            }
+            EntityUpdate::Proxy(proxy) => self.update_proxy(workspace_id, proxy),
+            EntityUpdate::DataSet(data_set) => self.update_data_set(workspace_id, data_set),
+            EntityUpdate::TrustedRoots(trusted_roots) => {
+                self.update_trusted_roots(workspace_id, trusted_roots)
+            }
+            EntityUpdate::Defaults(defaults) => self.update_defaults(workspace_id, defaults),
+        }
+    }
+
+    /// Delete the specified entity, returning entities whose selections were reset
+    pub fn delete_entity(
+        &mut self,
+        workspace_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<Option<InvalidSelections>, ApicizeAppError> {
+        match entity_type {
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group => {
+                self.delete_request_entry(workspace_id, entity_id)
+            }
+            EntityType::Scenario => self.delete_scenario(workspace_id, entity_id),
+            EntityType::Authorization => self.delete_authorization(workspace_id, entity_id),
+            EntityType::Certificate => self.delete_certificate(workspace_id, entity_id),
+            EntityType::Proxy => self.delete_proxy(workspace_id, entity_id),
+            EntityType::DataSet => self.delete_data_set(workspace_id, entity_id),
+            EntityType::TrustedRoots => self.delete_trusted_roots(workspace_id, entity_id),
+            _ => Err(ApicizeAppError::InvalidOperation(
+                "Unable to perform delete on entity".to_owned(),
+            )),
+        }
+    }
+
+    /// Move the specified entity, returning true if it was moved
+    pub fn move_entity(
+        &mut self,
+        workspace_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+        relative_to: &str,
+        relative_position: IndexedEntityPosition,
+    ) -> Result<bool, ApicizeAppError> {
+        match entity_type {
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group => {
+                self.move_request_entry(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::Scenario => {
+                self.move_scenario(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::Authorization => {
+                self.move_authorization(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::Certificate => {
+                self.move_certificate(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::DataSet => {
+                self.move_data_set(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::Proxy => {
+                self.move_proxy(workspace_id, entity_id, relative_to, relative_position)
+            }
+            EntityType::TrustedRoots => {
+                self.move_trusted_roots(workspace_id, entity_id, relative_to, relative_position)
+            }
+            _ => Err(ApicizeAppError::InvalidOperation(format!(
+                "Unable to move {entity_type}",
+            ))),
+        }
+    }
+
+    /// Return entity and navigation update notifications for requests, groups,
+    /// authorizations and defaults whose selections were reset
+    pub fn generate_selection_updates(
+        &self,
+        workspace_id: &str,
+        invalid_selections: &InvalidSelections,
+    ) -> Result<Vec<(EntityUpdate, UpdatedNavigationEntry)>, ApicizeAppError> {
+        let mut notifications = invalid_selections
+            .request_or_group_ids
+            .iter()
+            .filter_map(|id| {
+                self.generate_request_selection_update(workspace_id, id)
+                    .ok()
+            })
+            .chain(
+                invalid_selections
+                    .authorization_ids
+                    .iter()
+                    .filter_map(|id| {
+                        self.generate_authorization_selection_update(workspace_id, id)
+                            .ok()
+                    }),
+            )
+            .collect::<Vec<(EntityUpdate, UpdatedNavigationEntry)>>();
+
+        if invalid_selections.defaults {
+            let defaults = self.get_defaults(workspace_id)?;
+            notifications.push((
+                EntityUpdate::Defaults(DefaultsUpdate {
+                    entity_type: EntityType::Defaults,
+                    selected_scenario: Some(defaults.selected_scenario),
+                    selected_authorization: Some(defaults.selected_authorization),
+                    selected_certificate: Some(defaults.selected_certificate),
+                    selected_proxy: Some(defaults.selected_proxy),
+                    selected_data: Some(defaults.selected_data),
+                    selected_trusted_roots: Some(
+                        self.get_selected_trusted_roots(workspace_id, Navigation::DEFAULTS_ID)?,
+                    ),
+                    validation_warnings: defaults.validation_warnings.clone(),
+                }),
+                UpdatedNavigationEntry {
+                    entity_type: EntityType::Defaults,
+                    execution_state: ExecutionState::empty(),
+                    disabled: false,
+                    id: Navigation::DEFAULTS_ID.to_string(),
+                    name: Navigation::DEFAULTS_NAME.to_string(),
+                    validation_state: defaults.validation_state,
+                    encrypted: false,
+                    parameter_store: None,
+                },
+            ))
+        }
+
+        Ok(notifications)
+    }
+
+    /// Return an update that reverts the specified update by restoring the current
+    /// values of the updated entity, or None if they are encrypted and cannot be restored
+    pub fn generate_undo_update(
+        &self,
+        workspace_id: &str,
+        entity_update: &EntityUpdate,
+    ) -> Result<Option<EntityUpdate>, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        let workspace = &info.workspace;
+
+        Ok(Some(match entity_update {
+            EntityUpdate::Request(update) => match workspace.requests.entities.get(&update.id) {
+                Some(RequestEntry::Request(request)) => {
+                    EntityUpdate::Request(RequestUpdate::from_request(
+                        request,
+                        info.extensions.get_selected_trusted_roots(&request.id),
+                    ))
+                }
+                _ => return Err(ApicizeAppError::InvalidRequest(update.id.clone())),
+            },
+            EntityUpdate::RequestGroup(update) => {
+                match workspace.requests.entities.get(&update.id) {
+                    Some(RequestEntry::Group(group)) => {
+                        EntityUpdate::RequestGroup(RequestGroupUpdate::from_group(
+                            group,
+                            info.extensions.get_selected_trusted_roots(&group.id),
+                        ))
+                    }
+                    _ => return Err(ApicizeAppError::InvalidGroup(update.id.clone())),
+                }
+            }
+            EntityUpdate::Scenario(update) => match workspace.scenarios.entities.get(&update.id) {
+                Some(Scenario::Plain(scenario)) => EntityUpdate::Scenario(
+                    ScenarioUpdate::from(scenario.as_ref().clone())
+                        .with_secret_variables(info.extensions.secret_variables.get(&update.id)),
+                ),
+                Some(Scenario::Cipher(_)) => return Ok(None),
+                None => return Err(ApicizeAppError::InvalidScenario(update.id.clone())),
+            },
+            EntityUpdate::Authorization(update) => {
+                match workspace.authorizations.entities.get(&update.id) {
+                    Some(Authorization::Plain(authorization)) => {
+                        let undo = AuthorizationUpdate::from(authorization.as_ref().clone());
+                        EntityUpdate::Authorization(
+                            match info.extensions.authorization_settings.get(&update.id) {
+                                Some(AuthorizationSettings::Plain(settings)) => {
+                                    undo.with_settings(settings)
+                                }
+                                Some(AuthorizationSettings::Cipher { .. }) => {
+                                    return Ok(None);
+                                }
+                                None => undo,
+                            },
+                        )
+                    }
+                    Some(Authorization::Cipher(_)) => return Ok(None),
+                    None => return Err(ApicizeAppError::InvalidAuthorization(update.id.clone())),
+                }
+            }
+            EntityUpdate::Certificate(update) => {
+                match workspace.certificates.entities.get(&update.id) {
+                    Some(Certificate::Plain(certificate)) => EntityUpdate::Certificate(
+                        CertificateUpdate::from(certificate.as_ref().clone()),
+                    ),
+                    Some(Certificate::Cipher(_)) => return Ok(None),
+                    None => return Err(ApicizeAppError::InvalidCertificate(update.id.clone())),
+                }
+            }
+            EntityUpdate::Proxy(update) => match workspace.proxies.entities.get(&update.id) {
+                Some(Proxy::Plain(proxy)) => EntityUpdate::Proxy(
+                    ProxyUpdate::from(proxy.as_ref().clone())
+                        .with_settings(&info.extensions.get_proxy_settings(&update.id)),
+                ),
+                Some(Proxy::Cipher(_)) => return Ok(None),
+                None => return Err(ApicizeAppError::InvalidProxy(update.id.clone())),
+            },
+            EntityUpdate::DataSet(update) => match workspace.data.entities.get(&update.id) {
+                Some(data_set) => EntityUpdate::DataSet(DataSetUpdate::from_data_set(
+                    data_set,
+                    info.data_set_content.get(&update.id),
+                )),
+                None => return Err(ApicizeAppError::InvalidDataSet(update.id.clone())),
+            },
+            EntityUpdate::Defaults(..) => EntityUpdate::Defaults(DefaultsUpdate::from_defaults(
+                &workspace.defaults,
+                info.extensions
+                    .get_selected_trusted_roots(Navigation::DEFAULTS_ID),
+            )),
+            EntityUpdate::TrustedRoots(update) => {
+                match info.extensions.trusted_roots.entities.get(&update.id) {
+                    Some(trusted_roots) => {
+                        EntityUpdate::TrustedRoots(TrustedRootsUpdate::from(trusted_roots.clone()))
+                    }
+                    None => return Err(ApicizeAppError::InvalidTrustedRoots(update.id.clone())),
+                }
+            }
+        }))
+    }
+
+    /// Return an operation that moves an entity back to its current position, if it can be
+    /// positioned relative to another entity
+    pub fn generate_undo_move(
+        &self,
+        workspace_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<Option<UndoOperation>, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        let workspace = &info.workspace;
+        let anchor = match entity_type {
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group => {
+                find_anchor(&workspace.requests, entity_id)
+            }
+            EntityType::Scenario => find_anchor(&workspace.scenarios, entity_id),
+            EntityType::Authorization => find_anchor(&workspace.authorizations, entity_id),
+            EntityType::Certificate => find_anchor(&workspace.certificates, entity_id),
+            EntityType::Proxy => find_anchor(&workspace.proxies, entity_id),
+            EntityType::DataSet => find_anchor(&workspace.data, entity_id),
+            EntityType::TrustedRoots => find_anchor(&info.extensions.trusted_roots, entity_id),
+            _ => None,
+        };
+        Ok(
+            anchor.map(|(relative_to_id, relative_position)| UndoOperation::Move {
+                entity_type,
+                entity_id: entity_id.to_string(),
+                relative_to_id,
+                relative_position,
+            }),
+        )
+    }
+
+    /// Capture an entity, its descendants and related settings prior to deleting it,
+    /// including the selections that will be reset when it is deleted
+    pub fn capture_deleted_entity(
+        &self,
+        workspace_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<DeletedEntity, ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        let workspace = &info.workspace;
+        let extensions = &info.extensions;
+        let invalid_id = || ApicizeAppError::InvalidOperation(format!("Invalid ID {entity_id}"));
+
+        let entities = match entity_type {
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group => {
+                DeletedEntities::Requests(
+                    RemovedEntities::capture(&workspace.requests, entity_id)
+                        .ok_or_else(invalid_id)?,
+                )
+            }
+            EntityType::Scenario => DeletedEntities::Scenario(
+                RemovedEntities::capture(&workspace.scenarios, entity_id).ok_or_else(invalid_id)?,
+                extensions.secret_variables.get(entity_id).cloned(),
+            ),
+            EntityType::Authorization => DeletedEntities::Authorization(
+                RemovedEntities::capture(&workspace.authorizations, entity_id)
+                    .ok_or_else(invalid_id)?,
+                extensions.authorization_settings.get(entity_id).cloned(),
+            ),
+            EntityType::Certificate => DeletedEntities::Certificate(
+                RemovedEntities::capture(&workspace.certificates, entity_id)
+                    .ok_or_else(invalid_id)?,
+            ),
+            EntityType::Proxy => DeletedEntities::Proxy(
+                RemovedEntities::capture(&workspace.proxies, entity_id).ok_or_else(invalid_id)?,
+                extensions.proxy_settings.get(entity_id).cloned(),
+            ),
+            EntityType::DataSet => DeletedEntities::DataSet(
+                RemovedEntities::capture(&workspace.data, entity_id).ok_or_else(invalid_id)?,
+                info.data_set_content.get(entity_id).cloned(),
+            ),
+            EntityType::TrustedRoots => DeletedEntities::TrustedRoots(
+                RemovedEntities::capture(&extensions.trusted_roots, entity_id)
+                    .ok_or_else(invalid_id)?,
+            ),
+            _ => {
+                return Err(ApicizeAppError::InvalidOperation(
+                    "Unable to perform delete on entity".to_owned(),
+                ));
+            }
+        };
+
+        // Requests and groups are not selectable, so only parameter deletions reset selections
+        let mut selections = Vec::<EntityUpdate>::new();
+        if !matches!(entities, DeletedEntities::Requests(..)) {
+            let refers_to = |selection: &Selection| selection.id == entity_id;
+            let trusted_roots_refers_to =
+                |id: &str| refers_to(&extensions.get_selected_trusted_roots(id));
+
+            for entry in workspace.requests.entities.values() {
+                match entry {
+                    RequestEntry::Request(request) => {
+                        if refers_to(&request.selected_scenario)
+                            || refers_to(&request.selected_authorization)
+                            || refers_to(&request.selected_certificate)
+                            || refers_to(&request.selected_proxy)
+                            || refers_to(&request.selected_data)
+                            || trusted_roots_refers_to(&request.id)
+                        {
+                            let mut update = RequestUpdate::from_selections(request);
+                            update.selected_trusted_roots =
+                                Some(extensions.get_selected_trusted_roots(&request.id));
+                            selections.push(EntityUpdate::Request(update));
+                        }
+                    }
+                    RequestEntry::Group(group) => {
+                        if refers_to(&group.selected_scenario)
+                            || refers_to(&group.selected_authorization)
+                            || refers_to(&group.selected_certificate)
+                            || refers_to(&group.selected_proxy)
+                            || refers_to(&group.selected_data)
+                            || trusted_roots_refers_to(&group.id)
+                        {
+                            let mut update = RequestGroupUpdate::from_selections(group);
+                            update.selected_trusted_roots =
+                                Some(extensions.get_selected_trusted_roots(&group.id));
+                            selections.push(EntityUpdate::RequestGroup(update));
+                        }
+                    }
+                }
+            }
+
+            for authorization in workspace.authorizations.entities.values() {
+                if let Authorization::Plain(authorization) = authorization
+                    && let AuthorizationPlain::OAuth2Client {
+                        id,
+                        selected_certificate,
+                        selected_proxy,
+                        ..
+                    } = authorization.as_ref()
+                    && (refers_to(selected_certificate) || refers_to(selected_proxy))
+                {
+                    selections.push(EntityUpdate::Authorization(
+                        AuthorizationUpdate::from_selections(
+                            id,
+                            selected_certificate,
+                            selected_proxy,
+                        ),
+                    ));
+                }
+            }
+
+            let defaults = &workspace.defaults;
+            if refers_to(&defaults.selected_scenario)
+                || refers_to(&defaults.selected_authorization)
+                || refers_to(&defaults.selected_certificate)
+                || refers_to(&defaults.selected_proxy)
+                || refers_to(&defaults.selected_data)
+                || trusted_roots_refers_to(Navigation::DEFAULTS_ID)
+            {
+                selections.push(EntityUpdate::Defaults(DefaultsUpdate::from_defaults(
+                    defaults,
+                    extensions.get_selected_trusted_roots(Navigation::DEFAULTS_ID),
+                )));
+            }
+        }
+
+        Ok(DeletedEntity {
+            entity_type,
+            entity_id: entity_id.to_string(),
+            entities,
+            selections,
+        })
+    }
+
+    /// Re-insert a deleted entity and restore selections that referred to it
+    fn restore_deleted_entity(
+        &mut self,
+        workspace_id: &str,
+        deleted: DeletedEntity,
+        applied: &mut AppliedUndo,
+    ) -> Result<(), ApicizeAppError> {
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        info.dirty = true;
+        let workspace = &mut info.workspace;
+        let extensions = &mut info.extensions;
+        let id = deleted.entity_id;
+
+        match deleted.entities {
+            DeletedEntities::Requests(removed) => removed.restore(&mut workspace.requests)?,
+            DeletedEntities::Scenario(removed, secret_variables) => {
+                removed.restore(&mut workspace.scenarios)?;
+                if let Some(secret_variables) = secret_variables {
+                    extensions.secret_variables.insert(id, secret_variables);
+                }
+            }
+            DeletedEntities::Authorization(removed, settings) => {
+                removed.restore(&mut workspace.authorizations)?;
+                if let Some(settings) = settings {
+                    extensions.authorization_settings.insert(id, settings);
+                }
+            }
+            DeletedEntities::Certificate(removed) => {
+                removed.restore(&mut workspace.certificates)?
+            }
+            DeletedEntities::Proxy(removed, settings) => {
+                removed.restore(&mut workspace.proxies)?;
+                if let Some(settings) = settings {
+                    extensions.proxy_settings.insert(id, settings);
+                }
+            }
+            DeletedEntities::DataSet(removed, content) => {
+                removed.restore(&mut workspace.data)?;
+                if let Some(content) = content {
+                    info.data_set_content.insert(id, content);
+                }
+            }
+            DeletedEntities::TrustedRoots(removed) => {
+                removed.restore(&mut extensions.trusted_roots)?
+            }
+        }
+
+        for selection in deleted.selections {
+            self.update_entity(workspace_id, &selection)?;
+            applied.updates.push(selection);
+        }
+
+        self.get_workspace_mut(workspace_id)?.validate_selections();
+        Ok(())
+    }
+
+    /// Apply an undo or redo operation, returning the operation that reverts it
+    fn apply_undo_operation(
+        &mut self,
+        workspace_id: &str,
+        operation: UndoOperation,
+        applied: &mut AppliedUndo,
+    ) -> Result<UndoOperation, ApicizeAppError> {
+        match operation {
+            UndoOperation::Update(entity_update) => {
+                let undo = self
+                    .generate_undo_update(workspace_id, &entity_update)?
+                    .ok_or_else(|| {
+                        ApicizeAppError::ApicizeError(ApicizeError::Encryption {
+                            description: "Encrypted parameters cannot be updated".to_string(),
+                        })
+                    })?;
+                self.update_entity(workspace_id, &entity_update)?;
+                applied.updates.push(entity_update);
+                Ok(UndoOperation::Update(undo))
+            }
+            UndoOperation::UpdateBody { request_id, body } => {
+                let undo = match self
+                    .get_workspace(workspace_id)?
+                    .requests
+                    .entities
+                    .get(&request_id)
+                {
+                    Some(RequestEntry::Request(request)) => UndoOperation::UpdateBody {
+                        request_id: request_id.clone(),
+                        body: request.body.clone(),
+                    },
+                    _ => return Err(ApicizeAppError::InvalidRequest(request_id)),
+                };
+                let body_info = RequestBodyInfo {
+                    id: request_id,
+                    body_mime_type: body.as_ref().map(Workspaces::get_body_type),
+                    body_length: body.as_ref().map(Workspaces::get_body_length),
+                    body,
+                };
+                self.update_request_body(workspace_id, &body_info)?;
+                applied
+                    .updates
+                    .push(EntityUpdate::Request(RequestUpdate::from_body_info(
+                        body_info,
+                    )));
+                Ok(undo)
+            }
+            UndoOperation::Delete {
+                entity_type,
+                entity_id,
+            } => {
+                let deleted = self.capture_deleted_entity(workspace_id, entity_type, &entity_id)?;
+                if let Some(invalid_selections) =
+                    self.delete_entity(workspace_id, entity_type, &entity_id)?
+                {
+                    applied.updates.extend(
+                        self.generate_selection_updates(workspace_id, &invalid_selections)?
+                            .into_iter()
+                            .map(|(update, _)| update),
+                    );
+                }
+                applied.deleted.push((entity_type, entity_id));
+                Ok(UndoOperation::Restore(Box::new(deleted)))
+            }
+            UndoOperation::Restore(deleted) => {
+                let entity_type = deleted.entity_type;
+                let entity_id = deleted.entity_id.clone();
+                self.restore_deleted_entity(workspace_id, *deleted, applied)?;
+                Ok(UndoOperation::Delete {
+                    entity_type,
+                    entity_id,
+                })
+            }
+            UndoOperation::Move {
+                entity_type,
+                entity_id,
+                relative_to_id,
+                relative_position,
+            } => {
+                let undo = self
+                    .generate_undo_move(workspace_id, entity_type, &entity_id)?
+                    .ok_or_else(|| {
+                        ApicizeAppError::InvalidOperation(format!(
+                            "Unable to move {entity_type} {entity_id}"
+                        ))
+                    })?;
+                self.move_entity(
+                    workspace_id,
+                    entity_type,
+                    &entity_id,
+                    &relative_to_id,
+                    relative_position,
+                )?;
+                self.get_workspace_info_mut(workspace_id)?.dirty = true;
+                Ok(undo)
+            }
+        }
+    }
+
+    /// Apply a step's operations in reverse order of how they were recorded, returning
+    /// the operations that revert them (even if an operation fails)
+    fn apply_undo_operations(
+        &mut self,
+        workspace_id: &str,
+        operations: Vec<UndoOperation>,
+    ) -> (Vec<UndoOperation>, Result<AppliedUndo, ApicizeAppError>) {
+        let mut applied = AppliedUndo::default();
+        let mut reverts = Vec::with_capacity(operations.len());
+        for operation in operations.into_iter().rev() {
+            match self.apply_undo_operation(workspace_id, operation, &mut applied) {
+                Ok(revert) => reverts.push(revert),
+                Err(err) => return (reverts, Err(err)),
+            }
+        }
+        (reverts, Ok(applied))
+    }
+
+    /// Undo the workspace's last recorded edit, returning None if there is nothing to undo
+    pub fn undo(&mut self, workspace_id: &str) -> Result<Option<AppliedUndo>, ApicizeAppError> {
+        let Some(operations) = self
+            .get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .take_undo()
+        else {
+            return Ok(None);
+        };
+        let (reverts, result) = self.apply_undo_operations(workspace_id, operations);
+        self.get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .push_redo(reverts);
+        result.map(Some)
+    }
+
+    /// Redo the workspace's last undone edit, returning None if there is nothing to redo
+    pub fn redo(&mut self, workspace_id: &str) -> Result<Option<AppliedUndo>, ApicizeAppError> {
+        let Some(operations) = self
+            .get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .take_redo()
+        else {
+            return Ok(None);
+        };
+        let (reverts, result) = self.apply_undo_operations(workspace_id, operations);
+        self.get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .push_undo(reverts);
+        result.map(Some)
+    }
+
+    /// Apply a bulk update to the selected requests, or to every request within a group,
+    /// recording the changes as a single step to undo and returning each applied update
+    /// along with its response
+    pub fn bulk_update_requests(
+        &mut self,
+        workspace_id: &str,
+        bulk_update: &BulkRequestUpdate,
+    ) -> Result<Vec<(RequestUpdate, UpdateWithNavigationResponse)>, ApicizeAppError> {
+        let workspace = self.get_workspace(workspace_id)?;
+        let request_ids = bulk_update.get_request_ids(&workspace.requests)?;
+        let updates = request_ids
+            .iter()
+            .map(|id| match workspace.requests.entities.get(id) {
+                Some(RequestEntry::Request(request)) => Ok(bulk_update.for_request(request)),
+                _ => Err(ApicizeAppError::InvalidRequest(id.clone())),
+            })
+            .collect::<Result<Vec<RequestUpdate>, ApicizeAppError>>()?;
+
+        let mut reverts = Vec::with_capacity(updates.len());
+        let mut results = Vec::with_capacity(updates.len());
+        let mut error = None;
+        for update in updates {
+            let entity_update = EntityUpdate::Request(update);
+            let applied = self
+                .generate_undo_update(workspace_id, &entity_update)
+                .and_then(|undo| {
+                    let response = self.update_entity(workspace_id, &entity_update)?;
+                    Ok((undo, response))
+                });
+            match applied {
+                Ok((undo, response)) => {
+                    reverts.extend(undo.map(UndoOperation::Update));
+                    if let EntityUpdate::Request(update) = entity_update {
+                        results.push((update, response));
+                    }
+                }
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+
+        // Record whatever was applied, even if a later update failed
+        self.get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .record(reverts);
+        match error {
+            Some(err) => Err(err),
+            None => Ok(results),
+        }
+    }
+
+    /// Replace matches of a search in requests, groups and scenarios, recording the changes
+    /// as a single step to undo, and returning the changed and skipped fields along with the
+    /// updates to send to sessions
+    pub fn replace(
+        &mut self,
+        workspace_id: &str,
+        options: &ReplaceOptions,
+    ) -> Result<(ReplaceResults, AppliedUndo), ApicizeAppError> {
+        let info = self.get_workspace_info(workspace_id)?;
+        let planned = plan_replacement(&info.workspace, options)?;
+
+        let mut operations = Vec::new();
+        for request in &planned.requests {
+            operations.push(UndoOperation::Update(EntityUpdate::Request(
+                RequestUpdate::from_request(
+                    request,
+                    info.extensions.get_selected_trusted_roots(&request.id),
+                ),
+            )));
+            if let Some(RequestEntry::Request(existing)) =
+                info.workspace.requests.entities.get(&request.id)
+                && existing.body != request.body
+            {
+                operations.push(UndoOperation::UpdateBody {
+                    request_id: request.id.clone(),
+                    body: request.body.clone(),
+                });
+            }
+        }
+        for group in &planned.groups {
+            operations.push(UndoOperation::Update(EntityUpdate::RequestGroup(
+                RequestGroupUpdate::from_group(
+                    group,
+                    info.extensions.get_selected_trusted_roots(&group.id),
+                ),
+            )));
+        }
+        for scenario in planned.scenarios {
+            let secret_variables = info.extensions.secret_variables.get(&scenario.id);
+            operations.push(UndoOperation::Update(EntityUpdate::Scenario(
+                ScenarioUpdate::from(scenario).with_secret_variables(secret_variables),
+            )));
+        }
+
+        let (reverts, result) = self.apply_undo_operations(workspace_id, operations);
+        // Record whatever was applied, even if a later change failed
+        self.get_workspace_info_mut(workspace_id)?
+            .undo_history
+            .record(reverts);
+        Ok((
+            ReplaceResults {
+                fields: planned.fields,
+                skipped: planned.skipped,
+            },
+            result?,
+        ))
+    }
+
+    /// Bring changes made outside of Apicize to the workspace's workbook, private parameters,
+    /// extensions and data set files into the workspace.  If merging, unsaved changes are kept
+    /// (see `merge_workspace`), otherwise they are discarded.
+    pub fn apply_external_changes(
+        &mut self,
+        workspace_id: &str,
+        merge: bool,
+    ) -> Result<AppliedExternalChanges, ApicizeAppError> {
+        let vault_password = self.vault_password.clone();
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        if info.file_name.is_empty() {
+            return Err(ApicizeAppError::FileNameRequired());
+        }
+
+        let workbook_path = PathBuf::from(&info.file_name);
+        let changed_files = info.external_changes.get_changed_files();
+        let mut applied = AppliedExternalChanges::default();
+
+        if !merge
+            || changed_files.iter().any(|f| {
+                matches!(
+                    f,
+                    WatchedFile::Workbook
+                        | WatchedFile::WorkbookDirectory
+                        | WatchedFile::Private
+                        | WatchedFile::Extensions
+                )
+            })
+        {
+            let (disk, disk_extensions) = open_workbook(
+                &workbook_path,
+                info.workspace.private_password.clone(),
+                vault_password,
+            )?;
+            if merge {
+                let base = info.external_changes.base();
+                applied.conflicts =
+                    merge_workspace(&mut info.workspace, base.map(|(w, _)| w), &disk);
+                applied.conflicts.extend(merge_extensions(
+                    &mut info.extensions,
+                    base.map(|(_, e)| e),
+                    &disk_extensions,
+                    &info.workspace,
+                ));
+            } else {
+                info.workspace = disk.clone();
+                info.extensions = disk_extensions.clone();
+                info.dirty = false;
+            }
+            info.workspace.validate_selections();
+            info.external_changes
+                .set_base(Some((disk, disk_extensions)));
+            // Recorded edits may no longer apply to the reloaded or merged entities
+            info.undo_history = UndoHistory::default();
+            applied.workbook_changed = true;
+        }
+
+        // Reload external data set content that has been loaded, unless merging and there
+        // are unsaved changes to it
+        let data_sets = &info.workspace.data.entities;
+        info.data_set_content
+            .retain(|id, _| data_sets.contains_key(id));
+        let mut reload_data_sets = Vec::new();
+        for (data_set_id, content) in &info.data_set_content {
+            let data_set = &data_sets[data_set_id];
+            if data_set.source_type == DataSourceType::JSON || data_set.source.is_empty() {
+                continue;
+            }
+            if merge {
+                if !changed_files.contains(&WatchedFile::DataSet(data_set_id.clone())) {
+                    continue;
+                }
+                if content.dirty {
+                    applied.conflicts.push(MergeConflict {
+                        entity_type: EntityType::DataSet,
+                        entity_id: data_set_id.clone(),
+                        name: data_set.name.clone(),
+                    });
+                    continue;
+                }
+            }
+            reload_data_sets.push((data_set_id.clone(), data_set.source.clone()));
+        }
+
+        let data_path = std::path::absolute(&workbook_path)?
+            .parent()
+            .ok_or(ApicizeAppError::InvalidOperation(
+                "Unable to determine parent directory".to_string(),
+            ))?
+            .to_path_buf();
+        for (data_set_id, source) in reload_data_sets {
+            let absolute_file_name = build_absolute_file_name(&source, &data_path)?;
+            match self.load_data_set_from_file(
+                workspace_id,
+                &data_set_id,
+                &absolute_file_name,
+                &source,
+            ) {
+                Ok(response) => {
+                    let data_set = self.get_data_set(workspace_id, &data_set_id)?;
+                    applied.data_set_updates.push(EntityUpdate::DataSet(
+                        DataSetUpdate::from_data_set(&data_set, response.data_set_content.as_ref()),
+                    ));
+                }
+                Err(err) => {
+                    // File may have been removed, leave content as it was
+                    eprintln!("Unable to reload data set {data_set_id}: {err}");
+                }
+            }
+        }
+
+        let info = self.get_workspace_info_mut(workspace_id)?;
+        let requests = &info.workspace.requests.entities;
+        info.executions.retain(|id, _| requests.contains_key(id));
+        info.navigation = Navigation::new(&info.workspace, &info.extensions, &info.executions);
+        info.external_changes
+            .track(&info.file_name, &info.workspace);
+        Ok(applied)
+    }
+}
+
+/// Open a workbook, saved as a file or directory, along with its private parameters and
+/// extensions
+pub fn open_workbook(
+    workbook_path: &Path,
+    private_password: Option<String>,
+    vault_password: Option<String>,
+) -> Result<(Workspace, WorkbookExtensions), ApicizeError> {
+    let options = OpenWorkbookOptions {
+        override_default_scenario: Default::default(),
+        override_default_authorization: Default::default(),
+        override_default_certificate: Default::default(),
+        override_default_proxy: Default::default(),
+        override_data_seed: Default::default(),
+        private_password,
+        vault_password,
+    };
+    let workbook_path = workbook_path.to_path_buf();
+    let mut workspace = Workspace::open(
+        Some(&workbook_path),
+        workbook_path.parent().unwrap_or(Path::new(".")),
+        options,
+    )?;
+    if WorkbookFormat::detect(&workbook_path)? == WorkbookFormat::Directory {
+        open_directory(&mut workspace, &workbook_path)?;
+    }
+    let extensions = WorkbookExtensions::open(Some(&workbook_path), &mut workspace)?;
+    Ok((workspace, extensions))
+}
+
+/// Save a workspace, storing the workbook in the specified format
+pub fn save_workbook(
+    workspace: &Workspace,
+    params: &SaveWorkspaceParameters,
+    format: WorkbookFormat,
+) -> Result<(), ApicizeError> {
+    match &params.workbook_path {
+        Some(workbook_path) if params.include_workbook && format == WorkbookFormat::Directory => {
+            workspace.save(&SaveWorkspaceParameters {
+                workbook_path: params.workbook_path.clone(),
+                include_workbook: false,
+                include_private: params.include_private,
+                include_vault: params.include_vault,
+            })?;
+            save_directory(workspace, workbook_path)
+        }
+        _ => workspace.save(params).map(|_| ()),
+    }
 }
 
 impl WorkspaceInfo {
+    /// Build the information a session needs to display the workspace
+    pub fn build_initialization(
+        &self,
+        session: Session,
+        editor_count: usize,
+        private_env_var_set: bool,
+        vault_env_var_set: bool,
+        settings: ApicizeSettings,
+        clipboard_data_type: ClipboardDataType,
+    ) -> WorkspaceInitialization {
+        WorkspaceInitialization {
+            session,
+            navigation: self.navigation.clone(),
+            save_state: SessionSaveState {
+                file_name: self.file_name.clone(),
+                directory: self.directory.clone(),
+                display_name: self.display_name.clone(),
+                dirty: self.dirty,
+                editor_count,
+                can_undo: self.undo_history.can_undo(),
+                can_redo: self.undo_history.can_redo(),
+            },
+            private_lock_status: self.workspace.private_lock_status,
+            vault_lock_status: self.workspace.vault_lock_status,
+            private_env_var_set,
+            vault_env_var_set,
+            defaults: self.workspace.defaults.clone(),
+            settings,
+            executions: self
+                .executions
+                .iter()
+                .filter_map(|(id, exec)| match exec.execution_state {
+                    ExecutionState::RUNNING => Some((
+                        id.to_string(),
+                        ExecutionEvent::Start {
+                            execution_state: ExecutionState::RUNNING,
+                        },
+                    )),
+                    ExecutionState::ERROR => None,
+                    _ => Some((
+                        id.to_string(),
+                        ExecutionEvent::Complete(RequestExecution {
+                            menu: exec.menu.clone(),
+                            execution_state: exec.execution_state,
+                            active_summaries: exec.active_summaries.clone(),
+                        }),
+                    )),
+                })
+                .collect::<FxHashMap<String, ExecutionEvent>>(),
+            error: None,
+            clipboard_data_type,
+        }
+    }
+
+    /// Returns true if the entity exists in the workspace
+    pub fn contains_entity(&self, entity: &SessionEntity) -> bool {
+        let id = &entity.entity_id;
+        match entity.entity_type {
+            EntityType::RequestEntry | EntityType::Request | EntityType::Group => {
+                self.workspace.requests.entities.contains_key(id)
+            }
+            EntityType::Scenario => self.workspace.scenarios.entities.contains_key(id),
+            EntityType::Authorization => self.workspace.authorizations.entities.contains_key(id),
+            EntityType::Certificate => self.workspace.certificates.entities.contains_key(id),
+            EntityType::Proxy => self.workspace.proxies.entities.contains_key(id),
+            EntityType::DataSet => self.workspace.data.entities.contains_key(id),
+            EntityType::Defaults => true,
+            EntityType::TrustedRoots => self.extensions.trusted_roots.entities.contains_key(id),
+        }
+    }
+
     /// Check parameter and returns update to navigation if required
     pub fn check_parameter_navigation_update(
         &mut self,
@@ -2965,6 +4886,11 @@ impl WorkspaceInfo {
     ) -> Option<UpdatedNavigationEntry> {
         let entry = if entity_type == EntityType::DataSet {
             self.navigation.data_sets.iter_mut().find(|e| e.id == id)
+        } else if entity_type == EntityType::TrustedRoots {
+            self.navigation
+                .trusted_roots
+                .iter_mut()
+                .find(|e| e.id == id)
         } else {
             let section = match entity_type {
                 EntityType::Scenario => &mut self.navigation.scenarios,
@@ -3038,6 +4964,198 @@ impl WorkspaceInfo {
         }
     }
 
+    /// Return secret variable references of scenarios used when running the specified
+    /// request or descendants, indexed by scenario ID
+    pub fn get_execution_secret_variables(
+        &self,
+        request_or_group_id: &str,
+    ) -> BTreeMap<String, ScenarioSecretVariables> {
+        if self.extensions.secret_variables.is_empty() {
+            return BTreeMap::new();
+        }
+        let mut scenario_ids = HashSet::<String>::new();
+        append_selected_ids(
+            &self.workspace,
+            request_or_group_id,
+            |p| p.selected_scenario(),
+            &mut scenario_ids,
+        );
+        scenario_ids
+            .into_iter()
+            .filter_map(|id| {
+                self.extensions
+                    .secret_variables
+                    .get(&id)
+                    .map(|secrets| (id, secrets.clone()))
+            })
+            .collect()
+    }
+
+    /// Return the workbook's secret variable commands if the user has not trusted them, or
+    /// has trusted a different set of commands
+    pub fn get_untrusted_secret_commands(
+        &self,
+        trusted: &BTreeMap<String, String>,
+    ) -> BTreeSet<String> {
+        let commands = get_secret_commands(&self.extensions.secret_variables);
+        if commands.is_empty() {
+            return commands;
+        }
+        let fingerprint = get_commands_fingerprint(&commands);
+        if self.trusted_commands_fingerprint.as_ref() == Some(&fingerprint)
+            || (!self.file_name.is_empty() && trusted.get(&self.file_name) == Some(&fingerprint))
+        {
+            BTreeSet::new()
+        } else {
+            commands
+        }
+    }
+
+    /// Trust the workbook's current secret variable commands, returning their fingerprint
+    pub fn trust_secret_commands(&mut self) -> String {
+        let fingerprint =
+            get_commands_fingerprint(&get_secret_commands(&self.extensions.secret_variables));
+        self.trusted_commands_fingerprint = Some(fingerprint.clone());
+        fingerprint
+    }
+
+    /// Return substitution values from the scenario in effect for the specified request or
+    /// group (or the workbook's default scenario), for use outside of request execution
+    pub fn get_substitutions(&self, request_or_group_id: Option<&str>) -> HashMap<String, String> {
+        let selection = match request_or_group_id {
+            Some(id) => get_effective_selection(&self.workspace, id, |p| p.selected_scenario()),
+            None => Some(&self.workspace.defaults.selected_scenario)
+                .filter(|selection| !selection.is_default_or_none()),
+        };
+        let Some(scenario) =
+            selection.and_then(|selection| self.workspace.scenarios.entities.get(&selection.id))
+        else {
+            return HashMap::new();
+        };
+
+        let allowed_path = if self.directory.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(&self.directory))
+        };
+        match VariableCache::new(&allowed_path).get_scenario_values(scenario) {
+            Ok(values) => values
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = match value.as_ref().ok()? {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((format!("{{{{{name}}}}}"), value))
+                })
+                .collect(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Return OAuth2 tokens that may need renewal before running the specified request
+    /// or descendants
+    pub fn get_token_renewals(&self, request_or_group_id: &str) -> Vec<TokenRenewal> {
+        let mut authorization_ids = HashSet::<String>::new();
+        append_selected_ids(
+            &self.workspace,
+            request_or_group_id,
+            |p| p.selected_authorization(),
+            &mut authorization_ids,
+        );
+        if authorization_ids.is_empty() {
+            return Vec::new();
+        }
+        let subs = self.get_substitutions(Some(request_or_group_id));
+        authorization_ids
+            .into_iter()
+            .filter_map(|id| {
+                let Some(Authorization::Plain(auth)) =
+                    self.workspace.authorizations.entities.get(&id)
+                else {
+                    return None;
+                };
+                let source = match (
+                    self.extensions.authorization_settings.get(&id),
+                    auth.as_ref(),
+                ) {
+                    (Some(AuthorizationSettings::Plain(settings)), _) => {
+                        settings.get_token_source(&self.workspace, &subs)?
+                    }
+                    (Some(AuthorizationSettings::Cipher { .. }), _) => return None,
+                    (
+                        None,
+                        AuthorizationPlain::OAuth2Client {
+                            access_token_url,
+                            client_id,
+                            client_secret,
+                            audience,
+                            scope,
+                            selected_certificate,
+                            selected_proxy,
+                            send_credentials_in_body,
+                            ..
+                        },
+                    ) => Ok(TokenSource::ClientCredentials {
+                        token_url: clone_and_sub(access_token_url, &subs),
+                        client_id: clone_and_sub(client_id, &subs),
+                        client_secret: clone_and_sub(client_secret, &subs),
+                        send_credentials_in_body: send_credentials_in_body.unwrap_or_default(),
+                        scope: clone_and_sub(scope, &subs),
+                        audience: clone_and_sub(audience, &subs),
+                        certificate: self
+                            .workspace
+                            .certificates
+                            .get_optional(&selected_certificate.id)
+                            .cloned(),
+                        proxy: self
+                            .workspace
+                            .proxies
+                            .get_optional(&selected_proxy.id)
+                            .cloned(),
+                    }),
+                    (
+                        None,
+                        AuthorizationPlain::OAuth2Pkce {
+                            access_token_url,
+                            client_id,
+                            ..
+                        },
+                    ) => {
+                        // PKCE authorizations do not select a certificate or proxy, so
+                        // refresh using those selected for the request
+                        let certificate =
+                            get_effective_selection(&self.workspace, request_or_group_id, |p| {
+                                p.selected_certificate()
+                            })
+                            .and_then(|selection| {
+                                self.workspace.certificates.get_optional(&selection.id)
+                            });
+                        let proxy =
+                            get_effective_selection(&self.workspace, request_or_group_id, |p| {
+                                p.selected_proxy()
+                            })
+                            .and_then(|selection| {
+                                self.workspace.proxies.get_optional(&selection.id)
+                            });
+                        build_client(certificate, proxy).map(|client| TokenSource::Refresh {
+                            client,
+                            url: clone_and_sub(access_token_url, &subs),
+                            client_id: clone_and_sub(client_id, &subs),
+                            client_secret: String::default(),
+                        })
+                    }
+                    _ => return None,
+                };
+                Some(TokenRenewal {
+                    name: auth.get_name().to_string(),
+                    authorization_id: id,
+                    source,
+                })
+            })
+            .collect()
+    }
+
     /// Return a list of any Data Sets used by the specified request or decendants
     pub fn get_request_data_set_ids(
         &self,
@@ -3794,6 +5912,7 @@ pub enum EntityType {
     Proxy = 9,
     DataSet = 10,
     Defaults = 11,
+    TrustedRoots = 12,
 }
 
 impl EntityType {
@@ -3808,6 +5927,7 @@ impl EntityType {
             EntityType::Proxy => "Proxy",
             EntityType::DataSet => "Data",
             EntityType::Defaults => "Defaults",
+            EntityType::TrustedRoots => "TrustedRoots",
         }
     }
 }
@@ -3830,6 +5950,7 @@ pub enum Entity {
     Proxy(Proxy),
     DataSet(DataSet),
     Defaults(WorkbookDefaultParameters),
+    TrustedRoots(TrustedRoots),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
@@ -3839,6 +5960,8 @@ pub struct WorkspaceParameters {
     pub certificates: Vec<Selection>,
     pub proxies: Vec<Selection>,
     pub data: Vec<Selection>,
+    #[serde(rename = "trustedRoots")]
+    pub trusted_roots: Vec<Selection>,
 }
 
 pub struct OpenWorkspaceResult {
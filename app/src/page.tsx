@@ -156,8 +156,23 @@ const workspaceStore = new WorkspaceStore(
       'clear_cached_authorization', { authorizationId }),
     clearAllTokens: () => core.invoke(
       'clear_all_cached_authorizations'),
-    startExecution: async (requestOrGroupId: string, workbookFullName: string, singleRun: boolean) =>
-      core.invoke<{ [executingRequestOrGroupId: string]: undefined }>('start_execution', { sessionId, requestOrGroupId, workbookFullName, singleRun }),
+    startExecution: async (requestOrGroupId: string, workbookFullName: string, singleRun: boolean) => {
+      // Secret variable commands only run once the user has trusted them for the workbook
+      const untrusted = await core.invoke<string[]>('get_untrusted_secret_commands', { sessionId })
+      if (untrusted.length > 0) {
+        if (! await feedbackStore.confirm({
+          title: 'Run Secret Variable Commands',
+          message: `This workbook's secret variables run the following commands on your computer. Only continue if you trust them.\n\n${untrusted.join('\n')}`,
+          okButton: 'Trust and Run',
+          cancelButton: 'Cancel',
+          defaultToCancel: true
+        })) {
+          return
+        }
+        await core.invoke('trust_secret_commands', { sessionId })
+      }
+      return core.invoke<{ [executingRequestOrGroupId: string]: undefined }>('start_execution', { sessionId, requestOrGroupId, workbookFullName, singleRun })
+    },
     cancelExecution: (requestOrGroupId) => core.invoke(
       'cancel_execution', { sessionId, requestOrGroupId }),
     clearExecution: (requestOrGroupId) => core.invoke(
@@ -187,6 +187,57 @@ export function FileOperationsProvider(
         return saveStatus
     }
 
+    /**
+     * Saves the workspace, confirming with the user if likely secrets would be saved
+     * to the public workbook or if changes made outside of Apicize would be overwritten
+     * @returns true if saved
+     */
+    const invokeSave = async (fileName?: string, format?: WorkbookFormat) => {
+        let allowSecrets = false
+        let overwrite = false
+        for (; ;) {
+            const response = await core.invoke<SaveWorkspaceResponse>('save_workspace', {
+                sessionId: activeSessionId,
+                fileName,
+                allowSecrets,
+                overwrite,
+                format,
+            })
+            if (response.changedOnDisk) {
+                if (! await feedback.confirm({
+                    title: 'Save Workbook',
+                    message: 'The workbook has been changed outside of Apicize since it was opened or saved. Are you sure you want to overwrite those changes?',
+                    okButton: 'Yes',
+                    cancelButton: 'No',
+                    defaultToCancel: true
+                })) {
+                    return false
+                }
+                overwrite = true
+                continue
+            }
+
+            const findings = response.secretFindings
+            if (findings.length === 0) {
+                return true
+            }
+            const list = findings.slice(0, 10)
+                .map(f => `${f.entityName} (${f.field}): ${f.excerpt}`)
+                .join('\n')
+            const more = findings.length > 10 ? `\n...and ${findings.length - 10} more` : ''
+            if (! await feedback.confirm({
+                title: 'Save Workbook',
+                message: `The following values look like secrets and will be included if you share the workbook; consider moving them to private or vault parameters. Are you sure you want to save?\n\n${list}${more}`,
+                okButton: 'Yes',
+                cancelButton: 'No',
+                defaultToCancel: true
+            })) {
+                return false
+            }
+            allowSecrets = true
+        }
+    }
+
     /**
      * Saves the current worspake under its current name
      * @returns 
@@ -196,9 +247,9 @@ export function FileOperationsProvider(
             if (! await checkWorkspaceStatus()) {
                 return
             }
-            await core.invoke('save_workspace', {
-                sessionId: activeSessionId
-            })
+            if (! await invokeSave()) {
+                return
+            }
             feedback.toast('Workbook saved', ToastSeverity.Success)
         } catch (e) {
             feedback.toastError(e)
@@ -236,10 +287,17 @@ export function FileOperationsProvider(
                 fileName += `.${EXT}`
             }
 
-            await core.invoke('save_workspace', {
-                sessionId: activeSessionId,
-                fileName,
-            })
+            const format = await feedback.confirm({
+                title: 'Save Workbook',
+                message: 'Do you want to save the workbook as a single file, or as a directory with a file for each request, group and parameter? Directories merge more easily in source control.',
+                okButton: 'Directory',
+                cancelButton: 'Single File',
+                defaultToCancel: true
+            }) ? WorkbookFormat.Directory : WorkbookFormat.File
+
+            if (! await invokeSave(fileName, format)) {
+                return
+            }
             feedback.toast('Workbook saved', ToastSeverity.Success)
         } catch (e) {
             feedback.toastError(e)
@@ -527,4 +585,23 @@ export interface WorkspaceSaveStatus {
     anyInvalid: boolean
     fileName: string
     displayName: string
-}
\ No newline at end of file
+}
+
+export enum WorkbookFormat {
+    File = 'file',
+    Directory = 'directory',
+}
+
+export interface SaveWorkspaceResponse {
+    secretFindings: SecretFinding[]
+    changedOnDisk: boolean
+}
+
+export interface SecretFinding {
+    entityId: string
+    entityType: number
+    entityName: string
+    field: string
+    kind: string
+    excerpt: string
+}
@@ -117,6 +117,11 @@ export const OAuth2Provider = observer(({ store, children }: { store: WorkspaceS
 
                 new_window.once('tauri://destroyed', function () {
                     workspace.cancelPendingPkceAuthorization(auth.id)
+                    // Stop waiting for a redirect if the window is closed before the flow completes
+                    if (wip.current.get(auth.id)?.csrfToken === info.csrfToken) {
+                        wip.current.delete(auth.id)
+                        core.invoke<boolean>('cancel_pkce_flow', { csrfToken: info.csrfToken }).catch(console.error)
+                    }
                 }).catch(console.error)
 
                 wip.current.set(auth.id, info)
@@ -206,8 +211,18 @@ export const OAuth2Provider = observer(({ store, children }: { store: WorkspaceS
         const unlistenPkceSuccess = listen<string>('oauth2-pkce-success', (_event) => {
             // feedback.toast(event.payload, ToastSeverity.Success)
         })
-        const unlistenPkceError = listen<string>('oauth2-pkce-error', (event) => {
-            feedback.toast(event.payload, ToastSeverity.Error)
+        const unlistenPkceError = listen<OAuthPkceFlowError>('oauth2-pkce-error', (event) => {
+            const { state, error } = event.payload
+            if (state) {
+                for (const [id, entry] of wip.current) {
+                    if (entry.csrfToken === state) {
+                        wip.current.delete(id)
+                        workspace.cancelPendingPkceAuthorization(id)
+                        break
+                    }
+                }
+            }
+            feedback.toast(error, ToastSeverity.Error)
         })
 
         const checkPortUpdate = (newPort: number) => {
@@ -268,6 +283,11 @@ interface OAuthPkceAuthParams {
     state: string,
 }
 
+interface OAuthPkceFlowError {
+    state?: string,
+    error: string,
+}
+
 interface OAuthPkceAuthResponse {
     accessToken: string,
     refreshToken?: string,